@@ -7,6 +7,7 @@ extern crate terminus_spaceport;
 
 use rand::Rng;
 use std::ops::Deref;
+use terminus_spaceport::memory::allocator::Allocator;
 use terminus_spaceport::memory::region::*;
 use terminus_spaceport::space::Space;
 
@@ -88,3 +89,17 @@ fn bench_space_access(b: &mut Bencher) {
         jemalloc_sys::malloc_stats_print(None, null_mut(), null())
     };
 }
+
+#[bench]
+fn bench_allocator(b: &mut Bencher) {
+    let mut allocator = Allocator::new(0, 0x1_0000_0000);
+    let mut rng = rand::thread_rng();
+    let mut blocks = (0..4096)
+        .map(|_| allocator.alloc(rng.gen_range(1, 0x100), 8).unwrap().base)
+        .collect::<Vec<_>>();
+    b.iter(|| {
+        let i = rng.gen_range(0, blocks.len());
+        allocator.free(blocks[i]).unwrap();
+        blocks[i] = allocator.alloc(rng.gen_range(1, 0x100), 8).unwrap().base;
+    });
+}
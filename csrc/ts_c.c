@@ -3,53 +3,229 @@
 #include <ts_c.h>
 
 void* tsc_new_allocator(const uint64_t base, const uint64_t size){
-    return __ts_new_allocator(base, size);
+    return __ts_new_allocator(base, size, TS_FIRST_FIT);
 }
 
 void* tsc_new_locked_allocator(const uint64_t base, const uint64_t size) {
-    return __ts_new_locked_allocator(base, size);
+    return __ts_new_locked_allocator(base, size, TS_FIRST_FIT);
+}
+
+void* tsc_new_policy_allocator(const uint64_t base, const uint64_t size, const uint32_t policy) {
+    return __ts_new_allocator(base, size, policy);
+}
+
+void* tsc_new_policy_locked_allocator(const uint64_t base, const uint64_t size, const uint32_t policy) {
+    return __ts_new_locked_allocator(base, size, policy);
+}
+
+void* tsc_new_pool_allocator() {
+    return __ts_new_pool_allocator();
+}
+
+int32_t tsc_add_pool(const void* allocator, const char* name, const uint64_t base, const uint64_t size, const bool dma, const bool cacheable, const uint32_t policy) {
+    return __ts_add_pool(allocator, name, base, size, dma, cacheable, policy);
+}
+
+uint64_t tsc_alloc_pool_addr(const void* allocator, const uint64_t size, const uint64_t align, const uint64_t limit, const int32_t dma, const int32_t cacheable, const char* pool) {
+    return __ts_alloc_pool_addr(allocator, size, align, limit, dma, cacheable, pool);
 }
 
 uint64_t tsc_alloc_addr(const void* allocator, const uint64_t size, const uint64_t align) {
     return __ts_alloc_addr(allocator, size, align);
 }
 
-void tsc_free_addr(const void* allocator, const uint64_t addr) {
-    __ts_free_addr(allocator, addr);
+int32_t tsc_free_addr(const void* allocator, const uint64_t addr) {
+    return __ts_free_addr(allocator, addr);
+}
+
+void tsc_allocator_set_strict(const void* allocator, bool strict) {
+    __ts_allocator_set_strict(allocator, strict);
+}
+
+void tsc_allocator_set_seed(const void* allocator, const uint64_t seed) {
+    __ts_allocator_set_seed(allocator, seed);
+}
+
+void tsc_allocator_save(const void* allocator, const char* path) {
+    __ts_allocator_save(allocator, path);
+}
+
+void* tsc_allocator_restore(const char* path, const bool locked) {
+    return __ts_allocator_restore(path, locked);
+}
+
+void tsc_allocator_start_log(const void* allocator, const char* path) {
+    __ts_allocator_start_log(allocator, path);
+}
+
+void tsc_allocator_stop_log(const void* allocator) {
+    __ts_allocator_stop_log(allocator);
+}
+
+int32_t tsc_realloc_addr(const void* allocator, const uint64_t addr, const uint64_t size, const uint64_t align, uint64_t* new_addr) {
+    return __ts_realloc_addr(allocator, addr, size, align, new_addr);
+}
+
+int32_t tsc_alloc_addr_at(const void* allocator, const uint64_t base, const uint64_t size) {
+    return __ts_alloc_addr_at(allocator, base, size);
+}
+
+int32_t tsc_reserve_addr(const void* allocator, const uint64_t base, const uint64_t size) {
+    return __ts_reserve_addr(allocator, base, size);
+}
+
+void tsc_allocator_stats(const void* allocator, ts_alloc_stats* stats) {
+    __ts_allocator_stats(allocator, stats);
+}
+
+void tsc_allocator_report(const void* allocator) {
+    __ts_allocator_report(allocator);
 }
 
 void* tsc_space() {
     return __ts_space();
 }
 
+void tsc_free_space(void* space) {
+    __ts_free_space(space);
+}
+
 void tsc_delete_region(const void* space, const char* name) {
     __ts_delete_region(space, name);
 }
 
 void* tsc_get_region(const void* space, const char* name) {
     void* ptr = __ts_get_region(space, name);
-    __ts_clean_region(space, name, ptr);
+    __ts_track_region(space, name, ptr);
     return ptr;
 }
 
 void* tsc_add_region(const void* space, const char* name, void* region) {
     void* ptr = __ts_add_region(space,name, region);
-    __ts_clean_region(space,name,ptr);
+    __ts_track_region(space,name,ptr);
     __ts_clean_region(space,name,region);
     return ptr;
 }
 
+void* tsc_replace_region(const void* space, const char* name, void* region) {
+    void* ptr = __ts_replace_region(space, name, region);
+    __ts_track_region(space,name,ptr);
+    __ts_clean_region(space,name,region);
+    return ptr;
+}
+
+void* tsc_move_region(const void* space, const char* name, const uint64_t base) {
+    void* ptr = __ts_move_region(space, name, base);
+    __ts_track_region(space,name,ptr);
+    return ptr;
+}
+
+uint64_t tsc_space_region_count(const void* space) {
+    return __ts_space_region_count(space);
+}
+
+const char* tsc_space_region_name(const void* space, const uint64_t idx) {
+    return __ts_space_region_name(space, idx);
+}
+
+ts_mem_info* tsc_space_region_info(const void* space, const uint64_t idx) {
+    return (ts_mem_info*)__ts_space_region_info(space, idx);
+}
+
+uint64_t tsc_space_free_gap(const void* space, const uint64_t size, const uint64_t align) {
+    return __ts_space_free_gap(space, size, align);
+}
+
+void tsc_space_export_json(const void* space, const char* path) {
+    __ts_space_export_json(space, path);
+}
+
+int64_t tsc_space_import_regmap(const void* space, const char* path, uint32_t format) {
+    return __ts_space_import_regmap(space, path, format);
+}
+
+void tsc_space_export_csv(const void* space, const char* path) {
+    __ts_space_export_csv(space, path);
+}
+
+void* tsc_add_region_with_attr(const void* space, const char* name, void* region, bool readable, bool writable) {
+    void* ptr = __ts_add_region_with_attr(space, name, region, readable, writable);
+    __ts_track_region(space,name,ptr);
+    __ts_clean_region(space,name,region);
+    return ptr;
+}
+
+void* tsc_views() {
+    return __ts_views();
+}
+
+void* tsc_add_view(const void* views, const char* name) {
+    return __ts_add_view(views, name);
+}
+
+void* tsc_get_view(const void* views, const char* name) {
+    return __ts_get_view(views, name);
+}
+
+void tsc_views_trace(const void* views, bool enable) {
+    __ts_views_trace(views, enable);
+}
+
+const char* tsc_views_issuer(const void* views, const uint64_t addr) {
+    return __ts_views_issuer(views, addr);
+}
+
 void* tsc_alloc_region(void* heap, uint64_t size, uint64_t align) {
     assert(heap != NULL);
-    return __ts_alloc_region(heap, size, align, false);
+    return __ts_alloc_region(heap, size, align, TS_STORAGE_HEAP);
+}
+
+void* tsc_alloc_storage_region(void* heap, uint64_t size, uint64_t align, uint32_t storage) {
+    assert(heap != NULL);
+    return __ts_alloc_region(heap, size, align, storage);
 }
 
 void* tsc_root_region(uint64_t size, uint64_t align) {
-    return __ts_alloc_region(NULL, size, align, false);
+    return __ts_alloc_region(NULL, size, align, TS_STORAGE_MODEL);
 }
 
 void* tsc_lazy_root_region(uint64_t size, uint64_t align) {
-    return __ts_alloc_region(NULL, size, align, true);
+    return __ts_alloc_region(NULL, size, align, TS_STORAGE_LAZY_MODEL);
+}
+
+void* tsc_root_storage_region(uint64_t size, uint64_t align, uint32_t storage) {
+    return __ts_alloc_region(NULL, size, align, storage);
+}
+
+void* tsc_alloc_region_tagged(void* heap, uint64_t size, uint64_t align, const char* tag, const char* file, const uint32_t line) {
+    assert(heap != NULL);
+    return __ts_alloc_region_tagged(heap, size, align, TS_STORAGE_HEAP, tag, file, line);
+}
+
+void* tsc_root_region_tagged(uint64_t size, uint64_t align, const char* tag, const char* file, const uint32_t line) {
+    return __ts_alloc_region_tagged(NULL, size, align, TS_STORAGE_MODEL, tag, file, line);
+}
+
+uint64_t tsc_heap_leak_report(const void* heap) {
+    return __ts_heap_leak_report(heap);
+}
+
+int32_t tsc_realloc_region(const void* heap, void* region, uint64_t size, uint64_t align) {
+    assert(heap != NULL);
+    return __ts_realloc_region(heap, region, size, align);
+}
+
+void* tsc_alloc_region_at(void* heap, uint64_t base, uint64_t size) {
+    assert(heap != NULL);
+    return __ts_alloc_region_at(heap, base, size);
+}
+
+void* tsc_root_region_at(uint64_t base, uint64_t size) {
+    return __ts_alloc_region_at(NULL, base, size);
+}
+
+void tsc_heap_reserve(const void* heap, uint64_t base, uint64_t size) {
+    __ts_heap_reserve(heap, base, size);
 }
 
 void* tsc_map_region(const void* region, uint64_t base) {
@@ -72,6 +248,30 @@ void tsc_free_heap(const void* heap) {
     __ts_free_heap(heap);
 }
 
+void* tsc_scratch_heap(uint64_t size) {
+    return __ts_scratch_heap(size);
+}
+
+void tsc_heap_teardown(const void* heap) {
+    __ts_heap_teardown(heap);
+}
+
+void tsc_heap_reset(const void* heap) {
+    __ts_heap_reset(heap);
+}
+
+int32_t tsc_heaps_reset() {
+    return __ts_heaps_reset();
+}
+
+void tsc_heap_stats(const void* heap, ts_alloc_stats* stats) {
+    __ts_heap_stats(heap, stats);
+}
+
+void tsc_heap_report(const void* heap) {
+    __ts_heap_report(heap);
+}
+
 void tsc_region_write_u8(const void* region, const uint64_t addr, const uint8_t data) {
     __ts_region_write_u8(region, addr, data);
 }
@@ -130,6 +330,106 @@ uint64_t tsc_space_read_u64(const void* space, const uint64_t addr){
     return __ts_space_read_u64(space, addr);
 }
 
+void tsc_space_set_latency(const void* space, const char* name, const uint64_t read, const uint64_t write) {
+    __ts_space_set_latency(space, name, read, write);
+}
+
+uint64_t tsc_space_request_read(const void* space, const uint64_t addr, const uint32_t size) {
+    return __ts_space_request_read(space, addr, size);
+}
+
+uint64_t tsc_space_request_write(const void* space, const uint64_t addr, const uint64_t data, const uint32_t size) {
+    return __ts_space_request_write(space, addr, data, size);
+}
+
+void tsc_space_tick(const void* space, const uint64_t ticks) {
+    __ts_space_tick(space, ticks);
+}
+
+uint64_t tsc_space_attach(const void* space) {
+    return __ts_space_attach(space);
+}
+
+bool tsc_space_complete(const void* space, const uint64_t token) {
+    return __ts_space_complete(space, token);
+}
+
+bool tsc_space_poll(const void* space, const uint64_t token, uint64_t* data) {
+    return __ts_space_poll(space, token, data);
+}
+
+void* tsc_perf_model(const uint64_t latency, const uint64_t bandwidth) {
+    return __ts_perf_model(latency, bandwidth);
+}
+
+void tsc_perf_set_timing(const void* perf, const char* name, const uint64_t latency, const uint64_t bandwidth) {
+    __ts_perf_set_timing(perf, name, latency, bandwidth);
+}
+
+void tsc_space_set_perf(const void* space, const void* perf) {
+    __ts_space_set_perf(space, perf);
+}
+
+uint64_t tsc_perf_cycles(const void* perf) {
+    return __ts_perf_cycles(perf);
+}
+
+uint64_t tsc_perf_accesses(const void* perf, const char* name) {
+    return __ts_perf_accesses(perf, name);
+}
+
+void tsc_perf_reset(const void* perf) {
+    __ts_perf_reset(perf);
+}
+
+void tsc_perf_report(const void* perf) {
+    __ts_perf_report(perf);
+}
+
+void* tsc_cache(const uint64_t size, const uint64_t ways, const uint64_t line, const uint32_t replace, const uint32_t write, const bool buffered) {
+    return __ts_cache(size, ways, line, replace, write, buffered);
+}
+
+void tsc_space_set_cache(const void* space, const void* cache) {
+    __ts_space_set_cache(space, cache);
+}
+
+void tsc_cache_stats(const void* cache, ts_cache_stats* stats) {
+    __ts_cache_stats(cache, stats);
+}
+
+void tsc_cache_clean(const void* cache, const uint64_t addr, const uint64_t size) {
+    __ts_cache_clean(cache, addr, size);
+}
+
+void tsc_cache_invalidate(const void* cache, const uint64_t addr, const uint64_t size) {
+    __ts_cache_invalidate(cache, addr, size);
+}
+
+void tsc_cache_flush(const void* cache, const uint64_t addr, const uint64_t size) {
+    __ts_cache_flush(cache, addr, size);
+}
+
+void tsc_tick(const uint64_t cycles) {
+    __ts_tick(cycles);
+}
+
+uint64_t tsc_now() {
+    return __ts_now();
+}
+
+uint64_t tsc_schedule(const uint64_t delay, ts_callback cb, void* arg) {
+    return __ts_schedule(delay, cb, arg);
+}
+
+uint64_t tsc_schedule_every(const uint64_t period, ts_callback cb, void* arg) {
+    return __ts_schedule_every(period, cb, arg);
+}
+
+bool tsc_cancel(const uint64_t id) {
+    return __ts_cancel(id);
+}
+
 ts_mem_info* tsc_region_info(const void* region){
     return (ts_mem_info*)__ts_region_info(region);
 }
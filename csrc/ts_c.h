@@ -4,23 +4,71 @@
 
 void* tsc_new_allocator(const uint64_t base, const uint64_t size);
 void* tsc_new_locked_allocator(const uint64_t base, const uint64_t size);
+void* tsc_new_policy_allocator(const uint64_t base, const uint64_t size, const uint32_t policy);
+void* tsc_new_policy_locked_allocator(const uint64_t base, const uint64_t size, const uint32_t policy);
+void* tsc_new_pool_allocator();
+int32_t tsc_add_pool(const void* allocator, const char* name, const uint64_t base, const uint64_t size, const bool dma, const bool cacheable, const uint32_t policy);
+uint64_t tsc_alloc_pool_addr(const void* allocator, const uint64_t size, const uint64_t align, const uint64_t limit, const int32_t dma, const int32_t cacheable, const char* pool);
 uint64_t tsc_alloc_addr(const void* allocator, const uint64_t size, const uint64_t align);
-void tsc_free_addr(const void* allocator, const uint64_t addr);
+int32_t tsc_free_addr(const void* allocator, const uint64_t addr);
+void tsc_allocator_set_strict(const void* allocator, bool strict);
+void tsc_allocator_set_seed(const void* allocator, const uint64_t seed);
+void tsc_allocator_save(const void* allocator, const char* path);
+void* tsc_allocator_restore(const char* path, const bool locked);
+void tsc_allocator_start_log(const void* allocator, const char* path);
+void tsc_allocator_stop_log(const void* allocator);
+int32_t tsc_realloc_addr(const void* allocator, const uint64_t addr, const uint64_t size, const uint64_t align, uint64_t* new_addr);
+int32_t tsc_alloc_addr_at(const void* allocator, const uint64_t base, const uint64_t size);
+int32_t tsc_reserve_addr(const void* allocator, const uint64_t base, const uint64_t size);
+void tsc_allocator_stats(const void* allocator, ts_alloc_stats* stats);
+void tsc_allocator_report(const void* allocator);
 
 void* tsc_space();
+void tsc_free_space(void* space);
 void tsc_delete_region(const void* space, const char* name);
 void* tsc_get_region(const void* space, const char* name);
 void* tsc_add_region(const void* space, const char* name, void* region);
+void* tsc_replace_region(const void* space, const char* name, void* region);
+void* tsc_move_region(const void* space, const char* name, const uint64_t base);
+uint64_t tsc_space_region_count(const void* space);
+const char* tsc_space_region_name(const void* space, const uint64_t idx);
+ts_mem_info* tsc_space_region_info(const void* space, const uint64_t idx);
+uint64_t tsc_space_free_gap(const void* space, const uint64_t size, const uint64_t align);
+void tsc_space_export_json(const void* space, const char* path);
+int64_t tsc_space_import_regmap(const void* space, const char* path, uint32_t format);
+void tsc_space_export_csv(const void* space, const char* path);
+void* tsc_add_region_with_attr(const void* space, const char* name, void* region, bool readable, bool writable);
+
+void* tsc_views();
+void* tsc_add_view(const void* views, const char* name);
+void* tsc_get_view(const void* views, const char* name);
+void tsc_views_trace(const void* views, bool enable);
+const char* tsc_views_issuer(const void* views, const uint64_t addr);
 
 void* tsc_alloc_region(void* heap, uint64_t size, uint64_t align);
+void* tsc_alloc_storage_region(void* heap, uint64_t size, uint64_t align, uint32_t storage);
 void* tsc_root_region(uint64_t size, uint64_t align);
 void* tsc_lazy_root_region(uint64_t size, uint64_t align);
+void* tsc_root_storage_region(uint64_t size, uint64_t align, uint32_t storage);
+void* tsc_alloc_region_tagged(void* heap, uint64_t size, uint64_t align, const char* tag, const char* file, const uint32_t line);
+void* tsc_root_region_tagged(uint64_t size, uint64_t align, const char* tag, const char* file, const uint32_t line);
+uint64_t tsc_heap_leak_report(const void* heap);
+int32_t tsc_realloc_region(const void* heap, void* region, uint64_t size, uint64_t align);
+void* tsc_alloc_region_at(void* heap, uint64_t base, uint64_t size);
+void* tsc_root_region_at(uint64_t base, uint64_t size);
+void tsc_heap_reserve(const void* heap, uint64_t base, uint64_t size);
 
 void* tsc_map_region(const void* region, uint64_t base);
 void* tsc_map_region_partial(const void* region, uint64_t base, uint64_t offset, uint64_t size);
 void* tsc_heap(const void* region);
 void tsc_free_region(const void* region);
 void tsc_free_heap(const void* heap);
+void* tsc_scratch_heap(uint64_t size);
+void tsc_heap_teardown(const void* heap);
+void tsc_heap_reset(const void* heap);
+int32_t tsc_heaps_reset();
+void tsc_heap_stats(const void* heap, ts_alloc_stats* stats);
+void tsc_heap_report(const void* heap);
 
 void tsc_region_write_u8(const void* region, const uint64_t addr, const uint8_t data);
 void tsc_region_write_u16(const void* region, const uint64_t addr, const uint16_t data);
@@ -40,6 +88,35 @@ uint16_t tsc_space_read_u16(const void* space, const uint64_t addr);
 uint32_t tsc_space_read_u32(const void* space, const uint64_t addr);
 uint64_t tsc_space_read_u64(const void* space, const uint64_t addr);
 
+void tsc_space_set_latency(const void* space, const char* name, const uint64_t read, const uint64_t write);
+uint64_t tsc_space_request_read(const void* space, const uint64_t addr, const uint32_t size);
+uint64_t tsc_space_request_write(const void* space, const uint64_t addr, const uint64_t data, const uint32_t size);
+void tsc_space_tick(const void* space, const uint64_t ticks);
+uint64_t tsc_space_attach(const void* space);
+bool tsc_space_complete(const void* space, const uint64_t token);
+bool tsc_space_poll(const void* space, const uint64_t token, uint64_t* data);
+
+void* tsc_perf_model(const uint64_t latency, const uint64_t bandwidth);
+void tsc_perf_set_timing(const void* perf, const char* name, const uint64_t latency, const uint64_t bandwidth);
+void tsc_space_set_perf(const void* space, const void* perf);
+uint64_t tsc_perf_cycles(const void* perf);
+uint64_t tsc_perf_accesses(const void* perf, const char* name);
+void tsc_perf_reset(const void* perf);
+void tsc_perf_report(const void* perf);
+
+void* tsc_cache(const uint64_t size, const uint64_t ways, const uint64_t line, const uint32_t replace, const uint32_t write, const bool buffered);
+void tsc_space_set_cache(const void* space, const void* cache);
+void tsc_cache_stats(const void* cache, ts_cache_stats* stats);
+void tsc_cache_clean(const void* cache, const uint64_t addr, const uint64_t size);
+void tsc_cache_invalidate(const void* cache, const uint64_t addr, const uint64_t size);
+void tsc_cache_flush(const void* cache, const uint64_t addr, const uint64_t size);
+
+void tsc_tick(const uint64_t cycles);
+uint64_t tsc_now();
+uint64_t tsc_schedule(const uint64_t delay, ts_callback cb, void* arg);
+uint64_t tsc_schedule_every(const uint64_t period, ts_callback cb, void* arg);
+bool tsc_cancel(const uint64_t id);
+
 
 ts_mem_info* tsc_region_info(const void* region);
 
@@ -1,55 +1,273 @@
 #ifndef __TS_DPI_C__
 #define __TS_DPI_C__
 #include <ts_dpi.h>
+#include <stdlib.h>
+
+//TS_ALLOC_SEED=<seed> only seeds the allocators, random placement needs TS_RANDOM from tsv_new_policy_allocator
+static void* tsv_seeded_allocator(void* allocator) {
+    const char* seed = getenv("TS_ALLOC_SEED");
+    if (seed != NULL) {
+        __ts_allocator_set_seed(allocator, strtoull(seed, NULL, 0));
+    }
+    return allocator;
+}
 
 void* tsv_new_allocator(const uint64_t base, const uint64_t size){
-    return __ts_new_allocator(base, size);
+    return tsv_seeded_allocator(__ts_new_allocator(base, size, TS_FIRST_FIT));
 }
 
 void* tsv_new_locked_allocator(const uint64_t base, const uint64_t size) {
-    return __ts_new_locked_allocator(base, size);
+    return tsv_seeded_allocator(__ts_new_locked_allocator(base, size, TS_FIRST_FIT));
+}
+
+void* tsv_new_policy_allocator(const uint64_t base, const uint64_t size, const uint32_t policy) {
+    return tsv_seeded_allocator(__ts_new_allocator(base, size, policy));
+}
+
+void* tsv_new_policy_locked_allocator(const uint64_t base, const uint64_t size, const uint32_t policy) {
+    return tsv_seeded_allocator(__ts_new_locked_allocator(base, size, policy));
+}
+
+void* tsv_new_pool_allocator() {
+    return __ts_new_pool_allocator();
+}
+
+int32_t tsv_add_pool(const void* allocator, const char* name, const uint64_t base, const uint64_t size, const bool dma, const bool cacheable, const uint32_t policy) {
+    return __ts_add_pool(allocator, name, base, size, dma, cacheable, policy);
+}
+
+uint64_t tsv_alloc_pool_addr(const void* allocator, const uint64_t size, const uint64_t align, const uint64_t limit, const int32_t dma, const int32_t cacheable, const char* pool) {
+    //"" from sv means any pool
+    return __ts_alloc_pool_addr(allocator, size, align, limit, dma, cacheable, pool[0] ? pool : NULL);
 }
 
 uint64_t tsv_alloc_addr(const void* allocator, const uint64_t size, const uint64_t align) {
     return __ts_alloc_addr(allocator, size, align);
 }
 
-void tsv_free_addr(const void* allocator, const uint64_t addr) {
-    __ts_free_addr(allocator, addr);
+int32_t tsv_free_addr(const void* allocator, const uint64_t addr) {
+    return __ts_free_addr(allocator, addr);
+}
+
+void tsv_allocator_set_strict(const void* allocator, bool strict) {
+    __ts_allocator_set_strict(allocator, strict);
+}
+
+void tsv_allocator_set_seed(const void* allocator, const uint64_t seed) {
+    __ts_allocator_set_seed(allocator, seed);
+}
+
+void tsv_allocator_save(const void* allocator, const char* path) {
+    __ts_allocator_save(allocator, path);
+}
+
+void* tsv_allocator_restore(const char* path, const bool locked) {
+    return __ts_allocator_restore(path, locked);
+}
+
+void tsv_allocator_start_log(const void* allocator, const char* path) {
+    __ts_allocator_start_log(allocator, path);
+}
+
+void tsv_allocator_stop_log(const void* allocator) {
+    __ts_allocator_stop_log(allocator);
+}
+
+int32_t tsv_realloc_addr(const void* allocator, const uint64_t addr, const uint64_t size, const uint64_t align, uint64_t* new_addr) {
+    return __ts_realloc_addr(allocator, addr, size, align, new_addr);
+}
+
+int32_t tsv_alloc_addr_at(const void* allocator, const uint64_t base, const uint64_t size) {
+    return __ts_alloc_addr_at(allocator, base, size);
+}
+
+int32_t tsv_reserve_addr(const void* allocator, const uint64_t base, const uint64_t size) {
+    return __ts_reserve_addr(allocator, base, size);
+}
+
+uint64_t tsv_allocator_total_free(const void* allocator) {
+    ts_alloc_stats stats;
+    __ts_allocator_stats(allocator, &stats);
+    return stats.total_free;
+}
+
+uint64_t tsv_allocator_largest_free(const void* allocator) {
+    ts_alloc_stats stats;
+    __ts_allocator_stats(allocator, &stats);
+    return stats.largest_free;
+}
+
+uint64_t tsv_allocator_fragments(const void* allocator) {
+    ts_alloc_stats stats;
+    __ts_allocator_stats(allocator, &stats);
+    return stats.fragments;
+}
+
+uint64_t tsv_allocator_high_water(const void* allocator) {
+    ts_alloc_stats stats;
+    __ts_allocator_stats(allocator, &stats);
+    return stats.high_water;
+}
+
+uint64_t tsv_allocator_live(const void* allocator) {
+    ts_alloc_stats stats;
+    __ts_allocator_stats(allocator, &stats);
+    return stats.live;
+}
+
+void tsv_allocator_report(const void* allocator) {
+    __ts_allocator_report(allocator);
 }
 
 void* tsv_space() {
     return __ts_space();
 }
 
+void tsv_free_space(void* space) {
+    __ts_free_space(space);
+}
+
 void tsv_delete_region(const void* space, const char* name) {
     __ts_delete_region(space, name);
 }
 
 void* tsv_get_region(const void* space, const char* name) {
     void* ptr = __ts_get_region(space, name);
-    __ts_clean_region(space, name, ptr);
+    __ts_track_region(space, name, ptr);
     return ptr;
 }
 
 void* tsv_add_region(const void* space, const char* name, void* region) {
     void* ptr = __ts_add_region(space,name, region);
-    __ts_clean_region(space,name,ptr);
+    __ts_track_region(space,name,ptr);
+    __ts_clean_region(space,name,region);
+    return ptr;
+}
+
+void* tsv_replace_region(const void* space, const char* name, void* region) {
+    void* ptr = __ts_replace_region(space, name, region);
+    __ts_track_region(space,name,ptr);
     __ts_clean_region(space,name,region);
     return ptr;
 }
 
+void* tsv_move_region(const void* space, const char* name, const uint64_t base) {
+    void* ptr = __ts_move_region(space, name, base);
+    __ts_track_region(space,name,ptr);
+    return ptr;
+}
+
+uint64_t tsv_space_region_count(const void* space) {
+    return __ts_space_region_count(space);
+}
+
+const char* tsv_space_region_name(const void* space, const uint64_t idx) {
+    return __ts_space_region_name(space, idx);
+}
+
+uint64_t tsv_space_region_base(const void* space, const uint64_t idx) {
+    return ((ts_mem_info*)__ts_space_region_info(space, idx))->base;
+}
+
+uint64_t tsv_space_region_size(const void* space, const uint64_t idx) {
+    return ((ts_mem_info*)__ts_space_region_info(space, idx))->size;
+}
+
+uint64_t tsv_space_free_gap(const void* space, const uint64_t size, const uint64_t align) {
+    return __ts_space_free_gap(space, size, align);
+}
+
+void tsv_space_export_json(const void* space, const char* path) {
+    __ts_space_export_json(space, path);
+}
+
+int64_t tsv_space_import_regmap(const void* space, const char* path, uint32_t format) {
+    return __ts_space_import_regmap(space, path, format);
+}
+
+void tsv_space_export_csv(const void* space, const char* path) {
+    __ts_space_export_csv(space, path);
+}
+
+void* tsv_add_region_with_attr(const void* space, const char* name, void* region, bool readable, bool writable) {
+    void* ptr = __ts_add_region_with_attr(space, name, region, readable, writable);
+    __ts_track_region(space,name,ptr);
+    __ts_clean_region(space,name,region);
+    return ptr;
+}
+
+void* tsv_views() {
+    return __ts_views();
+}
+
+void* tsv_add_view(const void* views, const char* name) {
+    return __ts_add_view(views, name);
+}
+
+void* tsv_get_view(const void* views, const char* name) {
+    return __ts_get_view(views, name);
+}
+
+void tsv_views_trace(const void* views, bool enable) {
+    __ts_views_trace(views, enable);
+}
+
+const char* tsv_views_issuer(const void* views, const uint64_t addr) {
+    const char* name = __ts_views_issuer(views, addr);
+    return name == NULL ? "" : name;
+}
+
 void* tsv_alloc_region(void* heap, uint64_t size, uint64_t align) {
     assert(heap != NULL);
-    return __ts_alloc_region(heap, size, align, false);
+    return __ts_alloc_region(heap, size, align, TS_STORAGE_HEAP);
+}
+
+void* tsv_alloc_storage_region(void* heap, uint64_t size, uint64_t align, uint32_t storage) {
+    assert(heap != NULL);
+    return __ts_alloc_region(heap, size, align, storage);
 }
 
 void* tsv_root_region(uint64_t size, uint64_t align) {
-    return __ts_alloc_region(NULL, size, align, false);
+    return __ts_alloc_region(NULL, size, align, TS_STORAGE_MODEL);
 }
 
 void* tsv_lazy_root_region(uint64_t size, uint64_t align) {
-    return __ts_alloc_region(NULL, size, align, true);
+    return __ts_alloc_region(NULL, size, align, TS_STORAGE_LAZY_MODEL);
+}
+
+void* tsv_root_storage_region(uint64_t size, uint64_t align, uint32_t storage) {
+    return __ts_alloc_region(NULL, size, align, storage);
+}
+
+void* tsv_alloc_region_tagged(void* heap, uint64_t size, uint64_t align, const char* tag, const char* file, const uint32_t line) {
+    assert(heap != NULL);
+    return __ts_alloc_region_tagged(heap, size, align, TS_STORAGE_HEAP, tag, file, line);
+}
+
+void* tsv_root_region_tagged(uint64_t size, uint64_t align, const char* tag, const char* file, const uint32_t line) {
+    return __ts_alloc_region_tagged(NULL, size, align, TS_STORAGE_MODEL, tag, file, line);
+}
+
+uint64_t tsv_heap_leak_report(const void* heap) {
+    return __ts_heap_leak_report(heap);
+}
+
+int32_t tsv_realloc_region(const void* heap, void* region, uint64_t size, uint64_t align) {
+    assert(heap != NULL);
+    return __ts_realloc_region(heap, region, size, align);
+}
+
+void* tsv_alloc_region_at(void* heap, uint64_t base, uint64_t size) {
+    assert(heap != NULL);
+    return __ts_alloc_region_at(heap, base, size);
+}
+
+void* tsv_root_region_at(uint64_t base, uint64_t size) {
+    return __ts_alloc_region_at(NULL, base, size);
+}
+
+void tsv_heap_reserve(const void* heap, uint64_t base, uint64_t size) {
+    __ts_heap_reserve(heap, base, size);
 }
 
 void* tsv_map_region(const void* region, uint64_t base) {
@@ -72,6 +290,26 @@ void tsv_free_heap(const void* heap) {
     __ts_free_heap(heap);
 }
 
+void* tsv_scratch_heap(uint64_t size) {
+    return __ts_scratch_heap(size);
+}
+
+void tsv_heap_teardown(const void* heap) {
+    __ts_heap_teardown(heap);
+}
+
+void tsv_heap_reset(const void* heap) {
+    __ts_heap_reset(heap);
+}
+
+int32_t tsv_heaps_reset() {
+    return __ts_heaps_reset();
+}
+
+void tsv_heap_report(const void* heap) {
+    __ts_heap_report(heap);
+}
+
 void tsv_region_write_u8(const void* region, const uint64_t addr, const uint8_t data) {
     __ts_region_write_u8(region, addr, data);
 }
@@ -130,6 +368,118 @@ void tsv_space_read_u64(const void* space, const uint64_t addr, uint64_t* data){
     *data =__ts_space_read_u64(space, addr);
 }
 
+void tsv_space_set_latency(const void* space, const char* name, const uint64_t read, const uint64_t write) {
+    __ts_space_set_latency(space, name, read, write);
+}
+
+uint64_t tsv_space_request_read(const void* space, const uint64_t addr, const uint32_t size) {
+    return __ts_space_request_read(space, addr, size);
+}
+
+uint64_t tsv_space_request_write(const void* space, const uint64_t addr, const uint64_t data, const uint32_t size) {
+    return __ts_space_request_write(space, addr, data, size);
+}
+
+void tsv_space_tick(const void* space, const uint64_t ticks) {
+    __ts_space_tick(space, ticks);
+}
+
+uint64_t tsv_space_attach(const void* space) {
+    return __ts_space_attach(space);
+}
+
+bool tsv_space_complete(const void* space, const uint64_t token) {
+    return __ts_space_complete(space, token);
+}
+
+bool tsv_space_poll(const void* space, const uint64_t token, uint64_t* data) {
+    return __ts_space_poll(space, token, data);
+}
+
+void* tsv_perf_model(const uint64_t latency, const uint64_t bandwidth) {
+    return __ts_perf_model(latency, bandwidth);
+}
+
+void tsv_perf_set_timing(const void* perf, const char* name, const uint64_t latency, const uint64_t bandwidth) {
+    __ts_perf_set_timing(perf, name, latency, bandwidth);
+}
+
+void tsv_space_set_perf(const void* space, const void* perf) {
+    __ts_space_set_perf(space, perf);
+}
+
+uint64_t tsv_perf_cycles(const void* perf) {
+    return __ts_perf_cycles(perf);
+}
+
+uint64_t tsv_perf_accesses(const void* perf, const char* name) {
+    return __ts_perf_accesses(perf, name);
+}
+
+void tsv_perf_reset(const void* perf) {
+    __ts_perf_reset(perf);
+}
+
+void tsv_perf_report(const void* perf) {
+    __ts_perf_report(perf);
+}
+
+void* tsv_cache(const uint64_t size, const uint64_t ways, const uint64_t line, const uint32_t replace, const uint32_t write, const bool buffered) {
+    return __ts_cache(size, ways, line, replace, write, buffered);
+}
+
+void tsv_space_set_cache(const void* space, const void* cache) {
+    __ts_space_set_cache(space, cache);
+}
+
+uint64_t tsv_cache_hits(const void* cache) {
+    ts_cache_stats stats;
+    __ts_cache_stats(cache, &stats);
+    return stats.hits;
+}
+
+uint64_t tsv_cache_misses(const void* cache) {
+    ts_cache_stats stats;
+    __ts_cache_stats(cache, &stats);
+    return stats.misses;
+}
+
+uint64_t tsv_cache_evictions(const void* cache) {
+    ts_cache_stats stats;
+    __ts_cache_stats(cache, &stats);
+    return stats.evictions;
+}
+
+uint64_t tsv_cache_writebacks(const void* cache) {
+    ts_cache_stats stats;
+    __ts_cache_stats(cache, &stats);
+    return stats.writebacks;
+}
+
+void tsv_cache_clean(const void* cache, const uint64_t addr, const uint64_t size) {
+    __ts_cache_clean(cache, addr, size);
+}
+
+void tsv_cache_invalidate(const void* cache, const uint64_t addr, const uint64_t size) {
+    __ts_cache_invalidate(cache, addr, size);
+}
+
+void tsv_cache_flush(const void* cache, const uint64_t addr, const uint64_t size) {
+    __ts_cache_flush(cache, addr, size);
+}
+
+void tsv_tick(const uint64_t cycles) {
+    __ts_tick(cycles);
+}
+
+uint64_t tsv_now() {
+    return __ts_now();
+}
+
+bool tsv_cancel(const uint64_t id) {
+    return __ts_cancel(id);
+}
+
 uint64_t tsv_region_base(const void* region){
     return ((ts_mem_info*)__ts_region_info(region))->base;
 }
@@ -4,23 +4,75 @@
 
 void* tsv_new_allocator(const uint64_t base, const uint64_t size);
 void* tsv_new_locked_allocator(const uint64_t base, const uint64_t size);
+void* tsv_new_policy_allocator(const uint64_t base, const uint64_t size, const uint32_t policy);
+void* tsv_new_policy_locked_allocator(const uint64_t base, const uint64_t size, const uint32_t policy);
+void* tsv_new_pool_allocator();
+int32_t tsv_add_pool(const void* allocator, const char* name, const uint64_t base, const uint64_t size, const bool dma, const bool cacheable, const uint32_t policy);
+uint64_t tsv_alloc_pool_addr(const void* allocator, const uint64_t size, const uint64_t align, const uint64_t limit, const int32_t dma, const int32_t cacheable, const char* pool);
 uint64_t tsv_alloc_addr(const void* allocator, const uint64_t size, const uint64_t align);
-void tsv_free_addr(const void* allocator, const uint64_t addr);
+int32_t tsv_free_addr(const void* allocator, const uint64_t addr);
+void tsv_allocator_set_strict(const void* allocator, bool strict);
+void tsv_allocator_set_seed(const void* allocator, const uint64_t seed);
+void tsv_allocator_save(const void* allocator, const char* path);
+void* tsv_allocator_restore(const char* path, const bool locked);
+void tsv_allocator_start_log(const void* allocator, const char* path);
+void tsv_allocator_stop_log(const void* allocator);
+int32_t tsv_realloc_addr(const void* allocator, const uint64_t addr, const uint64_t size, const uint64_t align, uint64_t* new_addr);
+int32_t tsv_alloc_addr_at(const void* allocator, const uint64_t base, const uint64_t size);
+int32_t tsv_reserve_addr(const void* allocator, const uint64_t base, const uint64_t size);
+uint64_t tsv_allocator_total_free(const void* allocator);
+uint64_t tsv_allocator_largest_free(const void* allocator);
+uint64_t tsv_allocator_fragments(const void* allocator);
+uint64_t tsv_allocator_high_water(const void* allocator);
+uint64_t tsv_allocator_live(const void* allocator);
+void tsv_allocator_report(const void* allocator);
 
 void* tsv_space();
+void tsv_free_space(void* space);
 void tsv_delete_region(const void* space, const char* name);
 void* tsv_get_region(const void* space, const char* name);
 void* tsv_add_region(const void* space, const char* name, void* region);
+void* tsv_replace_region(const void* space, const char* name, void* region);
+void* tsv_move_region(const void* space, const char* name, const uint64_t base);
+uint64_t tsv_space_region_count(const void* space);
+const char* tsv_space_region_name(const void* space, const uint64_t idx);
+uint64_t tsv_space_region_base(const void* space, const uint64_t idx);
+uint64_t tsv_space_region_size(const void* space, const uint64_t idx);
+uint64_t tsv_space_free_gap(const void* space, const uint64_t size, const uint64_t align);
+void tsv_space_export_json(const void* space, const char* path);
+int64_t tsv_space_import_regmap(const void* space, const char* path, uint32_t format);
+void tsv_space_export_csv(const void* space, const char* path);
+void* tsv_add_region_with_attr(const void* space, const char* name, void* region, bool readable, bool writable);
+
+void* tsv_views();
+void* tsv_add_view(const void* views, const char* name);
+void* tsv_get_view(const void* views, const char* name);
+void tsv_views_trace(const void* views, bool enable);
+const char* tsv_views_issuer(const void* views, const uint64_t addr);
 
 void* tsv_alloc_region(void* heap, uint64_t size, uint64_t align);
+void* tsv_alloc_storage_region(void* heap, uint64_t size, uint64_t align, uint32_t storage);
 void* tsv_root_region(uint64_t size, uint64_t align);
 void* tsv_lazy_root_region(uint64_t size, uint64_t align);
+void* tsv_root_storage_region(uint64_t size, uint64_t align, uint32_t storage);
+void* tsv_alloc_region_tagged(void* heap, uint64_t size, uint64_t align, const char* tag, const char* file, const uint32_t line);
+void* tsv_root_region_tagged(uint64_t size, uint64_t align, const char* tag, const char* file, const uint32_t line);
+uint64_t tsv_heap_leak_report(const void* heap);
+int32_t tsv_realloc_region(const void* heap, void* region, uint64_t size, uint64_t align);
+void* tsv_alloc_region_at(void* heap, uint64_t base, uint64_t size);
+void* tsv_root_region_at(uint64_t base, uint64_t size);
+void tsv_heap_reserve(const void* heap, uint64_t base, uint64_t size);
 
 void* tsv_map_region(const void* region, uint64_t base);
 void* tsv_map_region_partial(const void* region, uint64_t base, uint64_t offset, uint64_t size);
 void* tsv_heap(const void* region);
 void tsv_free_region(const void* region);
 void tsv_free_heap(const void* heap);
+void* tsv_scratch_heap(uint64_t size);
+void tsv_heap_teardown(const void* heap);
+void tsv_heap_reset(const void* heap);
+int32_t tsv_heaps_reset();
+void tsv_heap_report(const void* heap);
 
 void tsv_region_write_u8(const void* region, const uint64_t addr, const uint8_t data);
 void tsv_region_write_u16(const void* region, const uint64_t addr, const uint16_t data);
@@ -40,6 +92,36 @@ void tsv_space_read_u16(const void* heap, const uint64_t addr, uint16_t* data);
 void tsv_space_read_u32(const void* heap, const uint64_t addr, uint32_t* data);
 void tsv_space_read_u64(const void* heap, const uint64_t addr, uint64_t* data);
 
+void tsv_space_set_latency(const void* space, const char* name, const uint64_t read, const uint64_t write);
+uint64_t tsv_space_request_read(const void* space, const uint64_t addr, const uint32_t size);
+uint64_t tsv_space_request_write(const void* space, const uint64_t addr, const uint64_t data, const uint32_t size);
+void tsv_space_tick(const void* space, const uint64_t ticks);
+uint64_t tsv_space_attach(const void* space);
+bool tsv_space_complete(const void* space, const uint64_t token);
+bool tsv_space_poll(const void* space, const uint64_t token, uint64_t* data);
+
+void* tsv_perf_model(const uint64_t latency, const uint64_t bandwidth);
+void tsv_perf_set_timing(const void* perf, const char* name, const uint64_t latency, const uint64_t bandwidth);
+void tsv_space_set_perf(const void* space, const void* perf);
+uint64_t tsv_perf_cycles(const void* perf);
+uint64_t tsv_perf_accesses(const void* perf, const char* name);
+void tsv_perf_reset(const void* perf);
+void tsv_perf_report(const void* perf);
+
+void* tsv_cache(const uint64_t size, const uint64_t ways, const uint64_t line, const uint32_t replace, const uint32_t write, const bool buffered);
+void tsv_space_set_cache(const void* space, const void* cache);
+uint64_t tsv_cache_hits(const void* cache);
+uint64_t tsv_cache_misses(const void* cache);
+uint64_t tsv_cache_evictions(const void* cache);
+uint64_t tsv_cache_writebacks(const void* cache);
+void tsv_cache_clean(const void* cache, const uint64_t addr, const uint64_t size);
+void tsv_cache_invalidate(const void* cache, const uint64_t addr, const uint64_t size);
+void tsv_cache_flush(const void* cache, const uint64_t addr, const uint64_t size);
+
+void tsv_tick(const uint64_t cycles);
+uint64_t tsv_now();
+bool tsv_cancel(const uint64_t id);
+
 uint64_t tsv_region_base(const void* region);
 uint64_t tsv_region_size(const void* region);
 
@@ -9,24 +9,136 @@ typedef struct{
     uint64_t size;
 } ts_mem_info ;
 
-extern void* __ts_new_allocator(const uint64_t base, const uint64_t size);
-extern void* __ts_new_locked_allocator(const uint64_t base, const uint64_t size);
+typedef enum {
+    TS_FIRST_FIT = 0,
+    TS_BEST_FIT = 1,
+    TS_NEXT_FIT = 2,
+    TS_POW2 = 3,
+    TS_SLAB = 4,
+    TS_RANDOM = 5
+} ts_alloc_policy;
+
+typedef enum {
+    TS_STORAGE_HEAP = 0,
+    TS_STORAGE_MODEL = 1,
+    TS_STORAGE_LAZY_MODEL = 2,
+    TS_STORAGE_SPARSE = 3,
+    TS_STORAGE_FILE = 4
+} ts_storage;
+
+typedef enum {
+    TS_REGMAP_IPXACT = 0,
+    TS_REGMAP_SYSTEMRDL = 1
+} ts_regmap_format;
+
+#define TS_LATENCY_EVENT UINT64_MAX
+
+typedef enum {
+    TS_CACHE_LRU = 0,
+    TS_CACHE_FIFO = 1,
+    TS_CACHE_RANDOM = 2
+} ts_cache_replace;
+
+typedef enum {
+    TS_CACHE_WRITE_BACK = 0,
+    TS_CACHE_WRITE_THROUGH = 1
+} ts_cache_write;
+
+typedef enum {
+    TS_OK = 0,
+    TS_DOUBLE_FREE = 1,
+    TS_NOT_ALLOCATED = 2,
+    TS_INTERIOR_POINTER = 3,
+    TS_OUT_OF_MEMORY = 4,
+    TS_OUT_OF_RANGE = 5,
+    TS_OCCUPIED = 6,
+    TS_RESERVED = 7,
+    TS_DUPLICATE_POOL = 8,
+    TS_SHARED = 9
+} ts_status;
+
+typedef enum {
+    TS_ATTR_ANY = -1,
+    TS_ATTR_CLEAR = 0,
+    TS_ATTR_SET = 1
+} ts_pool_attr_req;
+
+typedef struct{
+    uint64_t total_free;
+    uint64_t largest_free;
+    uint64_t fragments;
+    uint64_t high_water;
+    uint64_t live;
+} ts_alloc_stats;
+
+typedef struct{
+    uint64_t hits;
+    uint64_t misses;
+    uint64_t evictions;
+    uint64_t writebacks;
+} ts_cache_stats;
+
+extern void* __ts_new_allocator(const uint64_t base, const uint64_t size, const uint32_t policy);
+extern void* __ts_new_locked_allocator(const uint64_t base, const uint64_t size, const uint32_t policy);
+extern void* __ts_new_pool_allocator();
+extern int32_t __ts_add_pool(const void* allocator, const char* name, const uint64_t base, const uint64_t size, const bool dma, const bool cacheable, const uint32_t policy);
+extern uint64_t __ts_alloc_pool_addr(const void* allocator, const uint64_t size, const uint64_t align, const uint64_t limit, const int32_t dma, const int32_t cacheable, const char* pool);
 extern uint64_t __ts_alloc_addr(const void* allocator, const uint64_t size, const uint64_t align);
-extern void __ts_free_addr(const void* allocator, const uint64_t addr);
+extern int32_t __ts_free_addr(const void* allocator, const uint64_t addr);
+extern void __ts_allocator_set_strict(const void* allocator, bool strict);
+extern void __ts_allocator_set_seed(const void* allocator, const uint64_t seed);
+extern void __ts_allocator_save(const void* allocator, const char* path);
+extern void* __ts_allocator_restore(const char* path, const bool locked);
+extern void __ts_allocator_start_log(const void* allocator, const char* path);
+extern void __ts_allocator_stop_log(const void* allocator);
+extern int32_t __ts_realloc_addr(const void* allocator, const uint64_t addr, const uint64_t size, const uint64_t align, uint64_t* new_addr);
+extern int32_t __ts_alloc_addr_at(const void* allocator, const uint64_t base, const uint64_t size);
+extern int32_t __ts_reserve_addr(const void* allocator, const uint64_t base, const uint64_t size);
+extern void __ts_allocator_stats(const void* allocator, ts_alloc_stats* stats);
+extern void __ts_allocator_report(const void* allocator);
 
 extern void* __ts_space();
+extern void __ts_free_space(void* space);
 extern void* __ts_add_region(const void* space, const char* name, void* region);
+extern void* __ts_add_region_with_attr(const void* space, const char* name, void* region, bool readable, bool writable);
 extern void __ts_clean_region(const void* space, const char* name, void* ptr);
+extern void __ts_track_region(const void* space, const char* name, void* ptr);
 extern void* __ts_get_region(const void* space, const char* name);
 extern void __ts_delete_region(const void* space, const char* name);
+extern void* __ts_replace_region(const void* space, const char* name, void* region);
+extern void* __ts_move_region(const void* space, const char* name, const uint64_t base);
+extern uint64_t __ts_space_region_count(const void* space);
+extern const char* __ts_space_region_name(const void* space, const uint64_t idx);
+extern void* __ts_space_region_info(const void* space, const uint64_t idx);
+extern uint64_t __ts_space_free_gap(const void* space, const uint64_t size, const uint64_t align);
+extern void __ts_space_export_json(const void* space, const char* path);
+extern int64_t __ts_space_import_regmap(const void* space, const char* path, uint32_t format);
+extern void __ts_space_export_csv(const void* space, const char* path);
+
+extern void* __ts_views();
+extern void* __ts_add_view(const void* views, const char* name);
+extern void* __ts_get_view(const void* views, const char* name);
+extern void __ts_views_trace(const void* views, bool enable);
+extern const char* __ts_views_issuer(const void* views, const uint64_t addr);
 
-extern void* __ts_alloc_region(void* heap, uint64_t size, uint64_t align, bool lazy);
+extern void* __ts_alloc_region(void* heap, uint64_t size, uint64_t align, uint32_t storage);
+extern void* __ts_alloc_region_tagged(void* heap, uint64_t size, uint64_t align, uint32_t storage, const char* tag, const char* file, const uint32_t line);
+extern uint64_t __ts_heap_leak_report(const void* heap);
+extern int32_t __ts_realloc_region(const void* heap, void* region, uint64_t size, uint64_t align);
+extern void* __ts_alloc_region_at(const void* heap, uint64_t base, uint64_t size);
+extern void __ts_heap_reserve(const void* heap, uint64_t base, uint64_t size);
 extern void* __ts_map_region(const void* region, uint64_t base);
 extern void* __ts_map_region_partial(const void* region, uint64_t base, uint64_t offset, uint64_t size);
 extern void* __ts_heap(const void* region);
 extern void __ts_free_region(const void* region);
 extern void __ts_free_heap(const void* heap);
+extern void* __ts_scratch_heap(uint64_t size);
+extern void __ts_heap_teardown(const void* heap);
+extern void __ts_heap_reset(const void* heap);
+extern int32_t __ts_heaps_reset();
 extern void* __ts_region_info(const void* region);
+extern void __ts_heap_stats(const void* heap, ts_alloc_stats* stats);
+extern void __ts_heap_report(const void* heap);
 
 extern void __ts_region_write_u8(const void* region, const uint64_t addr, const uint8_t data);
 extern void __ts_region_write_u16(const void* region, const uint64_t addr, const uint16_t data);
@@ -46,4 +158,34 @@ extern uint16_t __ts_space_read_u16(const void* space, const uint64_t addr);
 extern uint32_t __ts_space_read_u32(const void* space, const uint64_t addr);
 extern uint64_t __ts_space_read_u64(const void* space, const uint64_t addr);
 
+extern void __ts_space_set_latency(const void* space, const char* name, const uint64_t read, const uint64_t write);
+extern uint64_t __ts_space_request_read(const void* space, const uint64_t addr, const uint32_t size);
+extern uint64_t __ts_space_request_write(const void* space, const uint64_t addr, const uint64_t data, const uint32_t size);
+extern void __ts_space_tick(const void* space, const uint64_t ticks);
+extern uint64_t __ts_space_attach(const void* space);
+extern bool __ts_space_complete(const void* space, const uint64_t token);
+extern bool __ts_space_poll(const void* space, const uint64_t token, uint64_t* data);
+
+extern void* __ts_perf_model(const uint64_t latency, const uint64_t bandwidth);
+extern void __ts_perf_set_timing(const void* perf, const char* name, const uint64_t latency, const uint64_t bandwidth);
+extern void __ts_space_set_perf(const void* space, const void* perf);
+extern uint64_t __ts_perf_cycles(const void* perf);
+extern uint64_t __ts_perf_accesses(const void* perf, const char* name);
+extern void __ts_perf_reset(const void* perf);
+extern void __ts_perf_report(const void* perf);
+
+extern void* __ts_cache(const uint64_t size, const uint64_t ways, const uint64_t line, const uint32_t replace, const uint32_t write, const bool buffered);
+extern void __ts_space_set_cache(const void* space, const void* cache);
+extern void __ts_cache_stats(const void* cache, ts_cache_stats* stats);
+extern void __ts_cache_clean(const void* cache, const uint64_t addr, const uint64_t size);
+extern void __ts_cache_invalidate(const void* cache, const uint64_t addr, const uint64_t size);
+extern void __ts_cache_flush(const void* cache, const uint64_t addr, const uint64_t size);
+
+typedef void (*ts_callback)(void* arg);
+extern void __ts_tick(const uint64_t cycles);
+extern uint64_t __ts_now();
+extern uint64_t __ts_schedule(const uint64_t delay, ts_callback cb, void* arg);
+extern uint64_t __ts_schedule_every(const uint64_t period, ts_callback cb, void* arg);
+extern bool __ts_cancel(const uint64_t id);
+
 #endif
\ No newline at end of file
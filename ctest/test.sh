@@ -14,4 +14,30 @@ gcc -g -o test test_region.c -I ../target/release -L../target/release -Wl,-Bstat
 ./test
 rm test
 echo "Test test_region Done!"
-echo "--------------------------"
\ No newline at end of file
+echo "--------------------------"
+echo "--------------------------"
+echo "Test test_views..."
+gcc -g -o test test_views.c -I ../target/release -L../target/release -Wl,-Bstatic -lterminus_spaceport  -Wl,-Bdynamic -lpthread -ldl -lm -lrt
+
+./test
+cat cpu_map.csv cpu_map.json
+rm test cpu_map.csv cpu_map.json
+echo "Test test_views Done!"
+echo "--------------------------"
+echo "--------------------------"
+echo "Test test_remap..."
+gcc -g -o test test_remap.c -I ../target/release -L../target/release -Wl,-Bstatic -lterminus_spaceport  -Wl,-Bdynamic -lpthread -ldl -lm -lrt
+
+./test
+rm test
+echo "Test test_remap Done!"
+echo "--------------------------"
+echo "--------------------------"
+echo "Test test_memprof..."
+CARGO_TARGET_DIR=${PWD}/../target cargo build --release --features memprof
+gcc -g -o test test_memprof.c -I ../target/release -L../target/release -Wl,-Bstatic -lterminus_spaceport  -Wl,-Bdynamic -lpthread -ldl -lm -lrt
+
+./test
+rm test
+echo "Test test_memprof Done!"
+echo "--------------------------"
@@ -0,0 +1,24 @@
+#include <ts_c.h>
+int main() {
+    void* allocator = tsc_new_allocator(0, 0x100);
+    uint64_t a = tsc_alloc_addr(allocator, 0x10, 1);
+    uint64_t b = tsc_alloc_addr(allocator, 0x20, 1);
+    tsc_alloc_addr(allocator, 0x10, 1);
+    tsc_free_addr(allocator, b);
+    ts_alloc_stats stats;
+    tsc_allocator_stats(allocator, &stats);
+    assert(stats.total_free == 0xe0);
+    assert(stats.largest_free == 0xc0);
+    assert(stats.fragments == 2);
+    assert(stats.high_water == 0x40);
+    assert(stats.live == 2);
+    tsc_allocator_report(allocator);
+
+    void* region = tsc_root_region(0x1000, 1);
+    void* heap = tsc_heap(region);
+    tsc_alloc_region(heap, 0x10, 1);
+    tsc_heap_stats(heap, &stats);
+    assert(stats.live == 1);
+    tsc_heap_report(heap);
+    tsc_free_addr(allocator, a);
+}
@@ -1,4 +1,5 @@
 #include <ts_c.h>
+#include <stdlib.h>
 int main() {
     void* allocator = tsc_new_allocator(1, 9);
     void* locked_allocator = tsc_new_locked_allocator(1, 9);
@@ -16,4 +17,130 @@ int main() {
     tsc_free_addr(locked_allocator, laddr);
     printf("laddr = %lu\n", tsc_alloc_addr(locked_allocator, 1, 1));
     printf("laddr = %lu\n", tsc_alloc_addr(locked_allocator, 1, 1));
-}
\ No newline at end of file
+
+    void* pow2_allocator = tsc_new_policy_allocator(0, 0x100, TS_POW2);
+    assert(tsc_alloc_addr(pow2_allocator, 3, 1) == 0);
+    assert(tsc_alloc_addr(pow2_allocator, 9, 1) == 0x10);
+    assert(tsc_alloc_addr(pow2_allocator, 2, 1) == 0x4);
+
+    void* next_allocator = tsc_new_policy_locked_allocator(0, 0x40, TS_NEXT_FIT);
+    uint64_t naddr = tsc_alloc_addr(next_allocator, 0x10, 1);
+    tsc_free_addr(next_allocator, naddr);
+    assert(tsc_alloc_addr(next_allocator, 0x10, 1) == 0x10);
+
+    void* region = tsc_root_region(0x100, 1);
+    void* heap = tsc_heap(region);
+    void* leaked = tsc_alloc_region_tagged(heap, 0x10, 1, "leaked", __FILE__, __LINE__);
+    void* freed = tsc_alloc_region_tagged(heap, 0x10, 1, "freed", NULL, 0);
+    tsc_free_region(freed);
+    assert(tsc_heap_leak_report(heap) == 1);
+    tsc_free_region(leaked);
+    assert(tsc_heap_leak_report(heap) == 0);
+
+    void* checked_allocator = tsc_new_allocator(0, 0x100);
+    uint64_t caddr = tsc_alloc_addr(checked_allocator, 0x10, 1);
+    assert(tsc_free_addr(checked_allocator, caddr + 4) == TS_INTERIOR_POINTER);
+    assert(tsc_free_addr(checked_allocator, caddr) == TS_OK);
+    assert(tsc_free_addr(checked_allocator, caddr) == TS_DOUBLE_FREE);
+    assert(tsc_free_addr(checked_allocator, 0x80) == TS_NOT_ALLOCATED);
+
+    uint64_t raddr = tsc_alloc_addr(checked_allocator, 0x10, 1);
+    uint64_t blocker = tsc_alloc_addr(checked_allocator, 0x10, 1);
+    uint64_t moved = 0;
+    assert(tsc_realloc_addr(checked_allocator, raddr, 0x20, 1, &moved) == TS_OK);
+    assert(moved != raddr);
+    assert(tsc_realloc_addr(checked_allocator, moved, 0x1000, 1, &moved) == TS_OUT_OF_MEMORY);
+    assert(tsc_realloc_addr(checked_allocator, raddr, 0x20, 1, &moved) == TS_DOUBLE_FREE);
+    tsc_free_addr(checked_allocator, blocker);
+
+    void* ring = tsc_alloc_region(heap, 0x10, 1);
+    assert(tsc_realloc_region(heap, ring, 0x40, 1) == TS_OK);
+    assert(tsc_realloc_region(heap, ring, 0x1000, 1) == TS_OUT_OF_MEMORY);
+    tsc_free_region(ring);
+
+    void* fixed_allocator = tsc_new_allocator(0, 0x100);
+    assert(tsc_reserve_addr(fixed_allocator, 0x40, 0x40) == TS_OK);
+    assert(tsc_alloc_addr_at(fixed_allocator, 0x20, 0x10) == TS_OK);
+    assert(tsc_alloc_addr_at(fixed_allocator, 0x28, 0x10) == TS_OCCUPIED);
+    assert(tsc_alloc_addr_at(fixed_allocator, 0x70, 0x10) == TS_RESERVED);
+    assert(tsc_reserve_addr(fixed_allocator, 0xf0, 0x20) == TS_OUT_OF_RANGE);
+    assert(tsc_alloc_addr(fixed_allocator, 0x40, 0x40) == 0x80);
+
+    tsc_heap_reserve(heap, 0x80, 0x80);
+    void* fw = tsc_alloc_region_at(heap, 0x40, 0x20);
+    tsc_free_region(fw);
+
+    void* pool_allocator = tsc_new_pool_allocator();
+    assert(tsc_add_pool(pool_allocator, "coherent", 0x1000, 0x1000, true, false, TS_BEST_FIT) == TS_OK);
+    assert(tsc_add_pool(pool_allocator, "normal", 0x100000000, 0x1000, false, true, TS_BEST_FIT) == TS_OK);
+    assert(tsc_add_pool(pool_allocator, "normal", 0x8000, 0x1000, false, true, TS_BEST_FIT) == TS_DUPLICATE_POOL);
+    assert(tsc_add_pool(pool_allocator, "overlap", 0x1800, 0x1000, false, true, TS_BEST_FIT) == TS_OCCUPIED);
+    assert(tsc_alloc_pool_addr(pool_allocator, 0x10, 1, 0, TS_ATTR_CLEAR, TS_ATTR_ANY, NULL) == 0x100000000);
+    uint64_t dma_addr = tsc_alloc_pool_addr(pool_allocator, 0x10, 1, 0x100000000, TS_ATTR_SET, TS_ATTR_CLEAR, NULL);
+    assert(dma_addr == 0x1000);
+    assert(tsc_alloc_pool_addr(pool_allocator, 0x10, 1, 0, TS_ATTR_ANY, TS_ATTR_ANY, "normal") == 0x100000010);
+    assert(tsc_free_addr(pool_allocator, dma_addr) == TS_OK);
+    assert(tsc_alloc_addr(pool_allocator, 0x10, 1) == 0x1000);
+
+    void* random_a = tsc_new_policy_allocator(0x1000, 0x10000, TS_RANDOM);
+    void* random_b = tsc_new_policy_allocator(0x1000, 0x10000, TS_RANDOM);
+    tsc_allocator_set_seed(random_a, 42);
+    tsc_allocator_set_seed(random_b, 42);
+    for (int i = 0; i < 16; i++) {
+        uint64_t raddr = tsc_alloc_addr(random_a, 0x20, 8);
+        assert(raddr % 8 == 0);
+        assert(raddr == tsc_alloc_addr(random_b, 0x20, 8));
+    }
+
+    void* logged = tsc_new_allocator(0, 0x1000);
+    tsc_alloc_addr(logged, 0x100, 1);
+    tsc_allocator_start_log(logged, "allocator.log");
+    uint64_t logged_addr = tsc_alloc_addr(logged, 0x10, 0x10);
+    tsc_free_addr(logged, logged_addr);
+    tsc_allocator_stop_log(logged);
+    tsc_allocator_save(logged, "allocator.txt");
+    void* restored = tsc_allocator_restore("allocator.txt", true);
+    assert(tsc_free_addr(restored, logged_addr) == TS_DOUBLE_FREE);
+    assert(tsc_alloc_addr(restored, 0x10, 0x10) == tsc_alloc_addr(logged, 0x10, 0x10));
+    system("cat allocator.log");
+    remove("allocator.log");
+    remove("allocator.txt");
+
+    void* scratch = tsc_scratch_heap(0x100);
+    void* scratch_block = tsc_alloc_region(scratch, 0x10, 1);
+    tsc_heap_reset(scratch);
+    tsc_free_region(scratch_block);
+    assert(tsc_heap_leak_report(scratch) == 0);
+    tsc_free_heap(scratch);
+
+    void* storage_heap = tsc_scratch_heap(0x10000);
+    void* storage_space = tsc_space();
+    tsc_add_region(storage_space, "sparse", tsc_alloc_storage_region(storage_heap, 0x1000, 0x1000, TS_STORAGE_SPARSE));
+    tsc_add_region(storage_space, "file", tsc_root_storage_region(0x1000, 0x1000, TS_STORAGE_FILE));
+    for (uint64_t i = 0; i < tsc_space_region_count(storage_space); i++) {
+        uint64_t storage_addr = tsc_space_region_info(storage_space, i)->base + 0x8;
+        assert(tsc_space_read_u64(storage_space, storage_addr) == 0);
+        tsc_space_write_u64(storage_space, storage_addr, i + 1);
+        assert(tsc_space_read_u64(storage_space, storage_addr) == i + 1);
+    }
+    tsc_delete_region(storage_space, "sparse");
+    tsc_delete_region(storage_space, "file");
+    assert(tsc_heap_leak_report(storage_heap) == 0);
+    tsc_free_heap(storage_heap);
+
+    uint64_t roots = tsc_heap_leak_report(NULL);
+    void* root = tsc_root_region(0x10, 1);
+    void* reset_heap = tsc_scratch_heap(0x1000);
+    void* stale = tsc_alloc_region(reset_heap, 0x10, 1);
+    tsc_region_write_u64(stale, tsc_region_info(stale)->base, 0x5a5a);
+    assert(tsc_heaps_reset() >= 1);
+    assert(tsc_heap_leak_report(reset_heap) == 0);
+    tsc_free_region(stale);
+    void* fresh = tsc_alloc_region(reset_heap, 0x10, 1);
+    assert(tsc_region_read_u64(fresh, tsc_region_info(fresh)->base) == 0);
+    tsc_free_region(fresh);
+    tsc_free_heap(reset_heap);
+    assert(tsc_heap_leak_report(NULL) == roots + 1);
+    tsc_free_region(root);
+    assert(tsc_heap_leak_report(NULL) == roots);
+}
@@ -0,0 +1,18 @@
+#include <ts_c.h>
+int main() {
+    void* space = tsc_space();
+    void* rom = tsc_add_region(space, "boot", tsc_map_region(tsc_root_region(16, 8), 0));
+    void* boot = tsc_get_region(space, "boot");
+    tsc_region_write_u32(rom, 0, 0x5aa5);
+    void* dram = tsc_root_region(16, 8);
+    tsc_region_write_u32(dram, tsc_region_info(dram)->base, 0xbeef);
+    tsc_replace_region(space, "boot", tsc_map_region(dram, 0));
+    printf("boot @0x0 = %x\n", tsc_space_read_u32(space, 0));
+    assert(tsc_space_read_u32(space, 0) == 0xbeef);
+    assert(tsc_region_read_u32(boot, 0) == 0xbeef);
+    tsc_move_region(space, "boot", 0x80000000);
+    printf("boot @0x%lx = %x\n", tsc_region_info(boot)->base, tsc_region_read_u32(boot, 0x80000000));
+    assert(tsc_space_read_u32(space, 0x80000000) == 0xbeef);
+    tsc_delete_region(space, "boot");
+    tsc_free_region(dram);
+}
@@ -0,0 +1,108 @@
+#include <ts_c.h>
+#include <string.h>
+
+static void count(void* arg) {
+    (*(int*)arg)++;
+}
+
+int main() {
+    void* views = tsc_views();
+    void* cpu = tsc_add_view(views, "cpu");
+    void* dma = tsc_add_view(views, "dma");
+    void* memory = tsc_root_region(16, 8);
+    tsc_add_region(cpu, "memory", tsc_map_region(memory, 0x80000000));
+    tsc_add_region(dma, "memory", tsc_map_region(memory, 0));
+    tsc_views_trace(views, true);
+    tsc_space_write_u32(tsc_get_view(views, "dma"), 4, 0xdeadbeef);
+    printf("cpu read @0x80000004 = %x\n", tsc_space_read_u32(cpu, 0x80000004));
+    assert(tsc_space_read_u32(cpu, 0x80000004) == 0xdeadbeef);
+    printf("issuer of 0x4 = %s\n", tsc_views_issuer(views, 4));
+    assert(strcmp(tsc_views_issuer(views, 4), "dma") == 0);
+    assert(strcmp(tsc_views_issuer(views, 0x80000004), "cpu") == 0);
+    assert(tsc_views_issuer(views, 0) == NULL);
+    tsc_views_trace(views, false);
+
+    assert(tsc_space_region_count(cpu) == 1);
+    printf("cpu region 0: %s @0x%lx size 0x%lx\n", tsc_space_region_name(cpu, 0), tsc_space_region_info(cpu, 0)->base, tsc_space_region_info(cpu, 0)->size);
+    assert(strcmp(tsc_space_region_name(cpu, 0), "memory") == 0);
+    assert(tsc_space_free_gap(dma, 16, 16) == 16);
+    tsc_space_export_csv(cpu, "cpu_map.csv");
+    tsc_space_export_json(cpu, "cpu_map.json");
+
+    FILE* rdl = fopen("regmap.json", "w");
+    fputs("{\"type\": \"addrmap\", \"inst_name\": \"timer\", \"addr_offset\": \"0x9000\", \"children\": ["
+          "{\"type\": \"reg\", \"inst_name\": \"ctrl\", \"addr_offset\": 4,"
+          " \"children\": [{\"type\": \"field\", \"inst_name\": \"en\", \"lsb\": 0, \"msb\": 3, \"reset\": 5}]}]}", rdl);
+    fclose(rdl);
+    assert(tsc_space_import_regmap(cpu, "regmap.json", TS_REGMAP_SYSTEMRDL) == 1);
+    assert(tsc_space_import_regmap(cpu, "regmap.json", TS_REGMAP_SYSTEMRDL) == -1);
+    assert(tsc_space_read_u32(cpu, 0x9004) == 5);
+    tsc_space_write_u32(cpu, 0x9004, 0xa3);
+    assert(tsc_space_read_u32(cpu, 0x9004) == 3);
+    remove("regmap.json");
+
+    uint64_t data = 0;
+    tsc_space_set_latency(cpu, "memory", 2, TS_LATENCY_EVENT);
+    uint64_t rd = tsc_space_request_read(cpu, 0x80000004, 4);
+    uint64_t wr = tsc_space_request_write(cpu, 0x80000004, 0x1234, 2);
+    tsc_space_tick(cpu, 1);
+    assert(!tsc_space_poll(cpu, rd, &data));
+    tsc_space_tick(cpu, 1);
+    assert(tsc_space_poll(cpu, rd, &data));
+    assert(data == 0xdeadbeef);
+    tsc_space_tick(cpu, 10);
+    assert(!tsc_space_poll(cpu, wr, &data));
+    assert(tsc_space_complete(cpu, wr));
+    assert(tsc_space_poll(cpu, wr, &data));
+    assert(tsc_space_read_u32(cpu, 0x80000004) == 0xdead1234);
+
+    int ticks = 0;
+    uint64_t every = tsc_schedule_every(2, count, &ticks);
+    tsc_schedule(3, count, &ticks);
+    tsc_tick(6);
+    assert(tsc_now() == 6);
+    assert(ticks == 4);
+
+    void* perf = tsc_perf_model(1, 16);
+    tsc_perf_set_timing(perf, "memory", 100, 8);
+    tsc_space_set_perf(cpu, perf);
+    tsc_space_set_perf(dma, perf);
+    tsc_space_read_u64(cpu, 0x80000000);
+    tsc_space_write_u32(dma, 0, 1);
+    tsc_space_read_u8(cpu, 0x9004);
+    tsc_perf_report(perf);
+    assert(tsc_perf_accesses(perf, "memory") == 2);
+    assert(tsc_perf_accesses(perf, "timer") == 1);
+    assert(tsc_perf_cycles(perf) == 101 + 101 + 2);
+    tsc_perf_reset(perf);
+    assert(tsc_perf_cycles(perf) == 0);
+
+    ts_cache_stats stats;
+    void* cache = tsc_cache(64, 2, 8, TS_CACHE_LRU, TS_CACHE_WRITE_BACK, true);
+    tsc_space_set_cache(cpu, cache);
+    tsc_space_write_u32(cpu, 0x80000008, 0x1234);
+    assert(tsc_space_read_u32(dma, 8) != 0x1234);
+    tsc_cache_clean(cache, 0x80000008, 4);
+    assert(tsc_space_read_u32(dma, 8) == 0x1234);
+    tsc_space_write_u32(dma, 8, 0x5678);
+    assert(tsc_space_read_u32(cpu, 0x80000008) == 0x1234);
+    tsc_cache_invalidate(cache, 0x80000000, 16);
+    assert(tsc_space_read_u32(cpu, 0x80000008) == 0x5678);
+    tsc_cache_stats(cache, &stats);
+    assert(stats.hits == 1 && stats.misses == 2 && stats.writebacks == 1);
+    assert(tsc_cancel(every));
+    tsc_tick(6);
+    assert(ticks == 4);
+
+    uint64_t clock = tsc_space_attach(cpu);
+    rd = tsc_space_request_read(cpu, 0x80000004, 4);
+    tsc_tick(2);
+    assert(tsc_space_poll(cpu, rd, &data));
+    assert(tsc_cancel(clock));
+    //freeing an attached space detaches it
+    void* scratch = tsc_space();
+    clock = tsc_space_attach(scratch);
+    tsc_free_space(scratch);
+    assert(!tsc_cancel(clock));
+    tsc_tick(1);
+}
@@ -0,0 +1,255 @@
+use proc_macro2::{Span, TokenStream};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Fields, Lit, Meta, NestedMeta, Result, Type};
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+#[derive(Default)]
+struct FieldAttr {
+    endian: Option<Endian>,
+    pad: usize,
+    bits: Option<u32>,
+}
+
+fn parse_attrs(attrs: &[syn::Attribute]) -> Result<FieldAttr> {
+    let mut attr = FieldAttr::default();
+    for a in attrs.iter().filter(|a| a.path.is_ident("layout")) {
+        let list = match a.parse_meta()? {
+            Meta::List(list) => list,
+            m => return Err(Error::new(m.span(), "expect #[layout(...)]!")),
+        };
+        for item in list.nested.iter() {
+            match item {
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("le") => {
+                    attr.endian = Some(Endian::Little)
+                }
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("be") => {
+                    attr.endian = Some(Endian::Big)
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) => {
+                    let v = match &nv.lit {
+                        Lit::Int(v) => v.base10_parse::<usize>()?,
+                        l => return Err(Error::new(l.span(), "expect integer!")),
+                    };
+                    if nv.path.is_ident("pad") {
+                        attr.pad = v
+                    } else if nv.path.is_ident("bits") {
+                        attr.bits = Some(v as u32)
+                    } else {
+                        return Err(Error::new(nv.path.span(), "expect pad or bits!"));
+                    }
+                }
+                i => {
+                    return Err(Error::new(
+                        i.span(),
+                        "expect one of [le|be|pad = N|bits = N]!",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(attr)
+}
+
+const SCALARS: [&str; 8] = ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
+
+fn scalar_bits(ty: &Type) -> Option<u32> {
+    if let Type::Path(p) = ty {
+        let ident = p.path.get_ident()?.to_string();
+        SCALARS
+            .iter()
+            .position(|s| *s == ident)
+            .map(|i| 8 << (i % 4))
+    } else {
+        None
+    }
+}
+
+fn is_signed(ty: &Type) -> bool {
+    scalar_bits(ty).is_some() && quote! {#ty}.to_string().starts_with('i')
+}
+
+fn is_bytes(ty: &Type) -> bool {
+    if let Type::Array(a) = ty {
+        if let Type::Path(p) = a.elem.as_ref() {
+            return p.path.is_ident("u8");
+        }
+    }
+    false
+}
+
+enum Item {
+    Scalar(syn::Ident, Type, Endian),
+    Bytes(syn::Ident, Type),
+    Nested(syn::Ident, Type),
+    //consecutive bitfields of the same type share one unit, allocated from lsb
+    Bits(Type, Endian, Vec<(syn::Ident, u32, u32)>),
+}
+
+impl Item {
+    fn size(&self) -> TokenStream {
+        match self {
+            Item::Scalar(_, ty, _) | Item::Bytes(_, ty) | Item::Bits(ty, _, _) => {
+                quote! {std::mem::size_of::<#ty>()}
+            }
+            Item::Nested(_, ty) => quote! {<#ty as Layout>::SIZE},
+        }
+    }
+
+    fn decode(&self, range: &TokenStream) -> TokenStream {
+        match self {
+            Item::Scalar(name, ty, endian) => {
+                let from = endian_fn(*endian, "from");
+                quote! {#name: <#ty as Endian>::#from(&data[#range]),}
+            }
+            Item::Bytes(name, ty) => quote! {
+                #name: {
+                    let mut bytes: #ty = [0; std::mem::size_of::<#ty>()];
+                    bytes.copy_from_slice(&data[#range]);
+                    bytes
+                },
+            },
+            Item::Nested(name, ty) => quote! {#name: <#ty as Layout>::decode(&data[#range]),},
+            Item::Bits(ty, endian, fields) => {
+                let from = endian_fn(*endian, "from");
+                let signed = is_signed(ty);
+                fields.iter().fold(quote! {}, |acc, (name, shift, width)| {
+                    let mask = mask(*width);
+                    let mut bits = quote! {
+                        (Endian::to_bits(<#ty as Endian>::#from(&data[#range])) >> #shift) & #mask
+                    };
+                    //signed fields are sign extended from their top bit
+                    if signed && *width < 64 {
+                        let ext = 64 - *width;
+                        bits = quote! {((((#bits) << #ext) as i64) >> #ext) as u64};
+                    }
+                    quote! {
+                        #acc
+                        #name: <#ty as Endian>::from_bits(#bits),
+                    }
+                })
+            }
+        }
+    }
+
+    fn encode(&self, range: &TokenStream) -> TokenStream {
+        match self {
+            Item::Scalar(name, _, endian) => {
+                let to = endian_fn(*endian, "to");
+                quote! {Endian::#to(self.#name, &mut data[#range]);}
+            }
+            Item::Bytes(name, _) => quote! {data[#range].copy_from_slice(&self.#name);},
+            Item::Nested(name, _) => quote! {Layout::encode(&self.#name, &mut data[#range]);},
+            Item::Bits(ty, endian, fields) => {
+                let to = endian_fn(*endian, "to");
+                let unit = fields
+                    .iter()
+                    .fold(quote! {0u64}, |acc, (name, shift, width)| {
+                        let mask = mask(*width);
+                        quote! {#acc | ((Endian::to_bits(self.#name) & #mask) << #shift)}
+                    });
+                quote! {<#ty as Endian>::#to(<#ty as Endian>::from_bits(#unit), &mut data[#range]);}
+            }
+        }
+    }
+}
+
+fn endian_fn(endian: Endian, dir: &str) -> syn::Ident {
+    let suffix = match endian {
+        Endian::Little => "le",
+        Endian::Big => "be",
+    };
+    syn::Ident::new(&format!("{}_{}", dir, suffix), Span::call_site())
+}
+
+fn mask(width: u32) -> u64 {
+    if width == 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let default_endian = parse_attrs(&input.attrs)?.endian.unwrap_or(Endian::Little);
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => &f.named,
+            _ => return Err(Error::new(Span::call_site(), "expect named fields!")),
+        },
+        _ => return Err(Error::new(Span::call_site(), "expect struct!")),
+    };
+
+    //(pad before item, item)
+    let mut items: Vec<(usize, Item)> = vec![];
+    let mut used_bits = 0;
+    for f in fields.iter() {
+        let attr = parse_attrs(&f.attrs)?;
+        let ident = f.ident.clone().unwrap();
+        let ty = f.ty.clone();
+        let endian = attr.endian.unwrap_or(default_endian);
+        if let Some(width) = attr.bits {
+            let unit_bits = scalar_bits(&ty)
+                .ok_or_else(|| Error::new(ty.span(), "bitfield must be an integer!"))?;
+            if width == 0 || width > unit_bits {
+                return Err(Error::new(
+                    f.span(),
+                    format!("bits must be in 1..={}!", unit_bits),
+                ));
+            }
+            if let Some((_, Item::Bits(unit_ty, unit_endian, bits))) = items.last_mut() {
+                if attr.pad == 0
+                    && *unit_ty == ty
+                    && *unit_endian == endian
+                    && used_bits + width <= unit_bits
+                {
+                    bits.push((ident, used_bits, width));
+                    used_bits += width;
+                    continue;
+                }
+            }
+            items.push((attr.pad, Item::Bits(ty, endian, vec![(ident, 0, width)])));
+            used_bits = width;
+        } else if scalar_bits(&ty).is_some() {
+            items.push((attr.pad, Item::Scalar(ident, ty, endian)))
+        } else if is_bytes(&ty) {
+            items.push((attr.pad, Item::Bytes(ident, ty)))
+        } else {
+            items.push((attr.pad, Item::Nested(ident, ty)))
+        }
+    }
+    let mut offset = quote! {0};
+    let mut decodes = quote! {};
+    let mut encodes = quote! {};
+    for (pad, item) in items.iter() {
+        let start = quote! {(#offset + #pad)};
+        let size = item.size();
+        let range = quote! {#start..#start + #size};
+        let decode = item.decode(&range);
+        let encode = item.encode(&range);
+        decodes = quote! {#decodes #decode};
+        encodes = quote! {#encodes #encode};
+        offset = quote! {#start + #size};
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics Layout for #name #ty_generics #where_clause {
+            const SIZE: usize = #offset;
+
+            fn decode(data: &[u8]) -> Self {
+                #name {
+                    #decodes
+                }
+            }
+
+            fn encode(&self, data: &mut [u8]) {
+                #encodes
+            }
+        }
+    })
+}
@@ -7,8 +7,25 @@ use proc_macro2::{Ident, Span};
 use syn::parse::{Parse, ParseStream, Result};
 use syn::punctuated::Punctuated;
 use syn::Token;
-use syn::{parse_macro_input, Error};
+use syn::{parse_macro_input, DeriveInput, Error};
 
+mod layout;
+
+mod reg;
+
+//little endian by default, #[layout(be)] on the struct or a field changes it,
+//#[layout(pad = N)] skips N bytes before a field, #[layout(bits = N)] packs integer fields into bitfields
+#[proc_macro_derive(Layout, attributes(layout))]
+pub fn derive_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match layout::expand(input) {
+        Ok(t) => t.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+//on a struct, listed traits are implemented by hand and the others panic,
+//on an impl, methods marked #[reg(offset = N, width = N)] become the handlers of the unlisted traits
 #[proc_macro_attribute]
 pub fn derive_io(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(args as Args);
@@ -18,8 +35,14 @@ pub fn derive_io(args: TokenStream, input: TokenStream) -> TokenStream {
     };
     let data = match item {
         syn::Item::Struct(s) => s,
+        syn::Item::Impl(i) => {
+            return match reg::expand(&args, i) {
+                Ok(t) => t.into(),
+                Err(e) => e.to_compile_error().into(),
+            }
+        }
         _ => {
-            return Error::new(Span::call_site(), "expect struct!")
+            return Error::new(Span::call_site(), "expect struct or impl!")
                 .to_compile_error()
                 .into()
         }
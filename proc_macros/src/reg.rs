@@ -0,0 +1,243 @@
+use super::{AccessTrait, Args};
+use proc_macro2::{Span, TokenStream};
+use syn::spanned::Spanned;
+use syn::{Error, FnArg, ImplItem, ImplItemMethod, ItemImpl, Lit, Meta, NestedMeta, Result};
+
+struct Reg {
+    method: syn::Ident,
+    offset: u64,
+    width: u32,
+    write: bool,
+    span: Span,
+}
+
+impl Reg {
+    fn end(&self) -> u64 {
+        self.offset + (self.width / 8) as u64
+    }
+
+    fn ty(&self) -> syn::Ident {
+        syn::Ident::new(&format!("u{}", self.width), Span::call_site())
+    }
+}
+
+fn parse_reg(method: &ImplItemMethod, attr: &syn::Attribute) -> Result<Reg> {
+    let list = match attr.parse_meta()? {
+        Meta::List(list) => list,
+        m => {
+            return Err(Error::new(
+                m.span(),
+                "expect #[reg(offset = N, width = N)]!",
+            ))
+        }
+    };
+    let mut offset = None;
+    let mut width = None;
+    for item in list.nested.iter() {
+        match item {
+            NestedMeta::Meta(Meta::NameValue(nv)) => {
+                let v = match &nv.lit {
+                    Lit::Int(v) => v.base10_parse::<u64>()?,
+                    l => return Err(Error::new(l.span(), "expect integer!")),
+                };
+                if nv.path.is_ident("offset") {
+                    offset = Some(v)
+                } else if nv.path.is_ident("width") {
+                    width = Some(v as u32)
+                } else {
+                    return Err(Error::new(nv.path.span(), "expect offset or width!"));
+                }
+            }
+            i => return Err(Error::new(i.span(), "expect offset = N or width = N!")),
+        }
+    }
+    let offset = offset.ok_or_else(|| Error::new(list.span(), "offset is missing!"))?;
+    let width = width.ok_or_else(|| Error::new(list.span(), "width is missing!"))?;
+    if ![8, 16, 32, 64].contains(&width) {
+        return Err(Error::new(
+            list.span(),
+            "width must be one of 8, 16, 32, 64!",
+        ));
+    }
+    if offset % (width / 8) as u64 != 0 {
+        return Err(Error::new(list.span(), "offset is not aligned to width!"));
+    }
+    //fn(&self) -> uN reads, fn(&self, uN) writes
+    let write = match method.sig.inputs.len() {
+        1 => false,
+        2 => true,
+        _ => {
+            return Err(Error::new(
+                method.sig.span(),
+                "expect fn(&self) -> uN or fn(&self, uN)!",
+            ))
+        }
+    };
+    if !matches!(method.sig.inputs.first(), Some(FnArg::Receiver(_))) {
+        return Err(Error::new(method.sig.span(), "expect &self receiver!"));
+    }
+    Ok(Reg {
+        method: method.sig.ident.clone(),
+        offset,
+        width,
+        write,
+        span: method.sig.span(),
+    })
+}
+
+fn collect(item: &mut ItemImpl) -> Result<Vec<Reg>> {
+    let mut regs: Vec<Reg> = vec![];
+    for i in item.items.iter_mut() {
+        if let ImplItem::Method(m) = i {
+            let (reg_attrs, attrs) = m
+                .attrs
+                .drain(..)
+                .partition::<Vec<_>, _>(|a| a.path.is_ident("reg"));
+            m.attrs = attrs;
+            for a in reg_attrs.iter() {
+                let reg = parse_reg(m, a)?;
+                if let Some(r) = regs
+                    .iter()
+                    .find(|r| r.write == reg.write && r.offset < reg.end() && reg.offset < r.end())
+                {
+                    return Err(Error::new(
+                        reg.span,
+                        format!(
+                            "{} @{:#x} overlaps {} @{:#x}!",
+                            reg.method, reg.offset, r.method, r.offset
+                        ),
+                    ));
+                }
+                regs.push(reg);
+            }
+        }
+    }
+    Ok(regs)
+}
+
+fn width_impl(name: &str, regs: &[Reg], access: AccessTrait, width: u32) -> TokenStream {
+    let trait_name = access.trait_name();
+    let ty = syn::Ident::new(&format!("u{}", width), Span::call_site());
+    let arm = |write: bool| {
+        regs.iter()
+            .filter(|r| r.width == width && r.write == write)
+            .fold(quote! {}, |acc, r| {
+                let offset = r.offset;
+                let method = &r.method;
+                if write {
+                    quote! {#acc #offset => self.#method(data),}
+                } else {
+                    quote! {#acc #offset => self.#method(),}
+                }
+            })
+    };
+    let write_arms = arm(true);
+    let read_arms = arm(false);
+    let write_msg = format!("{}::write for {} @{{:#x}} not mapped!", trait_name, name);
+    let read_msg = format!("{}::read for {} @{{:#x}} not mapped!", trait_name, name);
+    quote! {
+        fn write(&self, addr: &u64, data: #ty) {
+            match *addr {
+                #write_arms
+                _ => panic!(#write_msg, *addr),
+            }
+        }
+
+        fn read(&self, addr: &u64) -> #ty {
+            match *addr {
+                #read_arms
+                _ => panic!(#read_msg, *addr),
+            }
+        }
+    }
+}
+
+//bytes accesses must hit one register with its exact size
+fn bytes_impl(name: &str, regs: &[Reg]) -> TokenStream {
+    let arm = |write: bool| {
+        regs.iter()
+            .filter(|r| r.write == write)
+            .fold(quote! {}, |acc, r| {
+                let offset = r.offset;
+                let size = (r.width / 8) as usize;
+                let method = &r.method;
+                let ty = r.ty();
+                if write {
+                    quote! {
+                        #acc
+                        (#offset, #size) => self.#method(
+                            #ty::from_le_bytes(std::convert::TryInto::try_into(data).unwrap())
+                        ),
+                    }
+                } else {
+                    quote! {#acc (#offset, #size) => data.copy_from_slice(&self.#method().to_le_bytes()),}
+                }
+            })
+    };
+    let write_arms = arm(true);
+    let read_arms = arm(false);
+    let write_msg = format!(
+        "BytesAccess::write for {} @{{:#x}} size {{}} not mapped!",
+        name
+    );
+    let read_msg = format!(
+        "BytesAccess::read for {} @{{:#x}} size {{}} not mapped!",
+        name
+    );
+    quote! {
+        fn write(&self, addr: &u64, data: &[u8]) -> std::result::Result<usize, String> {
+            match (*addr, data.len()) {
+                #write_arms
+                _ => return Err(format!(#write_msg, *addr, data.len())),
+            }
+            Ok(data.len())
+        }
+
+        fn read(&self, addr: &u64, data: &mut [u8]) -> std::result::Result<usize, String> {
+            match (*addr, data.len()) {
+                #read_arms
+                _ => return Err(format!(#read_msg, *addr, data.len())),
+            }
+            Ok(data.len())
+        }
+    }
+}
+
+//traits listed in args are implemented by hand, the others dispatch to #[reg] methods
+pub fn expand(args: &Args, mut item: ItemImpl) -> Result<TokenStream> {
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(Error::new(path.span(), "expect inherent impl!"));
+    }
+    let regs = collect(&mut item)?;
+    let self_ty = &item.self_ty;
+    let name = quote! {#self_ty}.to_string().replace(' ', "");
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+    let impls = [
+        (AccessTrait::U8, 8),
+        (AccessTrait::U16, 16),
+        (AccessTrait::U32, 32),
+        (AccessTrait::U64, 64),
+        (AccessTrait::Bytes, 0),
+    ]
+    .iter()
+    .filter(|(t, _)| !args.0.iter().any(|a| a == t))
+    .fold(quote! {}, |acc, (t, width)| {
+        let trait_name = t.trait_name();
+        let content = if *t == AccessTrait::Bytes {
+            bytes_impl(&name, &regs)
+        } else {
+            width_impl(&name, &regs, *t, *width)
+        };
+        quote! {
+            #acc
+            impl #impl_generics #trait_name for #self_ty #where_clause {
+                #content
+            }
+        }
+    });
+    Ok(quote! {
+        #item
+        #impls
+        impl #impl_generics IOAccess for #self_ty #where_clause {}
+    })
+}
@@ -0,0 +1,369 @@
+use crate::memory::region::{BytesAccess, Region};
+use std::cell::{Cell, RefCell};
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Replace {
+    Lru,
+    Fifo,
+    Random,
+}
+
+impl TryFrom<u32> for Replace {
+    type Error = String;
+    fn try_from(v: u32) -> Result<Replace, String> {
+        match v {
+            0 => Ok(Replace::Lru),
+            1 => Ok(Replace::Fifo),
+            2 => Ok(Replace::Random),
+            _ => Err(format!("invalid replace policy {}!", v)),
+        }
+    }
+}
+
+//write back allocates lines on write miss, write through does not
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WritePolicy {
+    WriteBack,
+    WriteThrough,
+}
+
+impl TryFrom<u32> for WritePolicy {
+    type Error = String;
+    fn try_from(v: u32) -> Result<WritePolicy, String> {
+        match v {
+            0 => Ok(WritePolicy::WriteBack),
+            1 => Ok(WritePolicy::WriteThrough),
+            _ => Err(format!("invalid write policy {}!", v)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CacheConfig {
+    pub size: usize,
+    pub ways: usize,
+    pub line: usize,
+    pub replace: Replace,
+    pub write: WritePolicy,
+    //lines hold their own data, otherwise only tags are tracked and memory is always up to date
+    pub buffered: bool,
+}
+
+impl CacheConfig {
+    fn check(&self) -> Result<(), String> {
+        if !self.line.is_power_of_two() {
+            return Err(format!("line size {} is not power of 2!", self.line));
+        }
+        if self.ways == 0 || self.size == 0 || !self.size.is_multiple_of(self.line * self.ways) {
+            return Err(format!(
+                "size {} is not a multiple of {} ways x {} bytes line!",
+                self.size, self.ways, self.line
+            ));
+        }
+        Ok(())
+    }
+
+    fn sets(&self) -> usize {
+        self.size / self.line / self.ways
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub writebacks: u64,
+}
+
+struct Line {
+    //line address, the region it was filled from
+    addr: u64,
+    region: Rc<Region>,
+    dirty: bool,
+    filled: u64,
+    used: u64,
+    data: Vec<u8>,
+}
+
+pub struct Cache {
+    config: CacheConfig,
+    sets: RefCell<Vec<Vec<Option<Line>>>>,
+    stats: Cell<CacheStats>,
+    clock: Cell<u64>,
+    seed: Cell<u64>,
+}
+
+impl Cache {
+    pub fn new(config: CacheConfig) -> Result<Cache, String> {
+        config.check()?;
+        let sets = (0..config.sets())
+            .map(|_| (0..config.ways).map(|_| None).collect())
+            .collect();
+        Ok(Cache {
+            config,
+            sets: RefCell::new(sets),
+            stats: Cell::new(CacheStats::default()),
+            clock: Cell::new(0),
+            seed: Cell::new(0x2545_f491_4f6c_dd1d),
+        })
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.set(CacheStats::default())
+    }
+
+    fn update<F: FnOnce(&mut CacheStats)>(&self, f: F) {
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats)
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.set(self.clock.get() + 1);
+        self.clock.get()
+    }
+
+    fn random(&self) -> u64 {
+        let mut x = self.seed.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed.set(x);
+        x
+    }
+
+    fn line_of(&self, addr: u64) -> u64 {
+        addr & !(self.config.line as u64 - 1)
+    }
+
+    fn set_of(&self, line: u64) -> usize {
+        ((line / self.config.line as u64) % self.config.sets() as u64) as usize
+    }
+
+    fn victim(&self, set: &[Option<Line>]) -> usize {
+        if let Some(way) = set.iter().position(|l| l.is_none()) {
+            return way;
+        }
+        let lines = set.iter().map(|l| l.as_ref().unwrap());
+        match self.config.replace {
+            Replace::Lru => lines.enumerate().min_by_key(|(_, l)| l.used).unwrap().0,
+            Replace::Fifo => lines.enumerate().min_by_key(|(_, l)| l.filled).unwrap().0,
+            Replace::Random => (self.random() % self.config.ways as u64) as usize,
+        }
+    }
+
+    fn write_back(&self, line: &Line) -> Result<(), u64> {
+        self.update(|s| s.writebacks += 1);
+        if self.config.buffered {
+            BytesAccess::write(line.region.as_ref(), &line.addr, &line.data)
+                .map_err(|_| line.addr)?;
+        }
+        Ok(())
+    }
+
+    //index of the way holding line, filled on miss if allocate
+    fn lookup(&self, region: &Rc<Region>, line: u64, allocate: bool) -> Result<Option<usize>, u64> {
+        let mut sets = self.sets.borrow_mut();
+        let set = &mut sets[self.set_of(line)];
+        let now = self.tick();
+        //another region mapped at the same address never hits
+        if let Some(way) = set.iter().position(|l| {
+            l.as_ref()
+                .is_some_and(|l| l.addr == line && Rc::ptr_eq(&l.region, region))
+        }) {
+            self.update(|s| s.hits += 1);
+            set[way].as_mut().unwrap().used = now;
+            return Ok(Some(way));
+        }
+        self.update(|s| s.misses += 1);
+        if !allocate {
+            return Ok(None);
+        }
+        let mut data = vec![];
+        if self.config.buffered {
+            data.resize(self.config.line, 0);
+            BytesAccess::read(region.as_ref(), &line, &mut data).map_err(|_| line)?;
+        }
+        //the victim stays cached if its write back fails
+        let way = self.victim(set);
+        if let Some(old) = set[way].as_mut() {
+            if old.dirty {
+                self.write_back(old)?;
+                old.dirty = false
+            }
+            self.update(|s| s.evictions += 1);
+        }
+        set[way] = Some(Line {
+            addr: line,
+            region: Rc::clone(region),
+            dirty: false,
+            filled: now,
+            used: now,
+            data,
+        });
+        Ok(Some(way))
+    }
+
+    //lines crossing the region boundary are not cached
+    fn cacheable(&self, region: &Region, line: u64) -> bool {
+        !region.is_io()
+            && line >= region.info.base
+            && line + self.config.line as u64 <= region.info.base + region.info.size
+    }
+
+    fn chunks(&self, addr: u64, size: usize) -> Vec<(u64, usize, usize)> {
+        let mut chunks = vec![];
+        let mut offset = 0;
+        while offset < size {
+            let a = addr + offset as u64;
+            let len = std::cmp::min(
+                size - offset,
+                (self.line_of(a) + self.config.line as u64 - a) as usize,
+            );
+            chunks.push((a, offset, len));
+            offset += len;
+        }
+        chunks
+    }
+
+    pub(crate) fn read(&self, region: &Rc<Region>, addr: &u64, data: &mut [u8]) -> Result<(), u64> {
+        for (a, offset, len) in self.chunks(*addr, data.len()) {
+            let buf = &mut data[offset..offset + len];
+            let line = self.line_of(a);
+            if !self.cacheable(region, line) {
+                BytesAccess::read(region.as_ref(), &a, buf).map_err(|_| a)?;
+                continue;
+            }
+            let way = self.lookup(region, line, true)?.unwrap();
+            if self.config.buffered {
+                let sets = self.sets.borrow();
+                let l = sets[self.set_of(line)][way].as_ref().unwrap();
+                let start = (a - line) as usize;
+                buf.copy_from_slice(&l.data[start..start + len]);
+            } else {
+                BytesAccess::read(region.as_ref(), &a, buf).map_err(|_| a)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write(&self, region: &Rc<Region>, addr: &u64, data: &[u8]) -> Result<(), u64> {
+        let write_back = self.config.write == WritePolicy::WriteBack;
+        for (a, offset, len) in self.chunks(*addr, data.len()) {
+            let buf = &data[offset..offset + len];
+            let line = self.line_of(a);
+            if !self.cacheable(region, line) {
+                BytesAccess::write(region.as_ref(), &a, buf).map_err(|_| a)?;
+                continue;
+            }
+            if let Some(way) = self.lookup(region, line, write_back)? {
+                let mut sets = self.sets.borrow_mut();
+                let l = sets[self.set_of(line)][way].as_mut().unwrap();
+                if self.config.buffered {
+                    let start = (a - line) as usize;
+                    l.data[start..start + len].copy_from_slice(buf);
+                }
+                l.dirty |= write_back;
+            }
+            if !write_back || !self.config.buffered {
+                BytesAccess::write(region.as_ref(), &a, buf).map_err(|_| a)?;
+            }
+        }
+        Ok(())
+    }
+
+    //apply f to the picked lines, the line is dropped if f returns false
+    //lines are dropped only after f succeeds on all of them, a failure drops nothing
+    fn maintain<P: Fn(&Line) -> bool, F: Fn(&Line) -> Result<bool, u64>>(
+        &self,
+        pick: P,
+        f: F,
+    ) -> Result<(), u64> {
+        let mut sets = self.sets.borrow_mut();
+        let mut dropped = vec![];
+        for (i, set) in sets.iter_mut().enumerate() {
+            for (way, slot) in set.iter_mut().enumerate() {
+                if let Some(l) = slot.as_mut().filter(|l| pick(l)) {
+                    if f(l)? {
+                        l.dirty = false
+                    } else {
+                        dropped.push((i, way))
+                    }
+                }
+            }
+        }
+        for (i, way) in dropped {
+            sets[i][way] = None
+        }
+        Ok(())
+    }
+
+    fn overlaps(&self, l: &Line, addr: u64, size: u64) -> bool {
+        l.addr + self.config.line as u64 > addr && l.addr < addr.saturating_add(size)
+    }
+
+    fn flush_line(&self, l: &Line) -> Result<bool, u64> {
+        if l.dirty {
+            self.write_back(l)?
+        }
+        Ok(false)
+    }
+
+    //write dirty lines back to memory and keep them
+    pub fn clean(&self, addr: u64, size: u64) -> Result<(), u64> {
+        self.maintain(
+            |l| self.overlaps(l, addr, size),
+            |l| {
+                if l.dirty {
+                    self.write_back(l)?
+                }
+                Ok(true)
+            },
+        )
+    }
+
+    //drop lines, dirty data is lost
+    pub fn invalidate(&self, addr: u64, size: u64) -> Result<(), u64> {
+        self.maintain(|l| self.overlaps(l, addr, size), |_| Ok(false))
+    }
+
+    //clean and invalidate
+    pub fn flush(&self, addr: u64, size: u64) -> Result<(), u64> {
+        self.maintain(|l| self.overlaps(l, addr, size), |l| self.flush_line(l))
+    }
+
+    //flush the lines filled from region, before it is unmapped or moved
+    pub(crate) fn flush_region(&self, region: &Rc<Region>) -> Result<(), u64> {
+        self.maintain(|l| Rc::ptr_eq(&l.region, region), |l| self.flush_line(l))
+    }
+
+    pub fn flush_all(&self) -> Result<(), u64> {
+        self.flush(0, u64::MAX)
+    }
+
+    pub fn is_cached(&self, addr: u64) -> bool {
+        let line = self.line_of(addr);
+        self.sets.borrow()[self.set_of(line)]
+            .iter()
+            .any(|l| l.as_ref().is_some_and(|l| l.addr == line))
+    }
+
+    pub fn is_dirty(&self, addr: u64) -> bool {
+        let line = self.line_of(addr);
+        self.sets.borrow()[self.set_of(line)]
+            .iter()
+            .any(|l| l.as_ref().is_some_and(|l| l.addr == line && l.dirty))
+    }
+}
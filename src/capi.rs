@@ -1,27 +1,96 @@
+use crate::cache::{Cache, CacheConfig, CacheStats, Replace, WritePolicy};
+use crate::deferred::{FixedLatency, Latency, Response};
 use crate::memory::allocator::*;
 use crate::memory::region::*;
 use crate::memory::MemInfo;
-use crate::space::Space;
+use crate::perf::{PerfModel, Timing};
+use crate::regmap::import;
+use crate::sched;
+use crate::space::{Attr, Space};
+use crate::views::Views;
 use std::any::Any;
+use std::cell::RefCell;
+use std::convert::TryFrom;
 use std::ffi::{c_void, CStr};
+use std::fs::File;
+use std::io::BufReader;
 use std::ops::Deref;
 use std::os::raw::c_char;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 #[no_mangle]
-extern "C" fn __ts_new_allocator(base: u64, size: u64) -> *const c_void {
+extern "C" fn __ts_new_allocator(base: u64, size: u64, policy: u32) -> *const c_void {
+    let policy = Policy::try_from(policy).unwrap();
     Box::into_raw(Box::new(
-        Box::new(Allocator::new(base, size)) as Box<dyn Any>
+        Box::new(Allocator::with_policy(base, size, policy)) as Box<dyn Any>,
     )) as *const c_void
 }
 
 #[no_mangle]
-extern "C" fn __ts_new_locked_allocator(base: u64, size: u64) -> *const c_void {
+extern "C" fn __ts_new_locked_allocator(base: u64, size: u64, policy: u32) -> *const c_void {
+    let policy = Policy::try_from(policy).unwrap();
     Box::into_raw(Box::new(
-        Box::new(LockedAllocator::new(base, size)) as Box<dyn Any>
+        Box::new(LockedAllocator::with_policy(base, size, policy)) as Box<dyn Any>,
     )) as *const c_void
 }
 
+#[no_mangle]
+extern "C" fn __ts_new_pool_allocator() -> *const c_void {
+    Box::into_raw(Box::new(Box::new(PoolAllocator::new()) as Box<dyn Any>)) as *const c_void
+}
+
+#[no_mangle]
+extern "C" fn __ts_add_pool(
+    a: &mut Box<dyn Any>,
+    name: *const c_char,
+    base: u64,
+    size: u64,
+    dma: bool,
+    cacheable: bool,
+    policy: u32,
+) -> i32 {
+    let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
+    let policy = Policy::try_from(policy).unwrap();
+    let allocator = a
+        .downcast_mut::<PoolAllocator>()
+        .expect("wrong type!allocator should be create by ts_new_pool_allocator!");
+    status(&allocator.add_pool(name, base, size, PoolAttr { dma, cacheable }, policy))
+}
+
+//limit 0 means no limit, negative dma or cacheable means don't care, NULL pool means any pool
+#[no_mangle]
+extern "C" fn __ts_alloc_pool_addr(
+    a: &mut Box<dyn Any>,
+    size: u64,
+    align: u64,
+    limit: u64,
+    dma: i32,
+    cacheable: i32,
+    pool: *const c_char,
+) -> u64 {
+    let mut constraint = Constraint::new();
+    if limit != 0 {
+        constraint = constraint.below(limit)
+    }
+    if dma >= 0 {
+        constraint = constraint.dma(dma != 0)
+    }
+    if cacheable >= 0 {
+        constraint = constraint.cacheable(cacheable != 0)
+    }
+    if !pool.is_null() {
+        constraint = constraint.in_pool(unsafe { CStr::from_ptr(pool).to_str().unwrap() })
+    }
+    let allocator = a
+        .downcast_mut::<PoolAllocator>()
+        .expect("wrong type!allocator should be create by ts_new_pool_allocator!");
+    if let Some(info) = allocator.alloc(size, align, &constraint) {
+        info.base
+    } else {
+        panic!("oom!{:?}", constraint)
+    }
+}
+
 #[no_mangle]
 //safe pointer style
 extern "C" fn __ts_alloc_addr(a: &mut Box<dyn Any>, size: u64, align: u64) -> u64 {
@@ -29,6 +98,8 @@ extern "C" fn __ts_alloc_addr(a: &mut Box<dyn Any>, size: u64, align: u64) -> u6
         allocator.alloc(size, align)
     } else if let Some(allocator) = a.downcast_mut::<LockedAllocator>() {
         allocator.alloc(size, align)
+    } else if let Some(allocator) = a.downcast_mut::<PoolAllocator>() {
+        allocator.alloc(size, align, &Constraint::new())
     } else {
         panic!(
             "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
@@ -44,13 +115,202 @@ extern "C" fn __ts_alloc_addr(a: &mut Box<dyn Any>, size: u64, align: u64) -> u6
 
 #[no_mangle]
 //unsafe raw pointer style
-extern "C" fn __ts_free_addr(a: *mut c_void, addr: u64) {
+extern "C" fn __ts_free_addr(a: *mut c_void, addr: u64) -> i32 {
     let abox = unsafe { &mut *(a as *mut Box<dyn Any>) };
 
-    if let Some(allocator) = abox.downcast_mut::<Allocator>() {
+    let result = if let Some(allocator) = abox.downcast_mut::<Allocator>() {
         allocator.free(addr)
     } else if let Some(allocator) = abox.downcast_mut::<LockedAllocator>() {
         allocator.free(addr)
+    } else if let Some(allocator) = abox.downcast_mut::<PoolAllocator>() {
+        allocator.free(addr)
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    };
+
+    status(&result)
+}
+
+fn status<T>(result: &Result<T, Error>) -> i32 {
+    match result {
+        Ok(_) => 0,
+        Err(Error::DoubleFree(_)) => 1,
+        Err(Error::NotAllocated(_)) => 2,
+        Err(Error::InteriorPointer(_, _)) => 3,
+        Err(Error::OutOfMemory(_)) => 4,
+        Err(Error::OutOfRange(_)) => 5,
+        Err(Error::Occupied(_, _)) => 6,
+        Err(Error::Reserved(_, _)) => 7,
+        Err(Error::DuplicatePool(_)) => 8,
+        Err(Error::Shared(_)) => 9,
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_realloc_addr(
+    a: &mut Box<dyn Any>,
+    addr: u64,
+    size: u64,
+    align: u64,
+    new_addr: &mut u64,
+) -> i32 {
+    let result = if let Some(allocator) = a.downcast_mut::<Allocator>() {
+        allocator.realloc(addr, size, align)
+    } else if let Some(allocator) = a.downcast_mut::<LockedAllocator>() {
+        allocator.realloc(addr, size, align)
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    };
+    if let Ok(info) = result {
+        *new_addr = info.base
+    }
+    status(&result)
+}
+
+#[no_mangle]
+extern "C" fn __ts_alloc_addr_at(a: &mut Box<dyn Any>, base: u64, size: u64) -> i32 {
+    let result = if let Some(allocator) = a.downcast_mut::<Allocator>() {
+        allocator.alloc_at(base, size)
+    } else if let Some(allocator) = a.downcast_mut::<LockedAllocator>() {
+        allocator.alloc_at(base, size)
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    };
+    status(&result)
+}
+
+#[no_mangle]
+extern "C" fn __ts_reserve_addr(a: &mut Box<dyn Any>, base: u64, size: u64) -> i32 {
+    let result = if let Some(allocator) = a.downcast_mut::<Allocator>() {
+        allocator.reserve(base, size)
+    } else if let Some(allocator) = a.downcast_mut::<LockedAllocator>() {
+        allocator.reserve(base, size)
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    };
+    status(&result)
+}
+
+#[no_mangle]
+extern "C" fn __ts_allocator_set_seed(a: &mut Box<dyn Any>, seed: u64) {
+    if let Some(allocator) = a.downcast_mut::<Allocator>() {
+        allocator.set_seed(seed)
+    } else if let Some(allocator) = a.downcast_mut::<LockedAllocator>() {
+        allocator.set_seed(seed)
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_allocator_save(a: &mut Box<dyn Any>, path: *const c_char) {
+    let path = unsafe { CStr::from_ptr(path).to_str().unwrap() };
+    let mut file = File::create(path).unwrap();
+    if let Some(allocator) = a.downcast_mut::<Allocator>() {
+        allocator.save(&mut file).unwrap()
+    } else if let Some(allocator) = a.downcast_mut::<LockedAllocator>() {
+        allocator.save(&mut file).unwrap()
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_allocator_restore(path: *const c_char, locked: bool) -> *const c_void {
+    let path = unsafe { CStr::from_ptr(path).to_str().unwrap() };
+    let allocator = match Allocator::restore(BufReader::new(File::open(path).unwrap())) {
+        Ok(allocator) => allocator,
+        Err(msg) => panic!("restore {} fail: {}", path, msg),
+    };
+    let allocator = if locked {
+        Box::new(LockedAllocator::from(allocator)) as Box<dyn Any>
+    } else {
+        Box::new(allocator) as Box<dyn Any>
+    };
+    Box::into_raw(Box::new(allocator)) as *const c_void
+}
+
+//unbuffered, c allocators are never dropped
+#[no_mangle]
+extern "C" fn __ts_allocator_start_log(a: &mut Box<dyn Any>, path: *const c_char) {
+    let path = unsafe { CStr::from_ptr(path).to_str().unwrap() };
+    let file = Box::new(File::create(path).unwrap());
+    if let Some(allocator) = a.downcast_mut::<Allocator>() {
+        allocator.start_log(file).unwrap()
+    } else if let Some(allocator) = a.downcast_mut::<LockedAllocator>() {
+        allocator.start_log(file).unwrap()
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_allocator_stop_log(a: &mut Box<dyn Any>) {
+    if let Some(allocator) = a.downcast_mut::<Allocator>() {
+        allocator.stop_log();
+    } else if let Some(allocator) = a.downcast_mut::<LockedAllocator>() {
+        allocator.stop_log();
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_allocator_set_strict(a: &mut Box<dyn Any>, strict: bool) {
+    if let Some(allocator) = a.downcast_mut::<Allocator>() {
+        allocator.set_strict(strict)
+    } else if let Some(allocator) = a.downcast_mut::<LockedAllocator>() {
+        allocator.set_strict(strict)
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    }
+}
+
+#[cfg(feature = "memprof")]
+#[no_mangle]
+extern "C" fn __ts_allocator_stats(a: &Box<dyn Any>, stats: &mut Stats) {
+    *stats = if let Some(allocator) = a.downcast_ref::<Allocator>() {
+        allocator.stats()
+    } else if let Some(allocator) = a.downcast_ref::<LockedAllocator>() {
+        allocator.stats()
+    } else {
+        panic!(
+            "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
+        )
+    }
+}
+
+#[cfg(not(feature = "memprof"))]
+#[no_mangle]
+extern "C" fn __ts_allocator_stats(_: &Box<dyn Any>, _: &mut Stats) {
+    panic!("allocation statistics need the memprof feature!")
+}
+
+#[cfg(feature = "memprof")]
+#[no_mangle]
+extern "C" fn __ts_allocator_report(a: &Box<dyn Any>) {
+    if let Some(allocator) = a.downcast_ref::<Allocator>() {
+        print!("{}", allocator.report())
+    } else if let Some(allocator) = a.downcast_ref::<LockedAllocator>() {
+        print!("{}", allocator.report())
     } else {
         panic!(
             "wrong type!allocator should be create by ts_new_allocator or ts_new_locked_allocator!"
@@ -58,11 +318,22 @@ extern "C" fn __ts_free_addr(a: *mut c_void, addr: u64) {
     }
 }
 
+#[cfg(not(feature = "memprof"))]
+#[no_mangle]
+extern "C" fn __ts_allocator_report(_: &Box<dyn Any>) {
+    panic!("allocation statistics need the memprof feature!")
+}
+
 #[no_mangle]
 extern "C" fn __ts_space() -> *mut Space {
     Box::into_raw(Box::new(Space::new()))
 }
 
+#[no_mangle]
+extern "C" fn __ts_free_space(space: *mut Space) {
+    std::mem::drop(unsafe { Box::from_raw(space) })
+}
+
 #[no_mangle]
 extern "C" fn __ts_add_region(
     space: &mut Space,
@@ -76,6 +347,21 @@ extern "C" fn __ts_add_region(
     }
 }
 
+#[no_mangle]
+extern "C" fn __ts_add_region_with_attr(
+    space: &mut Space,
+    name: *const c_char,
+    region: &Box<Rc<Region>>,
+    readable: bool,
+    writable: bool,
+) -> *const Box<Rc<Region>> {
+    let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
+    match space.add_region_with_attr(name, region.deref(), Attr { readable, writable }) {
+        Ok(r) => to_c_ptr(r),
+        Err(e) => panic!("{:?}", e),
+    }
+}
+
 #[no_mangle]
 extern "C" fn __ts_clean_region(
     space: &mut Space,
@@ -85,6 +371,15 @@ extern "C" fn __ts_clean_region(
     space.clean(unsafe { CStr::from_ptr(name).to_str().unwrap() }, region)
 }
 
+#[no_mangle]
+extern "C" fn __ts_track_region(
+    space: &mut Space,
+    name: *const c_char,
+    region: *mut Box<Rc<Region>>,
+) {
+    space.track(unsafe { CStr::from_ptr(name).to_str().unwrap() }, region)
+}
+
 #[no_mangle]
 extern "C" fn __ts_get_region(space: &Space, name: *const c_char) -> *const Box<Rc<Region>> {
     let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
@@ -100,23 +395,150 @@ extern "C" fn __ts_delete_region(space: &mut Space, name: *const c_char) {
     space.delete_region(unsafe { CStr::from_ptr(name).to_str().unwrap() })
 }
 
+#[no_mangle]
+extern "C" fn __ts_replace_region(
+    space: &mut Space,
+    name: *const c_char,
+    region: &Box<Rc<Region>>,
+) -> *const Box<Rc<Region>> {
+    let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
+    match space.replace_region(name, region.deref()) {
+        Ok(_) => to_c_ptr(Rc::clone(region.deref())),
+        Err(e) => panic!("{:?}", e),
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_move_region(
+    space: &mut Space,
+    name: *const c_char,
+    base: u64,
+) -> *const Box<Rc<Region>> {
+    let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
+    match space.move_region(name, base) {
+        Ok(_) => to_c_ptr(space.get_region(name).unwrap()),
+        Err(e) => panic!("{:?}", e),
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_region_count(space: &Space) -> u64 {
+    space.iter().count() as u64
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_region_name(space: &Space, idx: u64) -> *const c_char {
+    if let Some((name, _)) = space.nth_region(idx as usize) {
+        name.as_ptr()
+    } else {
+        panic!("no region @{}", idx)
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_region_info(space: &Space, idx: u64) -> *const MemInfo {
+    if let Some((_, region)) = space.nth_region(idx as usize) {
+        Box::into_raw(Box::new(region.info))
+    } else {
+        panic!("no region @{}", idx)
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_free_gap(space: &Space, size: u64, align: u64) -> u64 {
+    if let Some(base) = space.free_gap(size, align) {
+        base
+    } else {
+        panic!("no free gap for size {:#x} align {:#x}!", size, align)
+    }
+}
+
+//format: 0 IP-XACT xml, 1 SystemRDL json
+//returns the number of blocks added, -1 if nothing is added
+#[no_mangle]
+extern "C" fn __ts_space_import_regmap(space: &mut Space, path: *const c_char, format: u32) -> i64 {
+    let path = unsafe { CStr::from_ptr(path).to_str().unwrap() };
+    let result = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| match format {
+            0 => import::ipxact(&content),
+            1 => import::systemrdl(&content),
+            _ => Err(format!("invalid regmap format {}!", format)),
+        })
+        .and_then(|blocks| import::add_to_space(space, blocks));
+    match result {
+        Ok(blocks) => blocks.len() as i64,
+        Err(e) => {
+            eprintln!("import regmap {} fail: {}", path, e);
+            -1
+        }
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_export_json(space: &Space, path: *const c_char) {
+    let path = unsafe { CStr::from_ptr(path).to_str().unwrap() };
+    std::fs::write(path, space.to_json()).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_export_csv(space: &Space, path: *const c_char) {
+    let path = unsafe { CStr::from_ptr(path).to_str().unwrap() };
+    std::fs::write(path, space.to_csv()).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+#[no_mangle]
+extern "C" fn __ts_views() -> *mut Views {
+    Box::into_raw(Box::new(Views::new()))
+}
+
+#[no_mangle]
+extern "C" fn __ts_add_view(views: &mut Views, name: *const c_char) -> *mut Space {
+    let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
+    match views.add_view(name) {
+        Ok(space) => space as *mut Space,
+        Err(e) => panic!("{:?}", e),
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_get_view(views: &mut Views, name: *const c_char) -> *mut Space {
+    let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
+    if let Some(space) = views.get_view_mut(name) {
+        space as *mut Space
+    } else {
+        panic!("no view {}", name)
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_views_trace(views: &Views, enable: bool) {
+    views.tracer().enable(enable)
+}
+
+#[no_mangle]
+extern "C" fn __ts_views_issuer(views: &Views, addr: u64) -> *const c_char {
+    if let Some(name) = views.issuer_cstr(&addr) {
+        name.as_ptr()
+    } else {
+        std::ptr::null()
+    }
+}
+
 #[no_mangle]
 extern "C" fn __ts_alloc_region(
     heap: *const Box<Rc<Heap>>,
     size: u64,
     align: u64,
-    lazy: bool,
+    storage: u32,
 ) -> *const Box<Rc<Region>> {
+    let storage = Storage::try_from(storage).unwrap();
     match unsafe {
         if heap.is_null() {
-            if lazy {
-                GHEAP.lazy_alloc(size, align)
-            } else {
-                GHEAP.alloc(size, align)
-            }
+            GHEAP.alloc_with_storage(size, align, storage)
         } else {
             let p = heap.as_ref().unwrap();
-            p.alloc(size, align)
+            p.alloc_with_storage(size, align, storage)
         }
     } {
         Ok(region) => to_c_ptr(region),
@@ -124,6 +546,85 @@ extern "C" fn __ts_alloc_region(
     }
 }
 
+#[no_mangle]
+extern "C" fn __ts_alloc_region_tagged(
+    heap: *const Box<Rc<Heap>>,
+    size: u64,
+    align: u64,
+    storage: u32,
+    tag: *const c_char,
+    file: *const c_char,
+    line: u32,
+) -> *const Box<Rc<Region>> {
+    let region = __ts_alloc_region(heap, size, align, storage);
+    let name = unsafe { CStr::from_ptr(tag).to_str().unwrap() };
+    let tag = if file.is_null() {
+        Tag::new(name)
+    } else {
+        let file = unsafe { CStr::from_ptr(file).to_str().unwrap() };
+        Tag::with_location(name, &format!("{}:{}", file, line))
+    };
+    let base = unsafe { (&*region).info.base };
+    if let Some(heap) = unsafe { heap.as_ref() } {
+        heap.set_tag(base, tag);
+    } else {
+        GHEAP.set_tag(base, tag);
+    }
+    region
+}
+
+#[no_mangle]
+extern "C" fn __ts_alloc_region_at(
+    heap: *const Box<Rc<Heap>>,
+    base: u64,
+    size: u64,
+) -> *const Box<Rc<Region>> {
+    match unsafe {
+        if let Some(heap) = heap.as_ref() {
+            heap.alloc_at(base, size)
+        } else {
+            GHEAP.alloc_at(base, size)
+        }
+    } {
+        Ok(region) => to_c_ptr(region),
+        Err(msg) => panic!("{}", msg),
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_heap_reserve(heap: *const Box<Rc<Heap>>, base: u64, size: u64) {
+    if let Err(msg) = unsafe {
+        if let Some(heap) = heap.as_ref() {
+            heap.reserve(base, size)
+        } else {
+            GHEAP.reserve(base, size)
+        }
+    } {
+        panic!("{}", msg)
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_realloc_region(
+    heap: &Box<Rc<Heap>>,
+    region: &mut Box<Rc<Region>>,
+    size: u64,
+    align: u64,
+) -> i32 {
+    status(&heap.realloc(region, size, align))
+}
+
+#[no_mangle]
+extern "C" fn __ts_heap_leak_report(heap: *const Box<Rc<Heap>>) -> u64 {
+    let (leaks, report) = if let Some(heap) = unsafe { heap.as_ref() } {
+        (heap.leaks().len(), heap.leak_report())
+    } else {
+        (GHEAP.leaks().len(), GHEAP.leak_report())
+    };
+    print!("{}", report);
+    leaks as u64
+}
+
 #[no_mangle]
 extern "C" fn __ts_free_region(region: *const Box<Rc<Region>>) {
     std::mem::drop(unsafe { region.read() })
@@ -134,9 +635,23 @@ extern "C" fn __ts_region_info(region: &Box<Rc<Region>>) -> *const MemInfo {
     Box::into_raw(Box::new(region.info))
 }
 
+thread_local! {
+    //heaps created from c, the only ones __ts_heaps_reset touches
+    static HEAPS: RefCell<Vec<Weak<Heap>>> = const { RefCell::new(vec![]) };
+}
+
+fn c_heap(heap: Rc<Heap>) -> *const Box<Rc<Heap>> {
+    HEAPS.with(|heaps| {
+        let mut heaps = heaps.borrow_mut();
+        heaps.retain(|h| h.strong_count() > 0);
+        heaps.push(Rc::downgrade(&heap));
+    });
+    Box::into_raw(Box::new(Box::new(heap)))
+}
+
 #[no_mangle]
 extern "C" fn __ts_heap(region: &Box<Rc<Region>>) -> *const Box<Rc<Heap>> {
-    Box::into_raw(Box::new(Box::new(Heap::new(region.deref()))))
+    c_heap(Heap::new(region.deref()))
 }
 
 #[no_mangle]
@@ -144,6 +659,79 @@ extern "C" fn __ts_free_heap(heap: *const Box<Rc<Heap>>) {
     std::mem::drop(unsafe { heap.read() })
 }
 
+#[no_mangle]
+extern "C" fn __ts_scratch_heap(size: u64) -> *const Box<Rc<Heap>> {
+    match Heap::scratch(size) {
+        Ok(heap) => c_heap(heap),
+        Err(msg) => panic!("{}", msg),
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_heap_teardown(heap: &Box<Rc<Heap>>) {
+    heap.teardown()
+}
+
+#[no_mangle]
+extern "C" fn __ts_heap_reset(heap: &Box<Rc<Heap>>) {
+    if let Err(msg) = heap.reset() {
+        panic!("{}", msg)
+    }
+}
+
+//reset every live heap created from c, all of them are checked before any is torn down
+//returns the number of heaps reset, -1 if one of them can not be reset
+#[no_mangle]
+extern "C" fn __ts_heaps_reset() -> i32 {
+    let heaps = HEAPS.with(|heaps| {
+        heaps
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>()
+    });
+    if let Some(msg) = heaps.iter().find_map(|heap| heap.check_reset().err()) {
+        eprintln!("heaps reset fail: {}", msg);
+        return -1;
+    }
+    for heap in &heaps {
+        heap.reset().unwrap()
+    }
+    heaps.len() as i32
+}
+
+#[cfg(feature = "memprof")]
+#[no_mangle]
+extern "C" fn __ts_heap_stats(heap: *const Box<Rc<Heap>>, stats: &mut Stats) {
+    *stats = if let Some(heap) = unsafe { heap.as_ref() } {
+        heap.stats()
+    } else {
+        GHEAP.stats()
+    }
+}
+
+#[cfg(not(feature = "memprof"))]
+#[no_mangle]
+extern "C" fn __ts_heap_stats(_: *const Box<Rc<Heap>>, _: &mut Stats) {
+    panic!("allocation statistics need the memprof feature!")
+}
+
+#[cfg(feature = "memprof")]
+#[no_mangle]
+extern "C" fn __ts_heap_report(heap: *const Box<Rc<Heap>>) {
+    if let Some(heap) = unsafe { heap.as_ref() } {
+        print!("{}", heap.report())
+    } else {
+        print!("{}", GHEAP.report())
+    }
+}
+
+#[cfg(not(feature = "memprof"))]
+#[no_mangle]
+extern "C" fn __ts_heap_report(_: *const Box<Rc<Heap>>) {
+    panic!("allocation statistics need the memprof feature!")
+}
+
 #[no_mangle]
 extern "C" fn __ts_map_region(region: &Box<Rc<Region>>, base: u64) -> *const Box<Rc<Region>> {
     to_c_ptr(Region::remap(base, region.deref()))
@@ -200,45 +788,233 @@ extern "C" fn __ts_region_read_u64(region: &Box<Rc<Region>>, addr: u64) -> u64 {
 }
 
 #[no_mangle]
-extern "C" fn __ts_space_write_u8(space: &Rc<Space>, addr: u64, data: u8) {
+extern "C" fn __ts_space_write_u8(space: &Space, addr: u64, data: u8) {
     space.write_u8(&addr, data).unwrap()
 }
 
 #[no_mangle]
-extern "C" fn __ts_space_write_u16(space: &Rc<Space>, addr: u64, data: u16) {
+extern "C" fn __ts_space_write_u16(space: &Space, addr: u64, data: u16) {
     space.write_u16(&addr, data).unwrap()
 }
 
 #[no_mangle]
-extern "C" fn __ts_space_write_u32(space: &Rc<Space>, addr: u64, data: u32) {
+extern "C" fn __ts_space_write_u32(space: &Space, addr: u64, data: u32) {
     space.write_u32(&addr, data).unwrap()
 }
 
 #[no_mangle]
-extern "C" fn __ts_space_write_u64(space: &Rc<Space>, addr: u64, data: u64) {
+extern "C" fn __ts_space_write_u64(space: &Space, addr: u64, data: u64) {
     space.write_u64(&addr, data).unwrap()
 }
 
 #[no_mangle]
-extern "C" fn __ts_space_read_u8(space: &Rc<Space>, addr: u64) -> u8 {
+extern "C" fn __ts_space_read_u8(space: &Space, addr: u64) -> u8 {
     space.read_u8(&addr).unwrap()
 }
 
 #[no_mangle]
-extern "C" fn __ts_space_read_u16(space: &Rc<Space>, addr: u64) -> u16 {
+extern "C" fn __ts_space_read_u16(space: &Space, addr: u64) -> u16 {
     space.read_u16(&addr).unwrap()
 }
 
 #[no_mangle]
-extern "C" fn __ts_space_read_u32(space: &Rc<Space>, addr: u64) -> u32 {
+extern "C" fn __ts_space_read_u32(space: &Space, addr: u64) -> u32 {
     space.read_u32(&addr).unwrap()
 }
 
 #[no_mangle]
-extern "C" fn __ts_space_read_u64(space: &Rc<Space>, addr: u64) -> u64 {
+extern "C" fn __ts_space_read_u64(space: &Space, addr: u64) -> u64 {
     space.read_u64(&addr).unwrap()
 }
 
+//u64::MAX latency waits for __ts_space_complete
+#[no_mangle]
+extern "C" fn __ts_space_set_latency(
+    space: &mut Space,
+    name: *const c_char,
+    read: u64,
+    write: u64,
+) {
+    let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
+    let to_latency = |l: u64| if l == u64::MAX { None } else { Some(l) };
+    let latency: Rc<dyn Latency> = Rc::new(FixedLatency::new(to_latency(read), to_latency(write)));
+    if let Err(e) = space.set_latency(name, &latency) {
+        panic!("{:?}", e)
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_request_read(space: &Space, addr: u64, size: u32) -> u64 {
+    assert!(size <= 8, "request size {} > 8!", size);
+    space.request_read(&addr, size as usize)
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_request_write(space: &Space, addr: u64, data: u64, size: u32) -> u64 {
+    assert!(size <= 8, "request size {} > 8!", size);
+    space.request_write(&addr, &data.to_le_bytes()[..size as usize])
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_tick(space: &Space, ticks: u64) {
+    space.tick(ticks)
+}
+
+//the space is ticked by __ts_tick from now on, freeing the space detaches it
+#[no_mangle]
+extern "C" fn __ts_space_attach(space: *const Space) -> u64 {
+    unsafe { &*space }.attach_with(&sched::global(), move |cycles| unsafe {
+        (*space).tick(cycles)
+    })
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_complete(space: &Space, token: u64) -> bool {
+    space.complete(token)
+}
+
+//false until the response is ready, read data is zero extended
+#[no_mangle]
+extern "C" fn __ts_space_poll(space: &Space, token: u64, data: &mut u64) -> bool {
+    match space.poll(token) {
+        Some(Response::Read(bytes)) => {
+            let mut buf = [0u8; 8];
+            buf[..bytes.len()].copy_from_slice(&bytes);
+            *data = u64::from_le_bytes(buf);
+            true
+        }
+        Some(Response::Write(_)) => true,
+        Some(Response::Err(addr)) => panic!("request {} access {:#x} fail!", token, addr),
+        None => false,
+    }
+}
+
+//regions without timing cost latency + size / bandwidth cycles
+#[no_mangle]
+extern "C" fn __ts_perf_model(latency: u64, bandwidth: u64) -> *const Rc<PerfModel> {
+    Box::into_raw(Box::new(Rc::new(PerfModel::new(Timing::new(
+        latency, bandwidth,
+    )))))
+}
+
+#[no_mangle]
+extern "C" fn __ts_perf_set_timing(
+    perf: &Rc<PerfModel>,
+    name: *const c_char,
+    latency: u64,
+    bandwidth: u64,
+) {
+    let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
+    perf.set_timing(name, Timing::new(latency, bandwidth))
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_set_perf(space: &mut Space, perf: &Rc<PerfModel>) {
+    space.set_perf(perf)
+}
+
+#[no_mangle]
+extern "C" fn __ts_perf_cycles(perf: &Rc<PerfModel>) -> u64 {
+    perf.cycles()
+}
+
+#[no_mangle]
+extern "C" fn __ts_perf_accesses(perf: &Rc<PerfModel>, name: *const c_char) -> u64 {
+    let name = unsafe { CStr::from_ptr(name).to_str().unwrap() };
+    perf.stats(name).accesses()
+}
+
+#[no_mangle]
+extern "C" fn __ts_perf_reset(perf: &Rc<PerfModel>) {
+    perf.reset()
+}
+
+#[no_mangle]
+extern "C" fn __ts_perf_report(perf: &Rc<PerfModel>) {
+    print!("{}", perf.report())
+}
+
+#[no_mangle]
+extern "C" fn __ts_cache(
+    size: u64,
+    ways: u64,
+    line: u64,
+    replace: u32,
+    write: u32,
+    buffered: bool,
+) -> *const Rc<Cache> {
+    let config = CacheConfig {
+        size: size as usize,
+        ways: ways as usize,
+        line: line as usize,
+        replace: Replace::try_from(replace).unwrap(),
+        write: WritePolicy::try_from(write).unwrap(),
+        buffered,
+    };
+    match Cache::new(config) {
+        Ok(cache) => Box::into_raw(Box::new(Rc::new(cache))),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[no_mangle]
+extern "C" fn __ts_space_set_cache(space: &mut Space, cache: &Rc<Cache>) {
+    space.set_cache(cache)
+}
+
+#[no_mangle]
+extern "C" fn __ts_cache_stats(cache: &Rc<Cache>, stats: &mut CacheStats) {
+    *stats = cache.stats()
+}
+
+#[no_mangle]
+extern "C" fn __ts_cache_clean(cache: &Rc<Cache>, addr: u64, size: u64) {
+    cache.clean(addr, size).unwrap()
+}
+
+#[no_mangle]
+extern "C" fn __ts_cache_invalidate(cache: &Rc<Cache>, addr: u64, size: u64) {
+    cache.invalidate(addr, size).unwrap()
+}
+
+#[no_mangle]
+extern "C" fn __ts_cache_flush(cache: &Rc<Cache>, addr: u64, size: u64) {
+    cache.flush(addr, size).unwrap()
+}
+
+#[no_mangle]
+extern "C" fn __ts_tick(cycles: u64) {
+    sched::global().tick(cycles)
+}
+
+#[no_mangle]
+extern "C" fn __ts_now() -> u64 {
+    sched::global().now()
+}
+
+#[no_mangle]
+extern "C" fn __ts_schedule(delay: u64, cb: extern "C" fn(*mut c_void), arg: *mut c_void) -> u64 {
+    sched::global().schedule(delay, move |_| cb(arg))
+}
+
+//fires every period until __ts_cancel
+#[no_mangle]
+extern "C" fn __ts_schedule_every(
+    period: u64,
+    cb: extern "C" fn(*mut c_void),
+    arg: *mut c_void,
+) -> u64 {
+    sched::global().every(period, move |_| {
+        cb(arg);
+        true
+    })
+}
+
+#[no_mangle]
+extern "C" fn __ts_cancel(id: u64) -> bool {
+    sched::global().cancel(id)
+}
+
 fn to_c_ptr(obj: Rc<Region>) -> *const Box<Rc<Region>> {
     Box::into_raw(Box::new(Box::new(obj)))
 }
@@ -0,0 +1,120 @@
+use crate::space::AccessKind;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+
+pub type Token = u64;
+
+//ticks from request to response, None waits for Space::complete
+pub trait Latency {
+    fn latency(&self, kind: AccessKind, addr: &u64, size: usize) -> Option<u64>;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FixedLatency {
+    pub read: Option<u64>,
+    pub write: Option<u64>,
+}
+
+impl FixedLatency {
+    pub fn new(read: Option<u64>, write: Option<u64>) -> FixedLatency {
+        FixedLatency { read, write }
+    }
+}
+
+impl Latency for FixedLatency {
+    fn latency(&self, kind: AccessKind, _: &u64, _: usize) -> Option<u64> {
+        match kind {
+            AccessKind::Read => self.read,
+            AccessKind::Write => self.write,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Response {
+    Read(Vec<u8>),
+    Write(usize),
+    //access fail @addr
+    Err(u64),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Op {
+    Read(usize),
+    Write(Vec<u8>),
+}
+
+pub(crate) struct Request {
+    pub(crate) addr: u64,
+    pub(crate) op: Op,
+    due: Option<u64>,
+}
+
+//tokens start from 1, 0 is never issued
+pub(crate) struct Deferred {
+    now: Cell<u64>,
+    next: Cell<Token>,
+    pending: RefCell<BTreeMap<Token, Request>>,
+    done: RefCell<HashMap<Token, Response>>,
+}
+
+impl Deferred {
+    pub(crate) fn new() -> Deferred {
+        Deferred {
+            now: Cell::new(0),
+            next: Cell::new(1),
+            pending: RefCell::new(BTreeMap::new()),
+            done: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn now(&self) -> u64 {
+        self.now.get()
+    }
+
+    fn token(&self) -> Token {
+        let token = self.next.get();
+        self.next.set(token + 1);
+        token
+    }
+
+    pub(crate) fn push(&self, addr: &u64, op: Op, latency: Option<u64>) -> Token {
+        let token = self.token();
+        self.pending.borrow_mut().insert(
+            token,
+            Request {
+                addr: *addr,
+                op,
+                due: latency.map(|l| self.now.get() + l),
+            },
+        );
+        token
+    }
+
+    pub(crate) fn finish(&self, token: Token, resp: Response) {
+        self.done.borrow_mut().insert(token, resp);
+    }
+
+    //advance the clock and drain the requests due, in issue order
+    pub(crate) fn advance(&self, ticks: u64) -> Vec<(Token, Request)> {
+        self.now.set(self.now.get() + ticks);
+        let now = self.now.get();
+        let mut pending = self.pending.borrow_mut();
+        let due = pending
+            .iter()
+            .filter(|(_, r)| r.due.is_some_and(|d| d <= now))
+            .map(|(t, _)| *t)
+            .collect::<Vec<_>>();
+        due.into_iter()
+            .map(|t| (t, pending.remove(&t).unwrap()))
+            .collect()
+    }
+
+    pub(crate) fn take(&self, token: Token) -> Option<Request> {
+        self.pending.borrow_mut().remove(&token)
+    }
+
+    pub(crate) fn poll(&self, token: Token) -> Option<Response> {
+        self.done.borrow_mut().remove(&token)
+    }
+}
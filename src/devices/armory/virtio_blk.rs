@@ -18,7 +18,7 @@ const VIRTIO_BLK_SECTOR_SHIFT: u64 = 9;
 const VIRTIO_BLK_S_OK: u8 = 0;
 const VIRTIO_BLK_S_IOERR: u8 = 1;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Layout)]
 #[repr(C)]
 struct VirtIOBlkHeader {
     ty: u32,
@@ -135,19 +135,9 @@ impl<T: BytesAccess> QueueClient for VirtIOBlkQueue<T> {
             true,
             true,
         )?;
-        let mut header = VirtIOBlkHeader::default();
-        let header_size = std::mem::size_of::<VirtIOBlkHeader>();
-        if write_len as usize >= header_size {
-            unsafe {
-                std::slice::from_raw_parts_mut(
-                    (&mut header as *mut VirtIOBlkHeader) as *mut u8,
-                    header_size,
-                )
-                .copy_from_slice(&write_buffer[..header_size])
-            }
-        } else {
-            return Err(Error::ClientError("invalid block header!".to_string()));
-        }
+        let header_size = VirtIOBlkHeader::SIZE;
+        let header = VirtIOBlkHeader::from_bytes(&write_buffer[..write_len as usize])
+            .map_err(|_| Error::ClientError("invalid block header!".to_string()))?;
 
         let disk_offset = header.sector_num << VIRTIO_BLK_SECTOR_SHIFT;
 
@@ -2,6 +2,7 @@ use crate::devices::TERM;
 use crate::irq::IrqVecSender;
 use crate::memory::prelude::*;
 use crate::memory::region::Region;
+use crate::sched::{EventId, Scheduler};
 use crate::virtio::{Device, DeviceAccess, MMIODevice, Queue, QueueClient, QueueSetting, Result};
 use std::cmp::min;
 use std::io::{ErrorKind, Read, Write};
@@ -77,6 +78,15 @@ impl VirtIOConsoleDevice {
         virtio_device.add_queue(output_queue);
         VirtIOConsoleDevice { virtio_device }
     }
+
+    //console_read every period until the device is dropped
+    pub fn schedule_read(self: &Rc<Self>, sched: &Scheduler, period: u64) -> EventId {
+        let device = Rc::downgrade(self);
+        sched.every(period, move |_| {
+            device.upgrade().map(|d| d.console_read()).is_some()
+        })
+    }
+
     pub fn console_read(&self) {
         let input_queue = self.virtio_device.get_queue(0);
         if !input_queue.get_ready() {
@@ -2,6 +2,7 @@ use crate::devices::{TunTap, TUNTAP_MODE};
 use crate::irq::IrqVecSender;
 use crate::memory::prelude::*;
 use crate::memory::region::Region;
+use crate::sched::{EventId, Scheduler};
 use crate::virtio::{
     DescMeta, Device, DeviceAccess, Error, MMIODevice, Queue, QueueClient, QueueSetting, Result,
 };
@@ -9,7 +10,7 @@ use std::cell::RefCell;
 use std::io::ErrorKind;
 use std::rc::Rc;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Layout)]
 #[repr(C)]
 struct VirtIONetHeader {
     flags: u8,
@@ -64,20 +65,12 @@ impl QueueClient for VirtIONetOutputQueue {
             false,
             true,
         )?;
-        let mut header = VirtIONetHeader::default();
-        let header_size = std::mem::size_of::<VirtIONetHeader>();
-        if write_len as usize >= header_size {
-            unsafe {
-                std::slice::from_raw_parts_mut(
-                    (&mut header as *mut VirtIONetHeader) as *mut u8,
-                    header_size,
-                )
-                .copy_from_slice(&write_buffer[..header_size])
-            }
-        } else {
-            return Err(Error::ClientError("invalid net header!".to_string()));
-        }
-        self.tap.send(&write_buffer[header_size..]).unwrap();
+        //header is not used, only checked
+        VirtIONetHeader::from_bytes(&write_buffer[..write_len as usize])
+            .map_err(|_| Error::ClientError("invalid net header!".to_string()))?;
+        self.tap
+            .send(&write_buffer[VirtIONetHeader::SIZE..])
+            .unwrap();
         queue.set_used(desc_head, write_len as u32)?;
         queue.update_last_avail();
         self.irq_sender.send().unwrap();
@@ -133,6 +126,15 @@ impl VirtIONetDevice {
             status: RefCell::new(0),
         }
     }
+
+    //net_read every period until the device is dropped
+    pub fn schedule_read(self: &Rc<Self>, sched: &Scheduler, period: u64) -> EventId {
+        let device = Rc::downgrade(self);
+        sched.every(period, move |_| {
+            device.upgrade().map(|d| d.net_read()).is_some()
+        })
+    }
+
     pub fn net_read(&self) {
         let input_queue = self.virtio_device.get_queue(0);
         if !input_queue.get_ready() {
@@ -155,7 +157,7 @@ impl VirtIONetDevice {
                     false,
                 )
                 .unwrap();
-            let header_size = std::mem::size_of::<VirtIONetHeader>();
+            let header_size = VirtIONetHeader::SIZE;
             //
             let ret = match self.tap.recv(&mut read_buffer[header_size..]) {
                 Ok(size) => {
@@ -0,0 +1,384 @@
+use crate::space::Space;
+use crate::virtio::VirtIOInfo;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+const FDT_MAGIC: u32 = 0xd00dfeed;
+const FDT_BEGIN_NODE: u32 = 0x1;
+const FDT_END_NODE: u32 = 0x2;
+const FDT_PROP: u32 = 0x3;
+const FDT_END: u32 = 0x9;
+const FDT_VERSION: u32 = 17;
+const FDT_LAST_COMP_VERSION: u32 = 16;
+const FDT_HEADER_SIZE: usize = 40;
+const FDT_RSVMAP_SIZE: usize = 16;
+
+#[derive(Debug)]
+pub enum Error {
+    NoRegion(String),
+    Unmapped(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::NoRegion(s) => write!(f, "NoRegion!{}", s),
+            Error::Unmapped(s) => write!(f, "Unmapped!{}", s),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Prop {
+    Empty,
+    U32(Vec<u32>),
+    Str(String),
+    Strs(Vec<String>),
+    Bytes(Vec<u8>),
+}
+
+impl Prop {
+    //<hi lo> pairs for #address-cells = <2> and #size-cells = <2>
+    pub fn reg(base: u64, size: u64) -> Prop {
+        Prop::U32(vec![
+            (base >> 32) as u32,
+            base as u32,
+            (size >> 32) as u32,
+            size as u32,
+        ])
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Prop::Empty => vec![],
+            Prop::U32(cells) => cells
+                .iter()
+                .flat_map(|c| c.to_be_bytes().to_vec())
+                .collect(),
+            Prop::Str(s) => [s.as_bytes(), &[0]].concat(),
+            Prop::Strs(strs) => strs
+                .iter()
+                .flat_map(|s| [s.as_bytes(), &[0]].concat())
+                .collect(),
+            Prop::Bytes(bytes) => bytes.clone(),
+        }
+    }
+}
+
+impl Display for Prop {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Prop::Empty => Ok(()),
+            Prop::U32(cells) => write!(
+                f,
+                " = <{}>",
+                cells
+                    .iter()
+                    .map(|c| format!("{:#x}", c))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            Prop::Str(s) => write!(f, " = \"{}\"", s),
+            Prop::Strs(strs) => write!(
+                f,
+                " = {}",
+                strs.iter()
+                    .map(|s| format!("\"{}\"", s))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Prop::Bytes(bytes) => write!(
+                f,
+                " = [{}]",
+                bytes
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub name: String,
+    pub props: Vec<(String, Prop)>,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn new(name: &str) -> Node {
+        Node {
+            name: name.to_string(),
+            props: vec![],
+            children: vec![],
+        }
+    }
+
+    pub fn prop(&mut self, name: &str, prop: Prop) -> &mut Node {
+        if let Some(p) = self.props.iter_mut().find(|(n, _)| n == name) {
+            p.1 = prop
+        } else {
+            self.props.push((name.to_string(), prop))
+        }
+        self
+    }
+
+    pub fn get_prop(&self, name: &str) -> Option<&Prop> {
+        self.props.iter().find(|(n, _)| n == name).map(|(_, p)| p)
+    }
+
+    pub fn add_child(&mut self, node: Node) -> &mut Node {
+        self.children.push(node);
+        self.children.last_mut().unwrap()
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|n| n.name == name)
+    }
+
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut Node> {
+        self.children.iter_mut().find(|n| n.name == name)
+    }
+
+    pub fn to_dts(&self) -> String {
+        format!("/dts-v1/;\n\n{}", self.dts_node(0))
+    }
+
+    fn dts_node(&self, level: usize) -> String {
+        let indent = "    ".repeat(level);
+        let name = if self.name.is_empty() {
+            "/"
+        } else {
+            &self.name
+        };
+        let mut dts = format!("{}{} {{\n", indent, name);
+        for (n, p) in self.props.iter() {
+            dts.push_str(&format!("{}    {}{};\n", indent, n, p));
+        }
+        for c in self.children.iter() {
+            dts.push_str(&c.dts_node(level + 1));
+        }
+        dts.push_str(&format!("{}}};\n", indent));
+        dts
+    }
+
+    pub fn to_dtb(&self) -> Vec<u8> {
+        let mut dt_struct = vec![];
+        let mut strings = Strings::new();
+        self.dtb_node(&mut dt_struct, &mut strings);
+        dt_struct.extend_from_slice(&FDT_END.to_be_bytes());
+
+        let off_mem_rsvmap = FDT_HEADER_SIZE;
+        let off_dt_struct = off_mem_rsvmap + FDT_RSVMAP_SIZE;
+        let off_dt_strings = off_dt_struct + dt_struct.len();
+        let totalsize = off_dt_strings + strings.bytes.len();
+        let header = [
+            FDT_MAGIC,
+            totalsize as u32,
+            off_dt_struct as u32,
+            off_dt_strings as u32,
+            off_mem_rsvmap as u32,
+            FDT_VERSION,
+            FDT_LAST_COMP_VERSION,
+            0,
+            strings.bytes.len() as u32,
+            dt_struct.len() as u32,
+        ];
+        let mut dtb = header
+            .iter()
+            .flat_map(|h| h.to_be_bytes().to_vec())
+            .collect::<Vec<u8>>();
+        dtb.extend_from_slice(&[0; FDT_RSVMAP_SIZE]);
+        dtb.extend_from_slice(&dt_struct);
+        dtb.extend_from_slice(&strings.bytes);
+        dtb
+    }
+
+    fn dtb_node(&self, dt_struct: &mut Vec<u8>, strings: &mut Strings) {
+        dt_struct.extend_from_slice(&FDT_BEGIN_NODE.to_be_bytes());
+        dt_struct.extend_from_slice(self.name.as_bytes());
+        dt_struct.push(0);
+        pad4(dt_struct);
+        for (n, p) in self.props.iter() {
+            let value = p.to_bytes();
+            dt_struct.extend_from_slice(&FDT_PROP.to_be_bytes());
+            dt_struct.extend_from_slice(&(value.len() as u32).to_be_bytes());
+            dt_struct.extend_from_slice(&strings.offset(n).to_be_bytes());
+            dt_struct.extend_from_slice(&value);
+            pad4(dt_struct);
+        }
+        for c in self.children.iter() {
+            c.dtb_node(dt_struct, strings)
+        }
+        dt_struct.extend_from_slice(&FDT_END_NODE.to_be_bytes());
+    }
+}
+
+fn pad4(bytes: &mut Vec<u8>) {
+    let len = (bytes.len() + 3) & !3;
+    bytes.resize(len, 0)
+}
+
+struct Strings {
+    bytes: Vec<u8>,
+    offsets: HashMap<String, u32>,
+}
+
+impl Strings {
+    fn new() -> Strings {
+        Strings {
+            bytes: vec![],
+            offsets: HashMap::new(),
+        }
+    }
+
+    fn offset(&mut self, s: &str) -> u32 {
+        if let Some(&off) = self.offsets.get(s) {
+            return off;
+        }
+        let off = self.bytes.len() as u32;
+        self.bytes.extend_from_slice(s.as_bytes());
+        self.bytes.push(0);
+        self.offsets.insert(s.to_string(), off);
+        off
+    }
+}
+
+//memories are names of regions in space, every virtio device must be mapped in space
+pub fn generate(space: &Space, memories: &[&str], virtios: &[VirtIOInfo]) -> Result<Node, Error> {
+    let mut root = Node::new("");
+    root.prop("#address-cells", Prop::U32(vec![2]))
+        .prop("#size-cells", Prop::U32(vec![2]))
+        .prop("compatible", Prop::Str("terminus".to_string()))
+        .prop("model", Prop::Str("terminus".to_string()));
+
+    for &name in memories {
+        let region = space
+            .get_region(name)
+            .ok_or_else(|| Error::NoRegion(format!("memory region {} is not in space!", name)))?;
+        root.add_child(Node::new(&format!("memory@{:x}", region.info.base)))
+            .prop("device_type", Prop::Str("memory".to_string()))
+            .prop("reg", Prop::reg(region.info.base, region.info.size));
+    }
+
+    let mut soc = Node::new("soc");
+    soc.prop("#address-cells", Prop::U32(vec![2]))
+        .prop("#size-cells", Prop::U32(vec![2]))
+        .prop("compatible", Prop::Str("simple-bus".to_string()))
+        .prop("ranges", Prop::Empty);
+    for virtio in virtios {
+        let mapped = space.get_region_by_addr(&virtio.base);
+        if !matches!(mapped, Ok(r) if virtio.base + virtio.size <= r.info.base + r.info.size) {
+            return Err(Error::Unmapped(format!(
+                "virtio {} [{:#x} : {:#x}] is not mapped in space!",
+                virtio.ty, virtio.base, virtio.size
+            )));
+        }
+        soc.add_child(Node::new(&format!("virtio_mmio@{:x}", virtio.base)))
+            .prop("compatible", Prop::Str("virtio,mmio".to_string()))
+            .prop("reg", Prop::reg(virtio.base, virtio.size))
+            .prop("interrupts", Prop::U32(vec![virtio.irq_id]));
+    }
+    root.add_child(soc);
+    Ok(root)
+}
+
+#[cfg(test)]
+use crate::memory::region::{Region, GHEAP};
+
+#[cfg(test)]
+fn fdt_u32(dtb: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&dtb[offset..offset + 4]);
+    u32::from_be_bytes(bytes)
+}
+
+#[test]
+fn dts_test() {
+    let mut space = Space::new();
+    let memory = GHEAP.lazy_alloc(0x1000_0000, 1).unwrap();
+    space
+        .add_region("main_memory", &Region::remap(0x8000_0000, &memory))
+        .unwrap();
+    space
+        .add_region(
+            "virtio_blk",
+            &Region::remap(0x1000_1000, &GHEAP.alloc(0x1000, 1).unwrap()),
+        )
+        .unwrap();
+    let virtios = vec![VirtIOInfo {
+        base: 0x1000_1000,
+        size: 0x1000,
+        irq_id: 1,
+        ty: "block".to_string(),
+    }];
+    let dt = generate(&space, &["main_memory"], &virtios).unwrap();
+    assert_eq!(
+        dt.child("memory@80000000").unwrap().get_prop("reg"),
+        Some(&Prop::U32(vec![0, 0x8000_0000, 0, 0x1000_0000]))
+    );
+    let dts = dt.to_dts();
+    assert!(dts.starts_with("/dts-v1/;\n\n/ {\n    #address-cells = <0x2>;\n"));
+    assert!(dts.contains(
+        "        virtio_mmio@10001000 {\n            compatible = \"virtio,mmio\";\n            reg = <0x0 0x10001000 0x0 0x1000>;\n            interrupts = <0x1>;\n        };\n"
+    ));
+    assert!(dts.contains("    soc {\n        #address-cells = <0x2>;\n"));
+    assert!(dts.contains("        ranges;\n"));
+
+    assert!(generate(&space, &["rom"], &virtios).is_err());
+    let unmapped = vec![VirtIOInfo {
+        base: 0x1000_1800,
+        size: 0x1000,
+        irq_id: 2,
+        ty: "net".to_string(),
+    }];
+    assert!(generate(&space, &[], &unmapped).is_err());
+}
+
+#[test]
+fn dtb_test() {
+    let mut root = Node::new("");
+    root.prop("#address-cells", Prop::U32(vec![2]))
+        .prop("model", Prop::Str("t".to_string()));
+    root.add_child(Node::new("chosen"))
+        .prop("bootargs", Prop::Str("console=hvc0".to_string()))
+        .prop("model", Prop::Empty);
+    let dtb = root.to_dtb();
+    assert_eq!(fdt_u32(&dtb, 0), FDT_MAGIC);
+    assert_eq!(fdt_u32(&dtb, 4) as usize, dtb.len());
+    assert_eq!(fdt_u32(&dtb, 20), FDT_VERSION);
+    let off_struct = fdt_u32(&dtb, 8) as usize;
+    let off_strings = fdt_u32(&dtb, 12) as usize;
+    assert_eq!(off_struct, 56);
+    assert_eq!(fdt_u32(&dtb, 36) as usize, off_strings - off_struct);
+    assert_eq!(&dtb[off_strings..], b"#address-cells\0model\0bootargs\0");
+
+    let expect = [
+        vec![FDT_BEGIN_NODE, 0],
+        vec![FDT_PROP, 4, 0, 2],
+        vec![FDT_PROP, 2, 15, 0x7400_0000],
+        vec![FDT_BEGIN_NODE],
+        b"chosen\0\0"
+            .chunks(4)
+            .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+            .collect(),
+        vec![FDT_PROP, 13, 21],
+        b"console=hvc0\0\0\0\0"
+            .chunks(4)
+            .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+            .collect(),
+        vec![FDT_PROP, 0, 15],
+        vec![FDT_END_NODE, FDT_END_NODE, FDT_END],
+    ]
+    .concat();
+    assert_eq!(
+        (off_struct..off_strings)
+            .step_by(4)
+            .map(|o| fdt_u32(&dtb, o))
+            .collect::<Vec<_>>(),
+        expect
+    );
+}
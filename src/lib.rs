@@ -6,10 +6,22 @@ pub mod memory;
 
 pub mod space;
 
+pub mod deferred;
+
+pub mod views;
+
 pub mod irq;
 
+pub mod sched;
+
+pub mod perf;
+
+pub mod cache;
+
 pub mod virtio;
 
+pub mod regmap;
+
 mod capi;
 
 mod utils;
@@ -17,5 +29,7 @@ pub use utils::EXIT_CTRL;
 
 pub mod devices;
 
+pub mod devicetree;
+
 #[cfg(test)]
 mod test;
@@ -0,0 +1,134 @@
+use std::cmp::Ordering;
+
+//free blocks in address order, each subtree keeps its largest block so fitting skips subtrees too small
+//a treap, priorities are hashed from the base so the shape only depends on the blocks
+#[derive(Default)]
+pub(super) struct FitTree {
+    root: Link,
+}
+
+type Link = Option<Box<Node>>;
+
+struct Node {
+    base: u64,
+    size: u64,
+    max: u64,
+    left: Link,
+    right: Link,
+}
+
+fn priority(base: u64) -> u64 {
+    base.wrapping_mul(0x9e37_79b9_7f4a_7c15).rotate_left(29)
+}
+
+fn max(link: &Link) -> u64 {
+    link.as_ref().map_or(0, |n| n.max)
+}
+
+impl Node {
+    fn update(&mut self) {
+        self.max = self.size.max(max(&self.left)).max(max(&self.right))
+    }
+}
+
+//(< base, >= base)
+fn split(link: Link, base: u64) -> (Link, Link) {
+    match link {
+        None => (None, None),
+        Some(mut n) => {
+            if n.base < base {
+                let (l, r) = split(n.right.take(), base);
+                n.right = l;
+                n.update();
+                (Some(n), r)
+            } else {
+                let (l, r) = split(n.left.take(), base);
+                n.left = r;
+                n.update();
+                (l, Some(n))
+            }
+        }
+    }
+}
+
+//every base in a is below every base in b
+fn merge(a: Link, b: Link) -> Link {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if priority(a.base) > priority(b.base) {
+                a.right = merge(a.right.take(), Some(b));
+                a.update();
+                Some(a)
+            } else {
+                b.left = merge(Some(a), b.left.take());
+                b.update();
+                Some(b)
+            }
+        }
+    }
+}
+
+fn remove(link: &mut Link, base: u64) {
+    let n = match link {
+        Some(n) => n,
+        None => return,
+    };
+    match base.cmp(&n.base) {
+        Ordering::Less => remove(&mut n.left, base),
+        Ordering::Greater => remove(&mut n.right, base),
+        Ordering::Equal => {
+            let (l, r) = (n.left.take(), n.right.take());
+            *link = merge(l, r);
+            return;
+        }
+    }
+    n.update()
+}
+
+//lowest block from from on with at least size bytes that fit accepts
+fn find<F: Fn(u64, u64) -> bool>(link: &Link, from: u64, size: u64, fit: &F) -> Option<(u64, u64)> {
+    let n = link.as_ref().filter(|n| n.max >= size)?;
+    if from < n.base {
+        if let Some(found) = find(&n.left, from, size, fit) {
+            return Some(found);
+        }
+    }
+    if n.base >= from && n.size >= size && fit(n.base, n.size) {
+        return Some((n.base, n.size));
+    }
+    find(&n.right, from, size, fit)
+}
+
+impl FitTree {
+    pub(super) fn insert(&mut self, base: u64, size: u64) {
+        let (l, r) = split(self.root.take(), base);
+        let n = Box::new(Node {
+            base,
+            size,
+            max: size,
+            left: None,
+            right: None,
+        });
+        self.root = merge(merge(l, Some(n)), r)
+    }
+
+    pub(super) fn remove(&mut self, base: u64) {
+        remove(&mut self.root, base)
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.root = None
+    }
+
+    //O(log n) unless blocks just big enough are too short once aligned
+    pub(super) fn find<F: Fn(u64, u64) -> bool>(
+        &self,
+        from: u64,
+        size: u64,
+        fit: F,
+    ) -> Option<(u64, u64)> {
+        find(&self.root, from, size, &fit)
+    }
+}
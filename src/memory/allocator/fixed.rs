@@ -0,0 +1,95 @@
+use super::*;
+
+fn overlap(blocks: &BTreeMap<u64, u64>, base: u64, size: u64) -> Option<MemInfo> {
+    blocks
+        .range(..base + size)
+        .next_back()
+        .map(|(&base, &size)| MemInfo { base, size })
+        .filter(|info| info.base + info.size > base)
+}
+
+impl Allocator {
+    //[base, base + size) must lie in a single free block
+    fn claim(&mut self, base: u64, size: u64) -> Result<(), Error> {
+        let range = MemInfo { base, size };
+        let end = base.checked_add(size);
+        if base < self.info.base
+            || end
+                .filter(|&end| end <= self.info.base + self.info.size)
+                .is_none()
+        {
+            return Err(Error::OutOfRange(range));
+        }
+        loop {
+            let holder = self
+                .free_blocks
+                .range(..=base)
+                .next_back()
+                .map(|(&base, &size)| MemInfo { base, size })
+                .filter(|info| info.base + info.size >= base + size);
+            if let Some(info) = holder {
+                self.carve_from(&info, base, size, 1);
+                return Ok(());
+            }
+            if let Some(info) = overlap(&self.alloced_blocks, base, size) {
+                return Err(Error::Occupied(range, info));
+            }
+            if let Some(info) = overlap(&self.reserved, base, size) {
+                return Err(Error::Reserved(range, info));
+            }
+            //only cached slab slots are left in the way
+            if !self.slab_reclaim() {
+                return Err(Error::OutOfMemory(size));
+            }
+        }
+    }
+
+    //allocate exactly [base, base + size), the block is freed as usual
+    pub fn alloc_at(&mut self, base: u64, size: u64) -> Result<MemInfo, Error> {
+        let result = self.claim(base, size).map(|_| {
+            self.record_alloc(base, size);
+            MemInfo { base, size }
+        });
+        self.log_op(|| {
+            format!(
+                "alloc_at {:#x} {:#x} {}",
+                base,
+                size,
+                replay::status(&result, |_| "ok".to_string())
+            )
+        });
+        result
+    }
+
+    //take [base, base + size) out of the pool for good, e.g. an MMIO window
+    pub fn reserve(&mut self, base: u64, size: u64) -> Result<(), Error> {
+        let result = self.claim(base, size).map(|_| {
+            self.reserved.insert(base, size);
+        });
+        self.log_op(|| {
+            format!(
+                "reserve {:#x} {:#x} {}",
+                base,
+                size,
+                replay::status(&result, |_| "ok".to_string())
+            )
+        });
+        result
+    }
+
+    pub fn reserved(&self) -> impl Iterator<Item = MemInfo> + '_ {
+        self.reserved
+            .iter()
+            .map(|(&base, &size)| MemInfo { base, size })
+    }
+}
+
+impl LockedAllocator {
+    pub fn alloc_at(&self, base: u64, size: u64) -> Result<MemInfo, Error> {
+        self.inner.lock().unwrap().alloc_at(base, size)
+    }
+
+    pub fn reserve(&self, base: u64, size: u64) -> Result<(), Error> {
+        self.inner.lock().unwrap().reserve(base, size)
+    }
+}
@@ -1,124 +1,377 @@
+mod fit;
+mod fixed;
 pub mod list;
+mod policy;
+mod pool;
+mod replay;
+mod stats;
+mod tag;
 #[cfg(test)]
 mod test;
 
+pub use policy::Policy;
+pub use pool::{Constraint, LockedPoolAllocator, Pool, PoolAllocator, PoolAttr};
+pub use stats::Stats;
+pub use tag::Tag;
+
 use super::*;
 use core::ops::Deref;
-use list::*;
-use std::sync::{Arc, Mutex};
+use fit::FitTree;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::io::Write;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum Error {
+    DoubleFree(u64),
+    NotAllocated(u64),
+    InteriorPointer(u64, MemInfo),
+    OutOfMemory(u64),
+    OutOfRange(MemInfo),
+    Occupied(MemInfo, MemInfo),
+    Reserved(MemInfo, MemInfo),
+    DuplicatePool(String),
+    //the block is referenced elsewhere and can not be moved
+    Shared(u64),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::DoubleFree(addr) => write!(f, "DoubleFree!{:#x}", addr),
+            Error::NotAllocated(addr) => write!(f, "NotAllocated!{:#x}", addr),
+            Error::InteriorPointer(addr, info) => write!(
+                f,
+                "InteriorPointer!{:#x}:block @{:#x} size {:#x}",
+                addr, info.base, info.size
+            ),
+            Error::OutOfMemory(size) => write!(f, "OutOfMemory!{:#x}", size),
+            Error::OutOfRange(range) => {
+                write!(f, "OutOfRange!@{:#x} size {:#x}", range.base, range.size)
+            }
+            Error::Occupied(range, info) => write!(
+                f,
+                "Occupied!@{:#x} size {:#x}:block @{:#x} size {:#x}",
+                range.base, range.size, info.base, info.size
+            ),
+            Error::Reserved(range, info) => write!(
+                f,
+                "Reserved!@{:#x} size {:#x}:reserved @{:#x} size {:#x}",
+                range.base, range.size, info.base, info.size
+            ),
+            Error::DuplicatePool(name) => write!(f, "DuplicatePool!{}", name),
+            Error::Shared(addr) => write!(f, "Shared!{:#x}", addr),
+        }
+    }
+}
+
+//frees older than this are reported as NotAllocated instead of DoubleFree
+pub const FREED_HISTORY: usize = 4096;
 
+//free blocks are kept in address order for coalescing, indexed by (size, base) for best fit
+//and in a tree keeping the largest block of each subtree for first fit
 #[cfg(test)]
 #[repr(C)]
 pub struct Allocator {
     pub info: MemInfo,
-    pub free_blocks: Arc<List<MemInfo>>,
-    pub alloced_blocks: Arc<List<MemInfo>>,
+    pub free_blocks: BTreeMap<u64, u64>,
+    pub alloced_blocks: BTreeMap<u64, u64>,
+    free_sizes: BTreeSet<(u64, u64)>,
+    free_tree: FitTree,
+    policy: Policy,
+    cursor: u64,
+    slabs: BTreeMap<u64, Vec<u64>>,
+    tags: HashMap<u64, Tag>,
+    //recent bases freed and not handed out again, to tell double frees apart
+    //base -> free order and back, the oldest is dropped past FREED_HISTORY
+    freed: BTreeMap<u64, u64>,
+    freed_order: BTreeMap<u64, u64>,
+    strict: bool,
+    rng: u64,
+    //ranges never handed out
+    reserved: BTreeMap<u64, u64>,
+    log: Option<Box<dyn Write + Send>>,
+    #[cfg(feature = "memprof")]
+    used: u64,
+    #[cfg(feature = "memprof")]
+    high_water: u64,
 }
 
 #[cfg(not(test))]
 #[repr(C)]
 pub struct Allocator {
     pub info: MemInfo,
-    free_blocks: Arc<List<MemInfo>>,
-    alloced_blocks: Arc<List<MemInfo>>,
+    free_blocks: BTreeMap<u64, u64>,
+    alloced_blocks: BTreeMap<u64, u64>,
+    free_sizes: BTreeSet<(u64, u64)>,
+    free_tree: FitTree,
+    policy: Policy,
+    cursor: u64,
+    slabs: BTreeMap<u64, Vec<u64>>,
+    tags: HashMap<u64, Tag>,
+    //recent bases freed and not handed out again, to tell double frees apart
+    //base -> free order and back, the oldest is dropped past FREED_HISTORY
+    freed: BTreeMap<u64, u64>,
+    freed_order: BTreeMap<u64, u64>,
+    strict: bool,
+    rng: u64,
+    //ranges never handed out
+    reserved: BTreeMap<u64, u64>,
+    log: Option<Box<dyn Write + Send>>,
+    #[cfg(feature = "memprof")]
+    used: u64,
+    #[cfg(feature = "memprof")]
+    high_water: u64,
 }
 
 impl Allocator {
     pub fn new(base: u64, size: u64) -> Allocator {
-        Allocator {
-            info: MemInfo {
-                base: base,
-                size: size,
-            },
-            free_blocks: List::cons(
-                MemInfo {
-                    base: base,
-                    size: size,
-                },
-                &List::nil(),
-            ),
-            alloced_blocks: List::nil(),
+        Self::with_policy(base, size, Policy::default())
+    }
+
+    pub fn with_policy(base: u64, size: u64, policy: Policy) -> Allocator {
+        let mut allocator = Allocator {
+            info: MemInfo { base, size },
+            free_blocks: BTreeMap::new(),
+            alloced_blocks: BTreeMap::new(),
+            free_sizes: BTreeSet::new(),
+            free_tree: FitTree::default(),
+            policy,
+            cursor: base,
+            slabs: BTreeMap::new(),
+            tags: HashMap::new(),
+            freed: BTreeMap::new(),
+            freed_order: BTreeMap::new(),
+            strict: false,
+            rng: policy::DEFAULT_SEED,
+            reserved: BTreeMap::new(),
+            log: None,
+            #[cfg(feature = "memprof")]
+            used: 0,
+            #[cfg(feature = "memprof")]
+            high_water: 0,
+        };
+        allocator.insert_free(base, size);
+        allocator
+    }
+
+    //drop every block but keep the settings and the log, reserved ranges stay out of the pool
+    pub fn reset(&mut self) {
+        self.free_blocks.clear();
+        self.free_sizes.clear();
+        self.free_tree.clear();
+        self.alloced_blocks.clear();
+        self.slabs.clear();
+        self.tags.clear();
+        self.freed.clear();
+        self.freed_order.clear();
+        self.cursor = self.info.base;
+        #[cfg(feature = "memprof")]
+        {
+            self.used = 0;
+        }
+        let reserved = self.reserved().collect::<Vec<_>>();
+        let mut start = self.info.base;
+        for r in reserved {
+            if r.base != start {
+                self.insert_free(start, r.base - start)
+            }
+            start = r.base + r.size;
+        }
+        if start != self.info.base + self.info.size {
+            self.insert_free(start, self.info.base + self.info.size - start)
+        }
+        self.log_op(|| "reset".to_string());
+    }
+
+    fn insert_free(&mut self, base: u64, size: u64) {
+        self.free_blocks.insert(base, size);
+        self.free_sizes.insert((size, base));
+        self.free_tree.insert(base, size);
+    }
+
+    fn remove_free(&mut self, base: u64) -> Option<u64> {
+        let size = self.free_blocks.remove(&base)?;
+        self.free_sizes.remove(&(size, base));
+        self.free_tree.remove(base);
+        Some(size)
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    fn carve(&mut self, info: &MemInfo, size: u64, align: u64) -> u64 {
+        self.carve_from(info, info.base, size, align)
+    }
+
+    //take [base, base + size) out of free block info, base is aligned up from start
+    fn carve_from(&mut self, info: &MemInfo, start: u64, size: u64, align: u64) -> u64 {
+        self.remove_free(info.base);
+        let base = align_up(start, align);
+        if base != info.base {
+            self.insert_free(info.base, base - info.base)
+        }
+        if info.base + info.size != base + size {
+            self.insert_free(base + size, info.base + info.size - base - size)
+        }
+        base
+    }
+
+    //give [base, base + size) back and coalesce with neighbours
+    fn release(&mut self, base: u64, size: u64) {
+        let mut info = MemInfo { base, size };
+        let pre = self
+            .free_blocks
+            .range(..base)
+            .next_back()
+            .map(|(&base, &size)| MemInfo { base, size });
+        if let Some(pre) = pre.filter(|pre| pre.base + pre.size == base) {
+            self.remove_free(pre.base);
+            info = MemInfo {
+                base: pre.base,
+                size: pre.size + info.size,
+            };
+        }
+        if let Some(post_size) = self.remove_free(info.base + info.size) {
+            info.size += post_size;
+        }
+        self.insert_free(info.base, info.size);
+    }
+
+    fn record_alloc(&mut self, base: u64, reserved: u64) {
+        self.alloced_blocks.insert(base, reserved);
+        let reused = self
+            .freed
+            .range(base..base + reserved)
+            .map(|(&addr, _)| addr)
+            .collect::<Vec<_>>();
+        for addr in reused {
+            self.forget_freed(addr);
+        }
+        #[cfg(feature = "memprof")]
+        {
+            self.used += reserved;
+            self.high_water = self.high_water.max(self.used);
+        }
+    }
+
+    fn note_freed(&mut self, addr: u64) {
+        self.forget_freed(addr);
+        let order = self.freed_order.keys().next_back().map_or(0, |o| o + 1);
+        self.freed.insert(addr, order);
+        self.freed_order.insert(order, addr);
+        if self.freed.len() > FREED_HISTORY {
+            let (_, oldest) = self.freed_order.pop_first().unwrap();
+            self.freed.remove(&oldest);
+        }
+    }
+
+    fn forget_freed(&mut self, addr: u64) {
+        if let Some(order) = self.freed.remove(&addr) {
+            self.freed_order.remove(&order);
         }
     }
 
     pub fn alloc(&mut self, size: u64, align: u64) -> Option<MemInfo> {
-        let (block, free_blocks) = List::delete(&self.free_blocks, |item| {
-            let info = item.car().unwrap();
-            info.size >= size + (align_up(info.base, align) - info.base)
+        let info = self.policy_alloc(size, align);
+        self.log_op(|| {
+            format!(
+                "alloc {:#x} {:#x} {}",
+                size,
+                align,
+                info.map_or("none".to_string(), |info| format!("{:#x}", info.base))
+            )
         });
-        if let Some(item) = block {
-            let info = item.car().unwrap();
-            let result = Some(MemInfo {
-                base: align_up(info.base, align),
-                size: size,
-            });
-            self.free_blocks = free_blocks;
-            if align_up(info.base, align) != info.base {
-                self.free_blocks = List::cons(
-                    MemInfo {
-                        base: info.base,
-                        size: align_up(info.base, align) - info.base,
-                    },
-                    &self.free_blocks,
-                )
-            }
-            if info.size != size + (align_up(info.base, align) - info.base) {
-                self.free_blocks = List::cons(
-                    MemInfo {
-                        base: align_up(info.base, align) + size,
-                        size: info.size - size - (align_up(info.base, align) - info.base),
-                    },
-                    &self.free_blocks,
-                )
+        info
+    }
+
+    //strict allocator panics on invalid free instead of returning the error
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict
+    }
+
+    pub fn free(&mut self, addr: u64) -> Result<(), Error> {
+        let result = self.free_block(addr);
+        self.log_op(|| {
+            format!(
+                "free {:#x} {}",
+                addr,
+                replay::status(&result, |_| "ok".to_string())
+            )
+        });
+        result
+    }
+
+    fn free_block(&mut self, addr: u64) -> Result<(), Error> {
+        // println!("free {}!", addr);
+        if let Some(size) = self.alloced_blocks.remove(&addr) {
+            self.tags.remove(&addr);
+            self.note_freed(addr);
+            #[cfg(feature = "memprof")]
+            {
+                self.used -= size;
             }
-            self.alloced_blocks = List::cons(result.unwrap(), &self.alloced_blocks);
-            result
+            self.policy_free(addr, size);
+            Ok(())
         } else {
-            None
+            Err(self.invalid_free(addr))
         }
     }
 
-    pub fn free(&mut self, addr: u64) {
-        // println!("free {}!", addr);
-        let (alloced_block, alloced_blocks) = List::delete(&self.alloced_blocks, |item| {
-            item.car().unwrap().base == addr
+    //grow or shrink in place if possible, otherwise move, the tag moves with the block
+    pub fn realloc(&mut self, addr: u64, size: u64, align: u64) -> Result<MemInfo, Error> {
+        let result = self.realloc_block(addr, size, align);
+        self.log_op(|| {
+            format!(
+                "realloc {:#x} {:#x} {:#x} {}",
+                addr,
+                size,
+                align,
+                replay::status(&result, |info| format!("{:#x}", info.base))
+            )
         });
-        if let Some(item) = alloced_block {
-            let (pre_block, free_blocks) = List::delete(&self.free_blocks, |i| {
-                let info = i.car().unwrap();
-                info.base + info.size == item.car().unwrap().base
-            });
-            let pre_info = if let Some(pre) = pre_block {
-                let info = pre.car().unwrap();
-                self.free_blocks = free_blocks;
-                MemInfo {
-                    base: info.base,
-                    size: info.size + item.car().unwrap().size,
-                }
-            } else {
-                item.car().unwrap()
-            };
+        result
+    }
 
-            let (post_block, free_blocks) = List::delete(&self.free_blocks, |i| {
-                let info = i.car().unwrap();
-                pre_info.base + pre_info.size == info.base
-            });
-            let post_info = if let Some(post) = post_block {
-                let info = post.car().unwrap();
-                self.free_blocks = free_blocks;
-                MemInfo {
-                    base: pre_info.base,
-                    size: pre_info.size + info.size,
-                }
-            } else {
-                pre_info
-            };
+    fn realloc_block(&mut self, addr: u64, size: u64, align: u64) -> Result<MemInfo, Error> {
+        if !self.alloced_blocks.contains_key(&addr) {
+            return Err(self.invalid_free(addr));
+        }
+        if self.policy_resize(addr, size, align) {
+            return Ok(MemInfo { base: addr, size });
+        }
+        let info = self
+            .policy_alloc(size, align)
+            .ok_or(Error::OutOfMemory(size))?;
+        let tag = self.tags.remove(&addr);
+        self.free_block(addr)?;
+        if let Some(tag) = tag {
+            self.tags.insert(info.base, tag);
+        }
+        Ok(info)
+    }
 
-            self.free_blocks = List::cons(post_info, &self.free_blocks);
-            self.alloced_blocks = alloced_blocks;
+    fn invalid_free(&self, addr: u64) -> Error {
+        let holder = self
+            .alloced_blocks
+            .range(..addr)
+            .next_back()
+            .map(|(&base, &size)| MemInfo { base, size })
+            .filter(|info| info.base + info.size > addr);
+        let err = if let Some(info) = holder {
+            Error::InteriorPointer(addr, info)
+        } else if self.freed.contains_key(&addr) {
+            Error::DoubleFree(addr)
         } else {
-            panic!(format!("invalid free @{}", addr));
+            Error::NotAllocated(addr)
+        };
+        if self.strict {
+            panic!("invalid free @{}: {}", addr, err);
         }
+        err
     }
 }
 
@@ -128,16 +381,31 @@ pub struct LockedAllocator {
 
 impl LockedAllocator {
     pub fn new(base: u64, size: u64) -> LockedAllocator {
+        Self::with_policy(base, size, Policy::default())
+    }
+    pub fn with_policy(base: u64, size: u64, policy: Policy) -> LockedAllocator {
         LockedAllocator {
-            inner: Mutex::new(Allocator::new(base, size)),
+            inner: Mutex::new(Allocator::with_policy(base, size, policy)),
         }
     }
     pub fn alloc(&self, size: u64, align: u64) -> Option<MemInfo> {
         self.inner.lock().unwrap().alloc(size, align)
     }
-    pub fn free(&self, addr: u64) {
+    pub fn free(&self, addr: u64) -> Result<(), Error> {
         self.inner.lock().unwrap().free(addr)
     }
+    pub fn realloc(&self, addr: u64, size: u64, align: u64) -> Result<MemInfo, Error> {
+        self.inner.lock().unwrap().realloc(addr, size, align)
+    }
+    pub fn set_strict(&self, strict: bool) {
+        self.inner.lock().unwrap().set_strict(strict)
+    }
+    pub fn set_seed(&self, seed: u64) {
+        self.inner.lock().unwrap().set_seed(seed)
+    }
+    pub fn reset(&self) {
+        self.inner.lock().unwrap().reset()
+    }
 }
 
 impl Deref for LockedAllocator {
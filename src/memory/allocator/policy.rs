@@ -0,0 +1,262 @@
+use super::*;
+use std::convert::TryFrom;
+
+const MAX_FIT_SCAN: usize = 8;
+const SLAB_MIN_SIZE: u64 = 8;
+const SLAB_MAX_SIZE: u64 = 0x1000;
+const SLAB_PAGE_SIZE: u64 = 0x1000;
+pub(super) const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+#[repr(u32)]
+pub enum Policy {
+    #[default]
+    FirstFit = 0,
+    BestFit = 1,
+    NextFit = 2,
+    //blocks are rounded up to power-of-two sizes and naturally aligned
+    Pow2 = 3,
+    Slab = 4,
+    Random = 5,
+}
+
+impl TryFrom<u32> for Policy {
+    type Error = String;
+    fn try_from(v: u32) -> Result<Policy, String> {
+        match v {
+            0 => Ok(Policy::FirstFit),
+            1 => Ok(Policy::BestFit),
+            2 => Ok(Policy::NextFit),
+            3 => Ok(Policy::Pow2),
+            4 => Ok(Policy::Slab),
+            5 => Ok(Policy::Random),
+            _ => Err(format!("invalid allocation policy {}!", v)),
+        }
+    }
+}
+
+fn to_info((&base, &size): (&u64, &u64)) -> MemInfo {
+    MemInfo { base, size }
+}
+
+impl Allocator {
+    fn fit(info: &MemInfo, size: u64, align: u64) -> bool {
+        info.size >= size + (align_up(info.base, align) - info.base)
+    }
+
+    fn first_fit(&self, size: u64, align: u64) -> Option<MemInfo> {
+        self.free_fit(self.info.base, size, align)
+    }
+
+    //lowest block at or above from that fits
+    fn free_fit(&self, from: u64, size: u64, align: u64) -> Option<MemInfo> {
+        self.free_tree
+            .find(from, size, |base, block| {
+                Self::fit(&MemInfo { base, size: block }, size, align)
+            })
+            .map(|(base, size)| MemInfo { base, size })
+    }
+
+    //best fit among blocks that may be too short once aligned, scan is bounded to skip padding fragments
+    fn best_fit(&self, size: u64, align: u64) -> Option<MemInfo> {
+        let fit_size = size.saturating_add(align - 1);
+        self.free_sizes
+            .range((size, 0)..(fit_size, 0))
+            .take(MAX_FIT_SCAN)
+            .map(|&(size, base)| MemInfo { base, size })
+            .find(|info| Self::fit(info, size, align))
+            .or_else(|| {
+                self.free_sizes
+                    .range((fit_size, 0)..)
+                    .next()
+                    .map(|&(size, base)| MemInfo { base, size })
+            })
+    }
+
+    //start from the cursor, the block holding the cursor is split there
+    fn next_fit(&self, size: u64, align: u64) -> Option<(MemInfo, u64)> {
+        let cursor = self.cursor;
+        let holder = self
+            .free_blocks
+            .range(..cursor)
+            .next_back()
+            .map(to_info)
+            .filter(|info| info.base + info.size > cursor)
+            .map(|info| (info, cursor));
+        holder
+            .into_iter()
+            .chain(
+                self.free_blocks
+                    .range(cursor..)
+                    .chain(self.free_blocks.range(..cursor))
+                    .map(|b| (to_info(b), *b.0)),
+            )
+            .find(|(info, start)| info.base + info.size >= align_up(*start, align) + size)
+    }
+
+    //None if the rounded size does not fit in u64
+    fn pow2_size(size: u64, align: u64) -> Option<(u64, u64)> {
+        let size = size.max(1).checked_next_power_of_two()?;
+        Some((size, size.max(align)))
+    }
+
+    fn slab_class(size: u64, align: u64) -> Option<u64> {
+        size.max(align)
+            .max(SLAB_MIN_SIZE)
+            .checked_next_power_of_two()
+            .filter(|class| *class <= SLAB_MAX_SIZE)
+    }
+
+    fn slab_alloc(&mut self, class: u64) -> Option<u64> {
+        if let Some(base) = self.slabs.get_mut(&class).and_then(|slots| slots.pop()) {
+            return Some(base);
+        }
+        //carve a whole page into slots, or a single slot if no page fits
+        let (info, size) = if let Some(info) = self.best_fit(SLAB_PAGE_SIZE, SLAB_PAGE_SIZE) {
+            (info, SLAB_PAGE_SIZE)
+        } else {
+            (self.best_fit(class, class)?, class)
+        };
+        let base = self.carve(&info, size, size);
+        let slots = self.slabs.entry(class).or_default();
+        slots.extend((1..size / class).rev().map(|i| base + i * class));
+        Some(base)
+    }
+
+    //give cached slab slots back to the free blocks
+    pub(super) fn slab_reclaim(&mut self) -> bool {
+        let slots = std::mem::take(&mut self.slabs)
+            .into_iter()
+            .flat_map(|(class, slots)| slots.into_iter().map(move |base| (base, class)))
+            .collect::<Vec<_>>();
+        let reclaimed = !slots.is_empty();
+        for (base, size) in slots {
+            self.release(base, size)
+        }
+        reclaimed
+    }
+
+    //same seed and same request sequence give the same addresses
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = if seed == 0 { DEFAULT_SEED } else { seed };
+        self.log_op(|| format!("seed {:#x}", seed))
+    }
+
+    //xorshift64*
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    //the first block that fits from a random address on, wrapping around, then a random aligned slot in it
+    fn random_fit(&mut self, size: u64, align: u64) -> Option<u64> {
+        let from = self.info.base + self.next_random() % self.info.size.max(1);
+        let info = self
+            .free_fit(from, size, align)
+            .or_else(|| self.first_fit(size, align))?;
+        let start = align_up(info.base, align);
+        let slots = (info.base + info.size - start - size) / align.max(1) + 1;
+        Some(start + self.next_random() % slots * align.max(1))
+    }
+
+    pub(super) fn policy_alloc(&mut self, size: u64, align: u64) -> Option<MemInfo> {
+        let (base, reserved) = match self.policy {
+            Policy::FirstFit => (self.carve(&self.first_fit(size, align)?, size, align), size),
+            Policy::BestFit => (self.carve(&self.best_fit(size, align)?, size, align), size),
+            Policy::NextFit => {
+                let (info, start) = self.next_fit(size, align)?;
+                let base = self.carve_from(&info, start, size, align);
+                self.cursor = base + size;
+                (base, size)
+            }
+            Policy::Random => {
+                let base = self.random_fit(size, align)?;
+                let info = self
+                    .free_blocks
+                    .range(..=base)
+                    .next_back()
+                    .map(to_info)
+                    .unwrap();
+                (self.carve_from(&info, base, size, align), size)
+            }
+            Policy::Pow2 => {
+                let (size, align) = Self::pow2_size(size, align)?;
+                (self.carve(&self.best_fit(size, align)?, size, align), size)
+            }
+            Policy::Slab => {
+                if let Some(class) = Self::slab_class(size, align) {
+                    let base = if let Some(base) = self.slab_alloc(class) {
+                        base
+                    } else if self.slab_reclaim() {
+                        self.slab_alloc(class)?
+                    } else {
+                        return None;
+                    };
+                    (base, class)
+                } else if let Some(info) = self.best_fit(size, align) {
+                    (self.carve(&info, size, align), size)
+                } else if self.slab_reclaim() {
+                    (self.carve(&self.best_fit(size, align)?, size, align), size)
+                } else {
+                    return None;
+                }
+            }
+        };
+        self.record_alloc(base, reserved);
+        Some(MemInfo { base, size })
+    }
+
+    fn reserved_size(&self, size: u64, align: u64) -> Option<u64> {
+        match self.policy {
+            Policy::Pow2 => Self::pow2_size(size, align).map(|(size, _)| size),
+            Policy::Slab => Some(Self::slab_class(size, align).unwrap_or(size)),
+            _ => Some(size),
+        }
+    }
+
+    //resize without moving, rounding policies only keep blocks of the same rounded size
+    pub(super) fn policy_resize(&mut self, base: u64, size: u64, align: u64) -> bool {
+        let old = self.alloced_blocks[&base];
+        let reserved = match self.reserved_size(size, align) {
+            Some(reserved) => reserved,
+            None => return false,
+        };
+        if align_up(base, align) != base {
+            return false;
+        }
+        match self.policy {
+            Policy::Pow2 | Policy::Slab => return reserved == old,
+            _ => {}
+        }
+        if reserved < old {
+            self.release(base + reserved, old - reserved);
+        } else if reserved > old {
+            let next = match self.free_blocks.get(&(base + old)) {
+                Some(&next) if old + next >= reserved => next,
+                _ => return false,
+            };
+            self.remove_free(base + old);
+            if old + next != reserved {
+                self.insert_free(base + reserved, old + next - reserved)
+            }
+        }
+        self.alloced_blocks.insert(base, reserved);
+        #[cfg(feature = "memprof")]
+        {
+            self.used = self.used + reserved - old;
+            self.high_water = self.high_water.max(self.used);
+        }
+        true
+    }
+
+    pub(super) fn policy_free(&mut self, base: u64, size: u64) {
+        match self.policy {
+            Policy::Slab if Self::slab_class(size, 1) == Some(size) => {
+                self.slabs.entry(size).or_default().push(base)
+            }
+            _ => self.release(base, size),
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use super::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PoolAttr {
+    pub dma: bool,
+    pub cacheable: bool,
+}
+
+impl PoolAttr {
+    pub const NORMAL: PoolAttr = PoolAttr {
+        dma: false,
+        cacheable: true,
+    };
+    pub const DMA: PoolAttr = PoolAttr {
+        dma: true,
+        cacheable: true,
+    };
+    pub const DMA_COHERENT: PoolAttr = PoolAttr {
+        dma: true,
+        cacheable: false,
+    };
+}
+
+impl Default for PoolAttr {
+    fn default() -> PoolAttr {
+        PoolAttr::NORMAL
+    }
+}
+
+//unset fields are don't care
+#[derive(Clone, Debug, Default)]
+pub struct Constraint {
+    pub limit: Option<u64>,
+    pub dma: Option<bool>,
+    pub cacheable: Option<bool>,
+    pub pool: Option<String>,
+}
+
+impl Constraint {
+    pub fn new() -> Constraint {
+        Constraint::default()
+    }
+
+    //the whole block must end at or below limit
+    pub fn below(mut self, limit: u64) -> Constraint {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn dma(mut self, dma: bool) -> Constraint {
+        self.dma = Some(dma);
+        self
+    }
+
+    pub fn cacheable(mut self, cacheable: bool) -> Constraint {
+        self.cacheable = Some(cacheable);
+        self
+    }
+
+    pub fn in_pool(mut self, name: &str) -> Constraint {
+        self.pool = Some(name.to_string());
+        self
+    }
+
+    fn allows(&self, pool: &Pool) -> bool {
+        self.pool.as_ref().is_none_or(|name| name == &pool.name)
+            && self.dma.is_none_or(|dma| dma == pool.attr.dma)
+            && self
+                .cacheable
+                .is_none_or(|cacheable| cacheable == pool.attr.cacheable)
+            && self
+                .limit
+                .is_none_or(|limit| pool.allocator.info.base < limit)
+    }
+}
+
+pub struct Pool {
+    pub name: String,
+    pub attr: PoolAttr,
+    allocator: Allocator,
+}
+
+impl Pool {
+    pub fn info(&self) -> MemInfo {
+        self.allocator.info
+    }
+
+    pub fn allocator(&self) -> &Allocator {
+        &self.allocator
+    }
+}
+
+impl Allocator {
+    //first fit in address order, policy is ignored to honour the limit
+    pub(super) fn alloc_below(&mut self, size: u64, align: u64, limit: u64) -> Option<MemInfo> {
+        let info = self.alloc_below_block(size, align, limit);
+        self.log_op(|| {
+            format!(
+                "alloc_below {:#x} {:#x} {:#x} {}",
+                size,
+                align,
+                limit,
+                info.map_or("none".to_string(), |info| format!("{:#x}", info.base))
+            )
+        });
+        info
+    }
+
+    fn alloc_below_block(&mut self, size: u64, align: u64, limit: u64) -> Option<MemInfo> {
+        let info = self
+            .free_blocks
+            .range(..limit)
+            .map(|(&base, &size)| MemInfo { base, size })
+            .find(|info| {
+                let base = align_up(info.base, align);
+                base + size <= limit && base + size <= info.base + info.size
+            })?;
+        let base = self.carve(&info, size, align);
+        self.record_alloc(base, size);
+        Some(MemInfo { base, size })
+    }
+}
+
+//pools are disjoint and tried in the order they were added
+#[derive(Default)]
+pub struct PoolAllocator {
+    pools: Vec<Pool>,
+}
+
+impl PoolAllocator {
+    pub fn new() -> PoolAllocator {
+        PoolAllocator::default()
+    }
+
+    pub fn add_pool(
+        &mut self,
+        name: &str,
+        base: u64,
+        size: u64,
+        attr: PoolAttr,
+        policy: Policy,
+    ) -> Result<(), Error> {
+        if self.pool(name).is_some() {
+            return Err(Error::DuplicatePool(name.to_string()));
+        }
+        let range = MemInfo { base, size };
+        if let Some(pool) = self.pools.iter().find(|pool| {
+            let info = pool.info();
+            info.base < base + size && base < info.base + info.size
+        }) {
+            return Err(Error::Occupied(range, pool.info()));
+        }
+        self.pools.push(Pool {
+            name: name.to_string(),
+            attr,
+            allocator: Allocator::with_policy(base, size, policy),
+        });
+        Ok(())
+    }
+
+    pub fn pools(&self) -> impl Iterator<Item = &Pool> {
+        self.pools.iter()
+    }
+
+    pub fn pool(&self, name: &str) -> Option<&Pool> {
+        self.pools.iter().find(|pool| pool.name == name)
+    }
+
+    pub fn pool_of(&self, addr: u64) -> Option<&Pool> {
+        self.pools.iter().find(|pool| {
+            let info = pool.info();
+            addr >= info.base && addr < info.base + info.size
+        })
+    }
+
+    fn pool_of_mut(&mut self, addr: u64) -> Option<&mut Pool> {
+        self.pools.iter_mut().find(|pool| {
+            let info = pool.allocator.info;
+            addr >= info.base && addr < info.base + info.size
+        })
+    }
+
+    pub fn alloc(&mut self, size: u64, align: u64, constraint: &Constraint) -> Option<MemInfo> {
+        self.pools
+            .iter_mut()
+            .filter(|pool| constraint.allows(pool))
+            .find_map(|pool| {
+                let info = pool.allocator.info;
+                match constraint.limit {
+                    Some(limit) if info.base + info.size > limit => {
+                        pool.allocator.alloc_below(size, align, limit)
+                    }
+                    _ => pool.allocator.alloc(size, align),
+                }
+            })
+    }
+
+    pub fn free(&mut self, addr: u64) -> Result<(), Error> {
+        if let Some(pool) = self.pool_of_mut(addr) {
+            pool.allocator.free(addr)
+        } else {
+            Err(Error::NotAllocated(addr))
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LockedPoolAllocator {
+    inner: Mutex<PoolAllocator>,
+}
+
+impl LockedPoolAllocator {
+    pub fn new() -> LockedPoolAllocator {
+        LockedPoolAllocator::default()
+    }
+    pub fn add_pool(
+        &self,
+        name: &str,
+        base: u64,
+        size: u64,
+        attr: PoolAttr,
+        policy: Policy,
+    ) -> Result<(), Error> {
+        self.inner
+            .lock()
+            .unwrap()
+            .add_pool(name, base, size, attr, policy)
+    }
+    pub fn alloc(&self, size: u64, align: u64, constraint: &Constraint) -> Option<MemInfo> {
+        self.inner.lock().unwrap().alloc(size, align, constraint)
+    }
+    pub fn free(&self, addr: u64) -> Result<(), Error> {
+        self.inner.lock().unwrap().free(addr)
+    }
+}
+
+impl Deref for LockedPoolAllocator {
+    type Target = Mutex<PoolAllocator>;
+
+    fn deref(&self) -> &Mutex<PoolAllocator> {
+        &self.inner
+    }
+}
@@ -0,0 +1,259 @@
+use super::*;
+use std::convert::TryFrom;
+use std::io::{self, BufRead};
+
+//text format, one record per line, numbers in hex:
+//  allocator <base> <size> <policy> <cursor> <seed> <strict>
+//  free_block <base> <size>
+//  used_block <base> <size>
+//  reserved_block <base> <size>
+//  slab_slot <class> <base>
+//  freed_addr <base>
+//  high_water <size>
+//a log is a snapshot followed by the calls made after it together with their results:
+//  alloc <size> <align> <base|none>
+//  alloc_below <size> <align> <limit> <base|none>
+//  free <addr> <ok|error>
+//  realloc <addr> <size> <align> <base|error>
+//  alloc_at <base> <size> <ok|error>
+//  reserve <base> <size> <ok|error>
+//  seed <seed>
+//  reset
+//tags are not saved
+
+pub(super) fn status<T, F: FnOnce(&T) -> String>(result: &Result<T, Error>, ok: F) -> String {
+    match result {
+        Ok(v) => ok(v),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn parse_u64(s: &str) -> Result<u64, String> {
+    let v = if let Some(hex) = s.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16)
+    } else {
+        s.parse::<u64>()
+    };
+    v.map_err(|e| format!("invalid number {}:{}!", s, e))
+}
+
+struct Record<'a> {
+    key: &'a str,
+    fields: Vec<u64>,
+    result: &'a str,
+}
+
+impl<'a> Record<'a> {
+    //the result is the rest of the line, error messages may contain spaces
+    fn parse(line: &'a str, n: usize, with_result: bool) -> Result<Record<'a>, String> {
+        let mut parts = line.splitn(n + 2, ' ');
+        let key = parts.next().unwrap();
+        let fields = (0..n)
+            .map(|_| parts.next().ok_or("missing field!".to_string()))
+            .map(|s| s.and_then(parse_u64))
+            .collect::<Result<Vec<_>, String>>()?;
+        let result = parts.next().unwrap_or("");
+        if with_result == result.is_empty() {
+            return Err("wrong number of fields!".to_string());
+        }
+        Ok(Record {
+            key,
+            fields,
+            result,
+        })
+    }
+}
+
+impl Allocator {
+    pub(super) fn log_op<F: FnOnce() -> String>(&mut self, op: F) {
+        if let Some(ref mut log) = self.log {
+            writeln!(log, "{}", op()).expect("write allocator log fail!")
+        }
+    }
+
+    pub fn save<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(
+            w,
+            "allocator {:#x} {:#x} {} {:#x} {:#x} {}",
+            self.info.base,
+            self.info.size,
+            self.policy as u32,
+            self.cursor,
+            self.rng,
+            self.strict as u32
+        )?;
+        for (base, size) in self.free_blocks.iter() {
+            writeln!(w, "free_block {:#x} {:#x}", base, size)?;
+        }
+        for (base, size) in self.alloced_blocks.iter() {
+            writeln!(w, "used_block {:#x} {:#x}", base, size)?;
+        }
+        for (base, size) in self.reserved.iter() {
+            writeln!(w, "reserved_block {:#x} {:#x}", base, size)?;
+        }
+        for (class, slots) in self.slabs.iter() {
+            for base in slots.iter() {
+                writeln!(w, "slab_slot {:#x} {:#x}", class, base)?;
+            }
+        }
+        //oldest first, restoring keeps the order
+        for base in self.freed_order.values() {
+            writeln!(w, "freed_addr {:#x}", base)?;
+        }
+        #[cfg(feature = "memprof")]
+        writeln!(w, "high_water {:#x}", self.high_water)?;
+        Ok(())
+    }
+
+    fn restore_record(&mut self, record: &Record) -> Result<(), String> {
+        let f = &record.fields;
+        match record.key {
+            "free_block" => self.insert_free(f[0], f[1]),
+            "used_block" => {
+                self.alloced_blocks.insert(f[0], f[1]);
+                #[cfg(feature = "memprof")]
+                {
+                    self.used += f[1];
+                }
+            }
+            "reserved_block" => {
+                self.reserved.insert(f[0], f[1]);
+            }
+            "slab_slot" => self.slabs.entry(f[0]).or_default().push(f[1]),
+            "freed_addr" => self.note_freed(f[0]),
+            "high_water" => {
+                #[cfg(feature = "memprof")]
+                {
+                    self.high_water = f[0];
+                }
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn replay_record(&mut self, record: &Record) -> Result<(), String> {
+        let f = &record.fields;
+        let result = match record.key {
+            "alloc" => self
+                .alloc(f[0], f[1])
+                .map_or("none".to_string(), |info| format!("{:#x}", info.base)),
+            "alloc_below" => self
+                .alloc_below(f[0], f[1], f[2])
+                .map_or("none".to_string(), |info| format!("{:#x}", info.base)),
+            "free" => status(&self.free(f[0]), |_| "ok".to_string()),
+            "realloc" => status(&self.realloc(f[0], f[1], f[2]), |info| {
+                format!("{:#x}", info.base)
+            }),
+            "alloc_at" => status(&self.alloc_at(f[0], f[1]), |_| "ok".to_string()),
+            "reserve" => status(&self.reserve(f[0], f[1]), |_| "ok".to_string()),
+            "seed" => {
+                self.set_seed(f[0]);
+                return Ok(());
+            }
+            "reset" => {
+                self.reset();
+                return Ok(());
+            }
+            _ => unreachable!(),
+        };
+        if result == record.result {
+            Ok(())
+        } else {
+            Err(format!("expect {} but get {}!", record.result, result))
+        }
+    }
+
+    fn load<R: BufRead>(r: R, replay: bool) -> Result<Allocator, String> {
+        let mut allocator: Option<Allocator> = None;
+        for (i, line) in r.lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let key = line.split(' ').next().unwrap();
+            let (n, with_result, is_op) = match key {
+                "allocator" => (6, false, false),
+                "free_block" | "used_block" | "reserved_block" | "slab_slot" => (2, false, false),
+                "freed_addr" | "high_water" => (1, false, false),
+                "alloc" | "alloc_at" | "reserve" => (2, true, true),
+                "free" => (1, true, true),
+                "realloc" | "alloc_below" => (3, true, true),
+                "seed" => (1, false, true),
+                "reset" => (0, false, true),
+                _ => return Err(format!("line {}: unknown record {}!", i + 1, key)),
+            };
+            let record = Record::parse(line, n, with_result)
+                .map_err(|e| format!("line {}: {}", i + 1, e))?;
+            let result = match (allocator.as_mut(), key) {
+                (None, "allocator") => {
+                    let f = &record.fields;
+                    Policy::try_from(f[2] as u32).map(|policy| {
+                        let mut a = Allocator::with_policy(f[0], f[1], policy);
+                        //start from an empty allocator, blocks come from the records
+                        a.remove_free(f[0]);
+                        a.cursor = f[3];
+                        a.rng = f[4];
+                        a.strict = f[5] != 0;
+                        allocator = Some(a);
+                    })
+                }
+                (None, _) => Err("allocator record must come first!".to_string()),
+                (Some(_), "allocator") => Err("duplicated allocator record!".to_string()),
+                (Some(_), _) if is_op && !replay => {
+                    Err(format!("{} is a log record, use replay!", key))
+                }
+                (Some(a), _) if is_op => a.replay_record(&record),
+                (Some(a), _) => a.restore_record(&record),
+            };
+            result.map_err(|e| format!("line {}: {}", i + 1, e))?;
+        }
+        allocator.ok_or_else(|| "no allocator record!".to_string())
+    }
+
+    pub fn restore<R: BufRead>(r: R) -> Result<Allocator, String> {
+        Self::load(r, false)
+    }
+
+    //restore the snapshot at the head of a log and redo the calls, results must match the log
+    pub fn replay<R: BufRead>(r: R) -> Result<Allocator, String> {
+        Self::load(r, true)
+    }
+
+    //a snapshot is written first so the log can be replayed alone
+    pub fn start_log(&mut self, mut w: Box<dyn Write + Send>) -> io::Result<()> {
+        self.save(&mut w)?;
+        self.log = Some(w);
+        Ok(())
+    }
+
+    pub fn stop_log(&mut self) -> Option<Box<dyn Write + Send>> {
+        self.log.take().map(|mut log| {
+            log.flush().expect("flush allocator log fail!");
+            log
+        })
+    }
+}
+
+impl LockedAllocator {
+    pub fn save<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.inner.lock().unwrap().save(w)
+    }
+
+    pub fn start_log(&self, w: Box<dyn Write + Send>) -> io::Result<()> {
+        self.inner.lock().unwrap().start_log(w)
+    }
+
+    pub fn stop_log(&self) -> Option<Box<dyn Write + Send>> {
+        self.inner.lock().unwrap().stop_log()
+    }
+}
+
+impl From<Allocator> for LockedAllocator {
+    fn from(allocator: Allocator) -> LockedAllocator {
+        LockedAllocator {
+            inner: Mutex::new(allocator),
+        }
+    }
+}
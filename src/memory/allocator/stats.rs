@@ -0,0 +1,78 @@
+#[cfg(feature = "memprof")]
+use super::*;
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct Stats {
+    pub total_free: u64,
+    pub largest_free: u64,
+    pub fragments: u64,
+    pub high_water: u64,
+    pub live: u64,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "total free:   {:#x}", self.total_free)?;
+        writeln!(f, "largest free: {:#x}", self.largest_free)?;
+        writeln!(f, "fragments:    {}", self.fragments)?;
+        writeln!(f, "high water:   {:#x}", self.high_water)?;
+        write!(f, "live:         {}", self.live)
+    }
+}
+
+#[cfg(feature = "memprof")]
+impl Allocator {
+    //cached slab slots are free but not counted as fragments, reserved ranges are not free
+    pub fn stats(&self) -> Stats {
+        Stats {
+            total_free: self.info.size - self.used - self.reserved().map(|r| r.size).sum::<u64>(),
+            largest_free: self
+                .free_sizes
+                .iter()
+                .next_back()
+                .map_or(0, |&(size, _)| size),
+            fragments: self.free_blocks.len() as u64,
+            high_water: self.high_water,
+            live: self.alloced_blocks.len() as u64,
+        }
+    }
+
+    pub fn allocations(&self) -> impl Iterator<Item = MemInfo> + '_ {
+        self.alloced_blocks
+            .iter()
+            .map(|(&base, &size)| MemInfo { base, size })
+    }
+
+    pub fn report(&self) -> String {
+        let mut s = format!(
+            "allocator @{:#x} size {:#x} {:?}\n{}\n",
+            self.info.base,
+            self.info.size,
+            self.policy,
+            self.stats()
+        );
+        for info in self.allocations() {
+            s.push_str(&format!("  {:#016x} size {:#x}\n", info.base, info.size));
+        }
+        for info in self.reserved() {
+            s.push_str(&format!(
+                "  {:#016x} size {:#x} reserved\n",
+                info.base, info.size
+            ));
+        }
+        s
+    }
+}
+
+#[cfg(feature = "memprof")]
+impl LockedAllocator {
+    pub fn stats(&self) -> Stats {
+        self.inner.lock().unwrap().stats()
+    }
+
+    pub fn report(&self) -> String {
+        self.inner.lock().unwrap().report()
+    }
+}
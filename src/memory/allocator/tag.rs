@@ -0,0 +1,105 @@
+use super::*;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    pub location: Option<String>,
+}
+
+impl Tag {
+    pub fn new(name: &str) -> Tag {
+        Tag {
+            name: name.to_string(),
+            location: None,
+        }
+    }
+
+    pub fn with_location(name: &str, location: &str) -> Tag {
+        Tag {
+            name: name.to_string(),
+            location: Some(location.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self.name)?;
+        if let Some(ref location) = self.location {
+            write!(f, " @{}", location)?;
+        }
+        Ok(())
+    }
+}
+
+impl Allocator {
+    pub fn alloc_tagged(&mut self, size: u64, align: u64, tag: Tag) -> Option<MemInfo> {
+        let info = self.alloc(size, align)?;
+        self.tags.insert(info.base, tag);
+        Some(info)
+    }
+
+    pub fn set_tag(&mut self, addr: u64, tag: Tag) -> bool {
+        if self.alloced_blocks.contains_key(&addr) {
+            self.tags.insert(addr, tag);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn get_tag(&self, addr: u64) -> Option<&Tag> {
+        self.tags.get(&addr)
+    }
+
+    //every block still allocated is reported as leaked
+    pub fn leaks(&self) -> Vec<(MemInfo, Option<Tag>)> {
+        self.alloced_blocks
+            .iter()
+            .map(|(&base, &size)| (MemInfo { base, size }, self.tags.get(&base).cloned()))
+            .collect()
+    }
+
+    pub fn leak_report(&self) -> String {
+        let leaks = self.leaks();
+        let mut s = format!(
+            "{} blocks leaked in allocator @{:#x} size {:#x}\n",
+            leaks.len(),
+            self.info.base,
+            self.info.size
+        );
+        for (info, tag) in leaks {
+            if let Some(tag) = tag {
+                s.push_str(&format!(
+                    "  {:#016x} size {:#x} tag {}\n",
+                    info.base, info.size, tag
+                ));
+            } else {
+                s.push_str(&format!(
+                    "  {:#016x} size {:#x} untagged\n",
+                    info.base, info.size
+                ));
+            }
+        }
+        s
+    }
+}
+
+impl LockedAllocator {
+    pub fn alloc_tagged(&self, size: u64, align: u64, tag: Tag) -> Option<MemInfo> {
+        self.inner.lock().unwrap().alloc_tagged(size, align, tag)
+    }
+
+    pub fn set_tag(&self, addr: u64, tag: Tag) -> bool {
+        self.inner.lock().unwrap().set_tag(addr, tag)
+    }
+
+    pub fn leaks(&self) -> Vec<(MemInfo, Option<Tag>)> {
+        self.inner.lock().unwrap().leaks()
+    }
+
+    pub fn leak_report(&self) -> String {
+        self.inner.lock().unwrap().leak_report()
+    }
+}
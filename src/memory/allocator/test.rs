@@ -1,3 +1,4 @@
+use super::list::*;
 use super::*;
 use std::borrow::Borrow;
 use std::sync::mpsc::Sender;
@@ -101,16 +102,13 @@ fn basic_free() {
     assert_eq!(allocator.alloc(1, 1), Some(MemInfo { base: 5, size: 1 }));
     assert_eq!(allocator.alloc(2, 1), Some(MemInfo { base: 6, size: 2 }));
     assert_eq!(allocator.alloc(1, 1), None);
-    allocator.free(8);
-    allocator.free(5);
-    allocator.free(1);
-    allocator.free(6);
-    assert!(allocator.free_blocks.iter().count() == 1);
-    assert_eq!(
-        allocator.free_blocks.car(),
-        Some(MemInfo { base: 1, size: 9 })
-    );
-    assert_eq!(allocator.alloced_blocks.car(), None);
+    allocator.free(8).unwrap();
+    allocator.free(5).unwrap();
+    allocator.free(1).unwrap();
+    allocator.free(6).unwrap();
+    assert!(allocator.free_blocks.len() == 1);
+    assert_eq!(allocator.free_blocks.iter().next(), Some((&1, &9)));
+    assert!(allocator.alloced_blocks.is_empty());
 }
 
 #[test]
@@ -146,7 +144,7 @@ fn basic_concurrency_alloc() {
     for block in addrs {
         let la = Arc::clone(&allocator);
         let handle = thread::spawn(move || {
-            la.free(block.unwrap().base);
+            la.free(block.unwrap().base).unwrap();
         });
         handles.push(handle);
     }
@@ -155,8 +153,578 @@ fn basic_concurrency_alloc() {
     }
     {
         let inner = allocator.inner.lock().unwrap();
-        assert!(inner.free_blocks.iter().count() == 1);
-        assert_eq!(inner.free_blocks.car(), Some(MemInfo { base: 1, size: 9 }));
-        assert_eq!(inner.alloced_blocks.car(), None);
+        assert!(inner.free_blocks.len() == 1);
+        assert_eq!(inner.free_blocks.iter().next(), Some((&1, &9)));
+        assert!(inner.alloced_blocks.is_empty());
+    }
+}
+
+#[test]
+fn policy_first_fit() {
+    let allocator = &mut Allocator::with_policy(0, 0x100, Policy::FirstFit);
+    let a = allocator.alloc(0x20, 1).unwrap();
+    let b = allocator.alloc(0x10, 1).unwrap();
+    let c = allocator.alloc(0x8, 1).unwrap();
+    allocator.alloc(0x8, 1).unwrap();
+    allocator.free(a.base).unwrap();
+    allocator.free(c.base).unwrap();
+    assert_eq!(allocator.alloc(0x8, 1), Some(MemInfo { base: 0, size: 8 }));
+    assert_eq!(
+        b,
+        MemInfo {
+            base: 0x20,
+            size: 0x10
+        }
+    );
+    //the tree must agree with a scan in address order on a fragmented heap
+    let allocator = &mut Allocator::with_policy(0x1000, 0x10000, Policy::FirstFit);
+    let mut blocks = vec![];
+    let mut seed = 1u64;
+    for i in 0..512 {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let (size, align) = ((seed >> 33) % 0x80 + 1, 1 << ((seed >> 60) % 6));
+        let scan = allocator
+            .free_blocks
+            .iter()
+            .map(|(&base, &size)| MemInfo { base, size })
+            .find(|info| info.size >= size + (align_up(info.base, align) - info.base))
+            .map(|info| align_up(info.base, align));
+        let info = allocator.alloc(size, align);
+        assert_eq!(info.map(|info| info.base), scan);
+        blocks.extend(info);
+        if i % 2 == 0 && !blocks.is_empty() {
+            let victim = blocks.remove((seed >> 40) as usize % blocks.len());
+            allocator.free(victim.base).unwrap();
+        }
+    }
+}
+
+#[test]
+fn policy_best_fit() {
+    let allocator = &mut Allocator::with_policy(0, 0x100, Policy::BestFit);
+    let a = allocator.alloc(0x20, 1).unwrap();
+    allocator.alloc(0x10, 1).unwrap();
+    let c = allocator.alloc(0x8, 1).unwrap();
+    allocator.alloc(0x8, 1).unwrap();
+    allocator.free(a.base).unwrap();
+    allocator.free(c.base).unwrap();
+    assert_eq!(allocator.alloc(0x8, 1), Some(c));
+}
+
+#[test]
+fn policy_next_fit() {
+    let allocator = &mut Allocator::with_policy(0, 0x40, Policy::NextFit);
+    let a = allocator.alloc(0x10, 1).unwrap();
+    let b = allocator.alloc(0x10, 1).unwrap();
+    allocator.free(a.base).unwrap();
+    assert_eq!(
+        allocator.alloc(0x10, 1),
+        Some(MemInfo {
+            base: 0x20,
+            size: 0x10
+        })
+    );
+    assert_eq!(
+        allocator.alloc(0x10, 1),
+        Some(MemInfo {
+            base: 0x30,
+            size: 0x10
+        })
+    );
+    assert_eq!(allocator.alloc(0x10, 1), Some(a));
+    assert_eq!(allocator.alloc(0x10, 1), None);
+    allocator.free(b.base).unwrap();
+    assert_eq!(
+        allocator.alloc(0x8, 1),
+        Some(MemInfo {
+            base: 0x10,
+            size: 0x8
+        })
+    );
+    //cursor inside a coalesced block
+    let allocator = &mut Allocator::with_policy(0, 0x40, Policy::NextFit);
+    let a = allocator.alloc(0x10, 1).unwrap();
+    allocator.free(a.base).unwrap();
+    assert_eq!(
+        allocator.alloc(0x10, 1),
+        Some(MemInfo {
+            base: 0x10,
+            size: 0x10
+        })
+    );
+    assert_eq!(allocator.free_blocks.len(), 2);
+}
+
+#[test]
+fn policy_pow2() {
+    let allocator = &mut Allocator::with_policy(0, 0x100, Policy::Pow2);
+    assert_eq!(allocator.alloc(0x3, 1), Some(MemInfo { base: 0, size: 3 }));
+    assert_eq!(
+        allocator.alloc(0x9, 1),
+        Some(MemInfo {
+            base: 0x10,
+            size: 9
+        })
+    );
+    assert_eq!(
+        allocator.alloc(0x2, 1),
+        Some(MemInfo { base: 0x4, size: 2 })
+    );
+    assert_eq!(allocator.alloc(0x81, 1), None);
+    assert_eq!(allocator.alloc(u64::MAX - 1, 1), None);
+    assert_eq!(allocator.alloced_blocks.get(&0x10), Some(&0x10));
+    allocator.free(0).unwrap();
+    allocator.free(0x4).unwrap();
+    allocator.free(0x10).unwrap();
+    assert_eq!(allocator.free_blocks.iter().next(), Some((&0, &0x100)));
+}
+
+#[test]
+fn policy_slab() {
+    let allocator = &mut Allocator::with_policy(0, 0x2000, Policy::Slab);
+    let a = allocator.alloc(0x5, 1).unwrap();
+    let b = allocator.alloc(0x7, 1).unwrap();
+    assert_eq!(b.base - a.base, 8);
+    allocator.free(a.base).unwrap();
+    assert_eq!(
+        allocator.alloc(0x6, 1),
+        Some(MemInfo {
+            base: a.base,
+            size: 6
+        })
+    );
+    let c = allocator.alloc(0x20, 1).unwrap();
+    assert_eq!(c.base & 0xfff, 0);
+    assert_ne!(c.base, a.base);
+    //cached slots are given back when a large request does not fit
+    allocator.free(a.base).unwrap();
+    allocator.free(b.base).unwrap();
+    allocator.free(c.base).unwrap();
+    assert_eq!(
+        allocator.alloc(0x2000, 1),
+        Some(MemInfo {
+            base: 0,
+            size: 0x2000
+        })
+    );
+}
+
+#[cfg(feature = "memprof")]
+#[test]
+fn allocator_stats() {
+    let allocator = &mut Allocator::new(0, 0x100);
+    let a = allocator.alloc(0x10, 1).unwrap();
+    let b = allocator.alloc(0x20, 1).unwrap();
+    allocator.alloc(0x10, 1).unwrap();
+    allocator.free(b.base).unwrap();
+    assert_eq!(
+        allocator.stats(),
+        Stats {
+            total_free: 0xe0,
+            largest_free: 0xc0,
+            fragments: 2,
+            high_water: 0x40,
+            live: 2,
+        }
+    );
+    assert_eq!(
+        allocator.allocations().collect::<Vec<_>>(),
+        vec![
+            a,
+            MemInfo {
+                base: 0x30,
+                size: 0x10
+            }
+        ]
+    );
+    let report = allocator.report();
+    assert!(report.starts_with("allocator @0x0 size 0x100 FirstFit\n"));
+    assert!(report.contains(&allocator.stats().to_string()));
+    assert!(report.contains("\n  0x00000000000000 size 0x10\n"));
+    assert!(report.ends_with("\n  0x00000000000030 size 0x10\n"));
+    assert!(!report.contains("reserved"));
+    let locked = LockedAllocator::with_policy(0, 0x100, Policy::Pow2);
+    locked.alloc(0x3, 1).unwrap();
+    assert_eq!(locked.stats().high_water, 4);
+}
+
+#[test]
+fn allocator_tag() {
+    let allocator = &mut Allocator::new(0, 0x100);
+    let a = allocator
+        .alloc_tagged(0x10, 1, Tag::with_location("desc", "ring.sv:10"))
+        .unwrap();
+    let b = allocator.alloc(0x10, 1).unwrap();
+    assert!(allocator.set_tag(b.base, Tag::new("buffer")));
+    assert!(!allocator.set_tag(0x80, Tag::new("none")));
+    let c = allocator.alloc(0x10, 1).unwrap();
+    allocator.free(b.base).unwrap();
+    assert_eq!(allocator.get_tag(b.base), None);
+    assert_eq!(
+        allocator.leaks(),
+        vec![
+            (a, Some(Tag::with_location("desc", "ring.sv:10"))),
+            (c, None)
+        ]
+    );
+    let report = allocator.leak_report();
+    assert!(report.starts_with("2 blocks leaked"));
+    assert!(report.contains("tag \"desc\" @ring.sv:10"));
+    assert!(report.contains("untagged"));
+}
+
+#[test]
+fn invalid_free() {
+    let allocator = &mut Allocator::new(0, 0x100);
+    let a = allocator.alloc(0x10, 1).unwrap();
+    let b = allocator.alloc(0x10, 1).unwrap();
+    allocator.free(a.base).unwrap();
+    assert_eq!(allocator.free(a.base), Err(Error::DoubleFree(0)));
+    assert_eq!(allocator.free(0x14), Err(Error::InteriorPointer(0x14, b)));
+    assert_eq!(allocator.free(0x40), Err(Error::NotAllocated(0x40)));
+    //reallocated base is valid again
+    assert_eq!(allocator.alloc(0x8, 1), Some(MemInfo { base: 0, size: 8 }));
+    allocator.free(0).unwrap();
+    allocator.alloc(0x10, 1).unwrap();
+    assert_eq!(allocator.free(0x8), Err(Error::InteriorPointer(0x8, a)));
+
+    //only the most recent frees are remembered
+    let allocator = &mut Allocator::new(0, FREED_HISTORY as u64 + 1);
+    let blocks = (0..=FREED_HISTORY)
+        .map(|_| allocator.alloc(1, 1).unwrap())
+        .collect::<Vec<_>>();
+    for b in blocks.iter() {
+        allocator.free(b.base).unwrap();
+    }
+    assert_eq!(allocator.free(0), Err(Error::NotAllocated(0)));
+    assert_eq!(allocator.free(1), Err(Error::DoubleFree(1)));
+}
+
+#[test]
+#[should_panic(expected = "invalid free @0: DoubleFree!0x0")]
+fn strict_free() {
+    let allocator = LockedAllocator::new(0, 0x100);
+    allocator.set_strict(true);
+    let a = allocator.alloc(0x10, 1).unwrap();
+    allocator.free(a.base).unwrap();
+    allocator.free(a.base).unwrap();
+}
+
+#[test]
+fn allocator_realloc() {
+    let allocator = &mut Allocator::new(0, 0x100);
+    let a = allocator.alloc_tagged(0x10, 1, Tag::new("ring")).unwrap();
+    assert_eq!(
+        allocator.realloc(a.base, 0x20, 1),
+        Ok(MemInfo {
+            base: 0,
+            size: 0x20
+        })
+    );
+    assert_eq!(
+        allocator.realloc(a.base, 0x8, 1),
+        Ok(MemInfo { base: 0, size: 8 })
+    );
+    let b = allocator.alloc(0x8, 1).unwrap();
+    assert_eq!(b.base, 0x8);
+    //blocked by b, so moved
+    let a = allocator.realloc(a.base, 0x10, 1).unwrap();
+    assert_eq!(
+        a,
+        MemInfo {
+            base: 0x10,
+            size: 0x10
+        }
+    );
+    assert_eq!(allocator.get_tag(0x10), Some(&Tag::new("ring")));
+    assert_eq!(allocator.free(0), Err(Error::DoubleFree(0)));
+    assert_eq!(
+        allocator.realloc(b.base, 0x100, 1),
+        Err(Error::OutOfMemory(0x100))
+    );
+    assert_eq!(allocator.alloced_blocks.get(&b.base), Some(&8));
+    assert_eq!(
+        allocator.realloc(0x14, 0x20, 1),
+        Err(Error::InteriorPointer(0x14, a))
+    );
+    //rounding policies resize in place only within the rounded size
+    let allocator = &mut Allocator::with_policy(0, 0x100, Policy::Pow2);
+    let a = allocator.alloc(0x5, 1).unwrap();
+    assert_eq!(
+        allocator.realloc(a.base, 0x7, 1),
+        Ok(MemInfo { base: 0, size: 7 })
+    );
+    assert_eq!(
+        allocator.realloc(a.base, 0x9, 1),
+        Ok(MemInfo {
+            base: 0x10,
+            size: 9
+        })
+    );
+}
+
+#[test]
+fn allocator_fixed() {
+    let allocator = &mut Allocator::new(0, 0x100);
+    allocator.reserve(0x40, 0x40).unwrap();
+    assert_eq!(
+        allocator.alloc_at(0x30, 0x20),
+        Err(Error::Reserved(
+            MemInfo {
+                base: 0x30,
+                size: 0x20
+            },
+            MemInfo {
+                base: 0x40,
+                size: 0x40
+            }
+        ))
+    );
+    let fw = allocator.alloc_at(0x20, 0x10).unwrap();
+    assert_eq!(
+        fw,
+        MemInfo {
+            base: 0x20,
+            size: 0x10
+        }
+    );
+    assert_eq!(
+        allocator.reserve(0x28, 0x4),
+        Err(Error::Occupied(
+            MemInfo {
+                base: 0x28,
+                size: 0x4
+            },
+            fw
+        ))
+    );
+    assert_eq!(
+        allocator.alloc_at(0xf0, 0x20),
+        Err(Error::OutOfRange(MemInfo {
+            base: 0xf0,
+            size: 0x20
+        }))
+    );
+    //reserved holes are never handed out
+    assert_eq!(
+        allocator.alloc(0x40, 0x40),
+        Some(MemInfo {
+            base: 0x80,
+            size: 0x40
+        })
+    );
+    assert_eq!(
+        allocator.alloc(0x40, 1),
+        Some(MemInfo {
+            base: 0xc0,
+            size: 0x40
+        })
+    );
+    assert_eq!(allocator.alloc(0x40, 1), None);
+    allocator.free(fw.base).unwrap();
+    assert_eq!(
+        allocator.alloc(0x40, 1),
+        Some(MemInfo {
+            base: 0,
+            size: 0x40
+        })
+    );
+    assert_eq!(
+        allocator.reserved().collect::<Vec<_>>(),
+        vec![MemInfo {
+            base: 0x40,
+            size: 0x40
+        }]
+    );
+    //cached slab slots are reclaimed to claim a range
+    let allocator = &mut Allocator::with_policy(0, 0x2000, Policy::Slab);
+    let slot = allocator.alloc(0x10, 1).unwrap();
+    allocator.free(slot.base).unwrap();
+    assert_eq!(
+        allocator.alloc_at(0x100, 0x10),
+        Ok(MemInfo {
+            base: 0x100,
+            size: 0x10
+        })
+    );
+}
+
+#[test]
+fn pool_allocator() {
+    let allocator = &mut PoolAllocator::new();
+    allocator
+        .add_pool(
+            "low",
+            0x1000,
+            0x1000,
+            PoolAttr::DMA_COHERENT,
+            Policy::BestFit,
+        )
+        .unwrap();
+    allocator
+        .add_pool(
+            "high",
+            0x1_0001_0000,
+            0x1000,
+            PoolAttr::NORMAL,
+            Policy::BestFit,
+        )
+        .unwrap();
+    allocator
+        .add_pool("span", 0xffff_f000, 0x2000, PoolAttr::DMA, Policy::NextFit)
+        .unwrap();
+    assert_eq!(
+        allocator.add_pool("low", 0x8000, 0x100, PoolAttr::NORMAL, Policy::BestFit),
+        Err(Error::DuplicatePool("low".to_string()))
+    );
+    assert_eq!(
+        allocator.add_pool("dup", 0x1800, 0x1000, PoolAttr::NORMAL, Policy::BestFit),
+        Err(Error::Occupied(
+            MemInfo {
+                base: 0x1800,
+                size: 0x1000
+            },
+            MemInfo {
+                base: 0x1000,
+                size: 0x1000
+            }
+        ))
+    );
+    let normal = allocator
+        .alloc(0x10, 1, &Constraint::new().dma(false))
+        .unwrap();
+    assert_eq!(allocator.pool_of(normal.base).unwrap().name, "high");
+    let coherent = Constraint::new().dma(true).cacheable(false);
+    let a = allocator.alloc(0x800, 1, &coherent).unwrap();
+    assert_eq!(a.base, 0x1000);
+    assert_eq!(allocator.alloc(0x1000, 1, &coherent), None);
+    //a pool crossing the limit only hands out the part below it
+    let below_4g = Constraint::new()
+        .dma(true)
+        .cacheable(true)
+        .below(0x1_0000_0000);
+    let b = allocator.alloc(0x800, 0x800, &below_4g).unwrap();
+    assert_eq!(
+        b,
+        MemInfo {
+            base: 0xffff_f000,
+            size: 0x800
+        }
+    );
+    assert!(allocator.alloc(0x1000, 1, &below_4g).is_none());
+    let c = allocator
+        .alloc(0x1000, 1, &Constraint::new().in_pool("span"))
+        .unwrap();
+    assert_eq!(c.base, 0xffff_f800);
+    allocator.free(b.base).unwrap();
+    assert_eq!(allocator.free(b.base), Err(Error::DoubleFree(b.base)));
+    assert_eq!(allocator.free(0x10), Err(Error::NotAllocated(0x10)));
+}
+
+#[test]
+fn allocator_random() {
+    let run = |seed: u64| {
+        let allocator = &mut Allocator::with_policy(0x1000, 0x10000, Policy::Random);
+        allocator.set_seed(seed);
+        let mut blocks = vec![];
+        for i in 0..64 {
+            let info = allocator.alloc(0x40 + i, 0x10).unwrap();
+            assert_eq!(info.base % 0x10, 0);
+            assert!(info.base >= 0x1000 && info.base + info.size <= 0x11000);
+            blocks.push(info);
+            if i % 3 == 0 {
+                let victim = blocks.remove(blocks.len() / 2);
+                allocator.free(victim.base).unwrap();
+            }
+        }
+        blocks.sort_by_key(|info| info.base);
+        for pair in blocks.windows(2) {
+            assert!(pair[0].base + pair[0].size <= pair[1].base);
+        }
+        blocks
+    };
+    assert_eq!(run(1), run(1));
+    assert_ne!(run(1), run(2));
+    //the only slot left must be found
+    let allocator = &mut Allocator::with_policy(0, 0x100, Policy::Random);
+    allocator.alloc_at(0, 0x80).unwrap();
+    allocator.alloc_at(0x90, 0x70).unwrap();
+    assert_eq!(
+        allocator.alloc(0x10, 0x10),
+        Some(MemInfo {
+            base: 0x80,
+            size: 0x10
+        })
+    );
+    assert_eq!(allocator.alloc(1, 1), None);
+}
+
+#[derive(Clone, Default)]
+struct SharedLog(Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
+
+fn saved(allocator: &Allocator) -> String {
+    let mut buf = vec![];
+    allocator.save(&mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn allocator_save_restore() {
+    let allocator = &mut Allocator::with_policy(0, 0x10000, Policy::Slab);
+    allocator.reserve(0x8000, 0x1000).unwrap();
+    let a = allocator.alloc(0x10, 1).unwrap();
+    allocator.alloc(0x2000, 0x100).unwrap();
+    allocator.free(a.base).unwrap();
+    let text = saved(allocator);
+    let restored = &mut Allocator::restore(text.as_bytes()).unwrap();
+    assert_eq!(saved(restored), text);
+    assert_eq!(restored.free(a.base), Err(Error::DoubleFree(a.base)));
+    assert_eq!(allocator.alloc(0x10, 1), restored.alloc(0x10, 1));
+    assert!(Allocator::restore("free_block 0x0 0x10".as_bytes()).is_err());
+    assert!(Allocator::restore("allocator 0x0 0x100 0x9 0x0 0x1 0".as_bytes()).is_err());
+    assert!(
+        Allocator::restore("allocator 0x0 0x100 0x1 0x0 0x1 0\nalloc 0x10 0x1 0x0".as_bytes())
+            .is_err()
+    );
+}
+
+#[test]
+fn allocator_replay() {
+    let log = SharedLog::default();
+    let allocator = &mut Allocator::with_policy(0x1000, 0x1000, Policy::Random);
+    allocator.set_seed(7);
+    allocator.alloc(0x100, 0x10).unwrap();
+    allocator.start_log(Box::new(log.clone())).unwrap();
+    allocator.set_seed(11);
+    let a = allocator.alloc(0x20, 0x10).unwrap();
+    allocator.alloc_at(0x1ff0, 0x10).ok();
+    allocator.realloc(a.base, 0x40, 0x10).unwrap();
+    allocator.free(a.base).ok();
+    allocator.alloc_below(0x10, 0x10, 0x1800).unwrap();
+    allocator.alloc(0x2000, 1);
+    allocator.stop_log();
+    allocator.alloc(0x10, 1).unwrap();
+    let text = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+    assert!(text.contains("\nalloc 0x2000 0x1 none\n"));
+    assert!(text.contains("\nalloc_below 0x10 0x10 0x1800 0x"));
+    let replayed = &mut Allocator::replay(text.as_bytes()).unwrap();
+    replayed.alloc(0x10, 1).unwrap();
+    assert_eq!(saved(replayed), saved(allocator));
+    //a log that does not match the allocator any more
+    let broken = text.replace("alloc 0x2000 0x1 none", "alloc 0x2000 0x1 0x1000");
+    assert!(Allocator::replay(broken.as_bytes())
+        .err()
+        .unwrap()
+        .contains("expect 0x1000 but get none!"));
+}
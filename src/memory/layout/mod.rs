@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod test;
+
+use crate::memory::region::BytesAccess;
+use std::convert::TryInto;
+use std::mem::size_of;
+
+//integers with explicit byte order, used by #[derive(Layout)]
+pub trait Endian: Copy {
+    fn from_le(data: &[u8]) -> Self;
+    fn from_be(data: &[u8]) -> Self;
+    fn to_le(self, data: &mut [u8]);
+    fn to_be(self, data: &mut [u8]);
+    fn to_bits(self) -> u64;
+    fn from_bits(bits: u64) -> Self;
+}
+
+macro_rules! endian_impl {
+    ($($t:ty),+) => {$(
+        impl Endian for $t {
+            fn from_le(data: &[u8]) -> $t {
+                <$t>::from_le_bytes(data[..size_of::<$t>()].try_into().unwrap())
+            }
+            fn from_be(data: &[u8]) -> $t {
+                <$t>::from_be_bytes(data[..size_of::<$t>()].try_into().unwrap())
+            }
+            fn to_le(self, data: &mut [u8]) {
+                data[..size_of::<$t>()].copy_from_slice(&self.to_le_bytes())
+            }
+            fn to_be(self, data: &mut [u8]) {
+                data[..size_of::<$t>()].copy_from_slice(&self.to_be_bytes())
+            }
+            fn to_bits(self) -> u64 {
+                self as u64
+            }
+            fn from_bits(bits: u64) -> $t {
+                bits as $t
+            }
+        }
+    )+};
+}
+
+endian_impl!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+//byte image independent of host layout, SIZE includes padding
+pub trait Layout: Sized {
+    const SIZE: usize;
+    fn decode(data: &[u8]) -> Self;
+    fn encode(&self, data: &mut [u8]);
+
+    fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        if data.len() < Self::SIZE {
+            Err(format!(
+                "expect at least {} bytes but get {}!",
+                Self::SIZE,
+                data.len()
+            ))
+        } else {
+            Ok(Self::decode(data))
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0; Self::SIZE];
+        self.encode(&mut data);
+        data
+    }
+}
+
+pub trait LayoutAccess: BytesAccess {
+    fn write<T: Layout>(&self, addr: &u64, data: &T) -> Result<usize, String> {
+        BytesAccess::write(self, addr, &data.to_bytes())
+    }
+
+    fn read<T: Layout>(&self, addr: &u64) -> Result<T, String> {
+        let mut data = vec![0; T::SIZE];
+        BytesAccess::read(self, addr, &mut data)?;
+        Ok(T::decode(&data))
+    }
+}
@@ -0,0 +1,105 @@
+use super::*;
+use crate::memory::prelude::*;
+use crate::memory::region::GHEAP;
+use std::ops::Deref;
+
+#[derive(Layout, Debug, PartialEq, Default)]
+struct Flags {
+    #[layout(bits = 1)]
+    valid: u8,
+    #[layout(bits = 3)]
+    kind: u8,
+    #[layout(bits = 4)]
+    prio: u8,
+}
+
+#[derive(Layout, Debug, PartialEq, Default)]
+#[layout(be)]
+struct Packet {
+    id: u16,
+    #[layout(le)]
+    len: u32,
+    flags: Flags,
+    #[layout(pad = 3)]
+    mac: [u8; 6],
+    #[layout(bits = 12)]
+    vlan: u16,
+    #[layout(bits = 4)]
+    pcp: u16,
+    #[layout(bits = 4, pad = 1)]
+    tc: u16,
+}
+
+#[derive(Layout, Debug, PartialEq)]
+struct Offsets {
+    #[layout(bits = 4)]
+    dx: i8,
+    #[layout(bits = 4)]
+    dy: i8,
+    #[layout(bits = 12)]
+    dz: i16,
+}
+
+#[test]
+fn layout_signed_bits() {
+    let offsets = Offsets {
+        dx: -1,
+        dy: 7,
+        dz: -0x800,
+    };
+    let bytes = offsets.to_bytes();
+    assert_eq!(bytes, vec![0x7f, 0, 0x08]);
+    assert_eq!(Offsets::from_bytes(&bytes).unwrap(), offsets);
+}
+
+#[test]
+fn layout_bytes() {
+    assert_eq!(Flags::SIZE, 1);
+    assert_eq!(Packet::SIZE, 2 + 4 + 1 + 3 + 6 + 2 + 1 + 2);
+    let packet = Packet {
+        id: 0x1234,
+        len: 0x5678,
+        flags: Flags {
+            valid: 1,
+            kind: 5,
+            prio: 0xa,
+        },
+        mac: [1, 2, 3, 4, 5, 6],
+        vlan: 0xabc,
+        pcp: 0xd,
+        tc: 0x1f,
+    };
+    let bytes = packet.to_bytes();
+    assert_eq!(
+        bytes,
+        vec![0x12, 0x34, 0x78, 0x56, 0, 0, 0xab, 0, 0, 0, 1, 2, 3, 4, 5, 6, 0xda, 0xbc, 0, 0, 0xf]
+    );
+    //out of width bits are dropped
+    let decoded = Packet::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.tc, 0xf);
+    assert_eq!(
+        Packet {
+            tc: packet.tc & 0xf,
+            ..packet
+        },
+        decoded
+    );
+    assert!(Packet::from_bytes(&bytes[1..]).is_err());
+}
+
+#[test]
+fn layout_access() {
+    let region = GHEAP.alloc(0x100, 1).unwrap();
+    let base = region.info.base;
+    let flags = Flags {
+        valid: 0,
+        kind: 7,
+        prio: 1,
+    };
+    assert_eq!(LayoutAccess::write(region.deref(), &base, &flags), Ok(1));
+    assert_eq!(U8Access::read(region.deref(), &base), 0x1e);
+    assert_eq!(
+        LayoutAccess::read::<Flags>(region.deref(), &base),
+        Ok(flags)
+    );
+}
@@ -1,4 +1,5 @@
 pub mod allocator;
+pub mod layout;
 pub mod region;
 
 pub mod prelude;
@@ -1,3 +1,4 @@
+pub use crate::memory::layout::{Endian, Layout, LayoutAccess};
 pub use crate::memory::region::{
     BytesAccess, IOAccess, SizedAccess, U16Access, U32Access, U64Access, U8Access,
 };
@@ -2,14 +2,23 @@
 mod test;
 
 use super::*;
-use crate::memory::allocator::{Allocator, LockedAllocator};
-use std::cell::RefCell;
+#[cfg(feature = "memprof")]
+use crate::memory::allocator::Stats;
+use crate::memory::allocator::{Allocator, Error, LockedAllocator, Policy, Tag};
+use crate::memory::layout::LayoutAccess;
+use crate::space::{AccessKind, Space};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs::{self, File, OpenOptions};
 use std::hash::{BuildHasherDefault, Hasher};
 use std::marker::Sized;
 use std::mem::size_of;
 use std::ops::Deref;
+use std::os::unix::fs::FileExt;
+use std::panic::Location;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 pub trait BytesAccess {
     fn write(&self, addr: &u64, data: &[u8]) -> Result<usize, String>;
@@ -140,6 +149,12 @@ impl U8Access for LazyModel {
     }
 }
 
+impl LazyModel {
+    fn wipe(&self) {
+        self.inner.borrow_mut().clear()
+    }
+}
+
 impl BytesAccess for LazyModel {
     fn write(&self, addr: &u64, data: &[u8]) -> Result<usize, String> {
         {
@@ -186,6 +201,10 @@ impl Model {
             inner: RefCell::new(vec![0; size as usize].into_boxed_slice()),
         }
     }
+
+    fn wipe(&self) {
+        self.inner.borrow_mut().iter_mut().for_each(|b| *b = 0)
+    }
 }
 
 impl U8Access for Model {
@@ -218,6 +237,176 @@ impl U32Access for Model {}
 
 impl U64Access for Model {}
 
+const SPARSE_PAGE_SIZE: u64 = 0x1000;
+
+//backed by pages allocated on first write
+struct SparseModel {
+    pages: RefCell<HashMap<u64, Box<[u8]>, BuildHasherDefault<ModelHasher>>>,
+}
+
+impl SparseModel {
+    fn new() -> SparseModel {
+        SparseModel {
+            pages: RefCell::new(HashMap::default()),
+        }
+    }
+
+    fn wipe(&self) {
+        self.pages.borrow_mut().clear()
+    }
+
+    //split [addr, addr + len) at page boundaries, f gets page, offset in page and offset in data
+    fn for_pages<F: FnMut(u64, usize, std::ops::Range<usize>)>(addr: u64, len: usize, mut f: F) {
+        let mut done = 0;
+        while done < len {
+            let cur = addr + done as u64;
+            let page = cur & !(SPARSE_PAGE_SIZE - 1);
+            let offset = (cur - page) as usize;
+            let n = (SPARSE_PAGE_SIZE as usize - offset).min(len - done);
+            f(page, offset, done..done + n);
+            done += n;
+        }
+    }
+}
+
+impl U8Access for SparseModel {
+    fn write(&self, addr: &u64, data: u8) {
+        BytesAccess::write(self, addr, &[data]).unwrap();
+    }
+
+    fn read(&self, addr: &u64) -> u8 {
+        let mut data = [0];
+        BytesAccess::read(self, addr, &mut data).unwrap();
+        data[0]
+    }
+}
+
+impl BytesAccess for SparseModel {
+    fn write(&self, addr: &u64, data: &[u8]) -> Result<usize, String> {
+        let mut pages = self.pages.borrow_mut();
+        Self::for_pages(*addr, data.len(), |page, offset, range| {
+            let page = pages
+                .entry(page)
+                .or_insert_with(|| vec![0; SPARSE_PAGE_SIZE as usize].into_boxed_slice());
+            page[offset..offset + range.len()].copy_from_slice(&data[range]);
+        });
+        Ok(data.len())
+    }
+
+    fn read(&self, addr: &u64, data: &mut [u8]) -> Result<usize, String> {
+        let pages = self.pages.borrow();
+        Self::for_pages(*addr, data.len(), |page, offset, range| {
+            let len = range.len();
+            if let Some(page) = pages.get(&page) {
+                data[range].copy_from_slice(&page[offset..offset + len]);
+            } else {
+                data[range].iter_mut().for_each(|d| *d = 0);
+            }
+        });
+        Ok(data.len())
+    }
+}
+
+impl U16Access for SparseModel {}
+
+impl U32Access for SparseModel {}
+
+impl U64Access for SparseModel {}
+
+static FILE_MODEL_ID: AtomicU64 = AtomicU64::new(0);
+
+//backed by an unlinked temporary file, holes read as zero
+struct FileModel {
+    info: MemInfo,
+    file: File,
+}
+
+impl FileModel {
+    fn new(info: MemInfo) -> Result<FileModel, String> {
+        let path = std::env::temp_dir().join(format!(
+            "ts_region_{}_{}",
+            std::process::id(),
+            FILE_MODEL_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| format!("create {:?} fail: {}", path, e))?;
+        fs::remove_file(&path).map_err(|e| format!("remove {:?} fail: {}", path, e))?;
+        file.set_len(info.size).map_err(|e| e.to_string())?;
+        Ok(FileModel { info, file })
+    }
+
+    fn wipe(&self) -> Result<(), String> {
+        self.file
+            .set_len(0)
+            .and_then(|_| self.file.set_len(self.info.size))
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl U8Access for FileModel {
+    fn write(&self, addr: &u64, data: u8) {
+        BytesAccess::write(self, addr, &[data]).unwrap();
+    }
+
+    fn read(&self, addr: &u64) -> u8 {
+        let mut data = [0];
+        BytesAccess::read(self, addr, &mut data).unwrap();
+        data[0]
+    }
+}
+
+impl BytesAccess for FileModel {
+    fn write(&self, addr: &u64, data: &[u8]) -> Result<usize, String> {
+        self.file
+            .write_all_at(data, *addr - self.info.base)
+            .map_err(|e| format!("file model write @{:#x} fail: {}", addr, e))?;
+        Ok(data.len())
+    }
+
+    fn read(&self, addr: &u64, data: &mut [u8]) -> Result<usize, String> {
+        self.file
+            .read_exact_at(data, *addr - self.info.base)
+            .map_err(|e| format!("file model read @{:#x} fail: {}", addr, e))?;
+        Ok(data.len())
+    }
+}
+
+impl U16Access for FileModel {}
+
+impl U32Access for FileModel {}
+
+impl U64Access for FileModel {}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+#[repr(u32)]
+pub enum Storage {
+    //share the memory of the heap, root blocks use Model
+    #[default]
+    Heap = 0,
+    Model = 1,
+    LazyModel = 2,
+    Sparse = 3,
+    File = 4,
+}
+
+impl TryFrom<u32> for Storage {
+    type Error = String;
+    fn try_from(v: u32) -> Result<Storage, String> {
+        match v {
+            0 => Ok(Storage::Heap),
+            1 => Ok(Storage::Model),
+            2 => Ok(Storage::LazyModel),
+            3 => Ok(Storage::Sparse),
+            4 => Ok(Storage::File),
+            _ => Err(format!("invalid storage {}!", v)),
+        }
+    }
+}
+
 struct Remap {
     region: Rc<Region>,
     info: MemInfo,
@@ -232,13 +421,93 @@ impl Remap {
     }
 }
 
+pub type BridgeHook = Box<dyn Fn(AccessKind, &u64, usize)>;
+
+struct Bridge {
+    space: Rc<Space>,
+    info: MemInfo,
+    hook: Option<BridgeHook>,
+}
+
+impl Bridge {
+    fn new(space: &Rc<Space>, info: MemInfo, hook: Option<BridgeHook>) -> Bridge {
+        Bridge {
+            space: Rc::clone(space),
+            info,
+            hook,
+        }
+    }
+
+    fn hook(&self, kind: AccessKind, addr: &u64, size: usize) {
+        if let Some(ref hook) = self.hook {
+            hook(kind, addr, size)
+        }
+    }
+}
+
+macro_rules! bridge_access {
+    ($t:ty, $size:expr, $write:ident, $read:ident) => {
+        fn write(&self, addr: &u64, data: $t) {
+            self.hook(AccessKind::Write, addr, $size);
+            self.space
+                .$write(addr, data)
+                .unwrap_or_else(|a| panic!("bridge: no region @{:#x}!", a))
+        }
+
+        fn read(&self, addr: &u64) -> $t {
+            self.hook(AccessKind::Read, addr, $size);
+            self.space
+                .$read(addr)
+                .unwrap_or_else(|a| panic!("bridge: no region @{:#x}!", a))
+        }
+    };
+}
+
+impl U8Access for Bridge {
+    bridge_access!(u8, 1, write_u8, read_u8);
+}
+
+impl U16Access for Bridge {
+    bridge_access!(u16, 2, write_u16, read_u16);
+}
+
+impl U32Access for Bridge {
+    bridge_access!(u32, 4, write_u32, read_u32);
+}
+
+impl U64Access for Bridge {
+    bridge_access!(u64, 8, write_u64, read_u64);
+}
+
+impl BytesAccess for Bridge {
+    fn write(&self, addr: &u64, data: &[u8]) -> Result<usize, String> {
+        self.hook(AccessKind::Write, addr, data.len());
+        self.space
+            .write_bytes(addr, data)
+            .map_err(|a| format!("bridge: no region @{:#x}!", a))
+    }
+
+    fn read(&self, addr: &u64, data: &mut [u8]) -> Result<usize, String> {
+        self.hook(AccessKind::Read, addr, data.len());
+        self.space
+            .read_bytes(addr, data)
+            .map_err(|a| format!("bridge: no region @{:#x}!", a))
+    }
+}
+
 enum Memory {
     Model(Model),
     LazyModel(LazyModel),
-    Block(Rc<Heap>, Rc<Region>),
-    RootBlock(Box<Region>),
+    Sparse(SparseModel),
+    File(FileModel),
+    //blocks carry the generation of the heap they were allocated from
+    Block(Rc<Heap>, Rc<Region>, u64),
+    RootBlock(Box<Region>, u64),
     Remap(Remap),
     IO(Box<dyn IOAccess>),
+    Bridge(Bridge),
+    //the block was handed back to the allocator by realloc, there is nothing left to free
+    Detached,
 }
 
 impl Memory {
@@ -246,8 +515,10 @@ impl Memory {
         match self {
             Memory::Model(_) => "Model".to_string(),
             Memory::LazyModel(_) => "LazyModel".to_string(),
-            Memory::Block(_, _) => "Block".to_string(),
-            Memory::RootBlock(_) => "Block".to_string(),
+            Memory::Sparse(_) => "Sparse".to_string(),
+            Memory::File(_) => "File".to_string(),
+            Memory::Block(_, _, _) => "Block".to_string(),
+            Memory::RootBlock(_, _) => "Block".to_string(),
             Memory::Remap(remap) => format!(
                 "Remap({}@{:#016x} -> {:#016x})",
                 remap.region.memory.get_type(),
@@ -255,6 +526,24 @@ impl Memory {
                 remap.info.base + remap.info.size
             ),
             Memory::IO(_) => "IO".to_string(),
+            Memory::Bridge(bridge) => format!(
+                "Bridge(Space@{:#016x} -> {:#016x})",
+                bridge.info.base,
+                bridge.info.base + bridge.info.size
+            ),
+            Memory::Detached => "Detached".to_string(),
+        }
+    }
+}
+
+impl Memory {
+    fn storage(&self) -> Option<Storage> {
+        match self {
+            Memory::Model(_) => Some(Storage::Model),
+            Memory::LazyModel(_) => Some(Storage::LazyModel),
+            Memory::Sparse(_) => Some(Storage::Sparse),
+            Memory::File(_) => Some(Storage::File),
+            _ => None,
         }
     }
 }
@@ -264,9 +553,19 @@ macro_rules! memory_access {
             Memory::IO(io) => $x::$f(io.deref(),$($p,)+),
             Memory::Model(model) => $x::$f(model,$($p,)+),
             Memory::LazyModel(model) => $x::$f(model,$($p,)+),
-            Memory::Block(_, region) =>  $x::$f(region.deref(),$($p,)+),
-            Memory::RootBlock(region) =>  $x::$f(region.deref(),$($p,)+),
+            Memory::Sparse(model) => $x::$f(model,$($p,)+),
+            Memory::File(model) => $x::$f(model,$($p,)+),
+            Memory::Block(heap, region, generation) => {
+                heap.check(*generation);
+                $x::$f(region.deref(),$($p,)+)
+            }
+            Memory::RootBlock(region, generation) => {
+                GHEAP.check(*generation);
+                $x::$f(region.deref(),$($p,)+)
+            }
             Memory::Remap(remap) => $x::$f(remap.region.deref(),$($p,)+),
+            Memory::Bridge(bridge) => $x::$f(bridge,$($p,)+),
+            Memory::Detached => panic!("access detached region!"),
         }
         }
 }
@@ -342,32 +641,51 @@ impl Region {
         })
     }
 
-    fn lazy_model(base: u64, size: u64) -> Region {
-        Region {
-            memory: Memory::LazyModel(LazyModel::new()),
-            info: MemInfo {
-                base: base,
-                size: size,
-            },
+    //Storage::Heap falls back to Model, there is no heap to share
+    fn with_storage(base: u64, size: u64, storage: Storage) -> std::result::Result<Region, String> {
+        let info = MemInfo { base, size };
+        let memory = match storage {
+            Storage::Heap | Storage::Model => Memory::Model(Model::new(info)),
+            Storage::LazyModel => Memory::LazyModel(LazyModel::new()),
+            Storage::Sparse => Memory::Sparse(SparseModel::new()),
+            Storage::File => Memory::File(FileModel::new(info)?),
+        };
+        Ok(Region { memory, info })
+    }
+
+    fn check_wipe(&self) -> std::result::Result<(), String> {
+        match &self.memory {
+            Memory::Model(_)
+            | Memory::LazyModel(_)
+            | Memory::Sparse(_)
+            | Memory::File(_)
+            | Memory::Block(..) => Ok(()),
+            Memory::RootBlock(region, _) => region.check_wipe(),
+            _ => Err(format!("{} can not be wiped!", self.get_type())),
         }
     }
 
-    fn model(base: u64, size: u64) -> Region {
-        Region {
-            memory: Memory::Model(Model::new(MemInfo {
-                base: base,
-                size: size,
-            })),
-            info: MemInfo {
-                base: base,
-                size: size,
-            },
+    fn wipe(&self) -> std::result::Result<(), String> {
+        match &self.memory {
+            Memory::Model(model) => model.wipe(),
+            Memory::LazyModel(model) => model.wipe(),
+            Memory::Sparse(model) => model.wipe(),
+            Memory::File(model) => model.wipe()?,
+            Memory::RootBlock(region, generation) => {
+                GHEAP.check(*generation);
+                region.wipe()?
+            }
+            Memory::Block(..) => {
+                BytesAccess::write(self, &self.info.base, &vec![0; self.info.size as usize])?;
+            }
+            _ => return Err(format!("{} can not be wiped!", self.get_type())),
         }
+        Ok(())
     }
 
     fn block(base: u64, size: u64, heap: &Rc<Heap>, memory: &Rc<Region>) -> Rc<Region> {
         Rc::new(Region {
-            memory: Memory::Block(Rc::clone(heap), Rc::clone(memory)),
+            memory: Memory::Block(Rc::clone(heap), Rc::clone(memory), heap.generation.get()),
             info: MemInfo {
                 base: base,
                 size: size,
@@ -375,9 +693,9 @@ impl Region {
         })
     }
 
-    fn root_block(base: u64, size: u64, memory: Region) -> Rc<Region> {
+    fn root_block(base: u64, size: u64, memory: Region, generation: u64) -> Rc<Region> {
         Rc::new(Region {
-            memory: Memory::RootBlock(Box::new(memory)),
+            memory: Memory::RootBlock(Box::new(memory), generation),
             info: MemInfo {
                 base: base,
                 size: size,
@@ -414,6 +732,63 @@ impl Region {
             },
         })
     }
+
+    //same memory at another base, without stacking remaps
+    pub fn rebase(base: u64, memory: &Rc<Region>) -> Rc<Region> {
+        if let Memory::Remap(remap) = &memory.memory {
+            Rc::new(Region {
+                memory: Memory::Remap(Remap::new(&remap.region, remap.info)),
+                info: MemInfo {
+                    base,
+                    size: memory.info.size,
+                },
+            })
+        } else {
+            Region::remap(base, memory)
+        }
+    }
+
+    pub fn bridge(base: u64, space: &Rc<Space>, target: u64, size: u64) -> Rc<Region> {
+        Rc::new(Region {
+            memory: Memory::Bridge(Bridge::new(space, MemInfo { base: target, size }, None)),
+            info: MemInfo { base, size },
+        })
+    }
+
+    pub fn bridge_with_hook<F: Fn(AccessKind, &u64, usize) + 'static>(
+        base: u64,
+        space: &Rc<Space>,
+        target: u64,
+        size: u64,
+        hook: F,
+    ) -> Rc<Region> {
+        Rc::new(Region {
+            memory: Memory::Bridge(Bridge::new(
+                space,
+                MemInfo { base: target, size },
+                Some(Box::new(hook)),
+            )),
+            info: MemInfo { base, size },
+        })
+    }
+
+    //IO devices and bridges answer themselves, they are never cached
+    pub fn is_io(&self) -> bool {
+        match &self.memory {
+            Memory::IO(_) | Memory::Bridge(_) => true,
+            Memory::Remap(remap) => remap.region.is_io(),
+            _ => false,
+        }
+    }
+
+    pub fn get_space(&self) -> Option<&Rc<Space>> {
+        if let Memory::Bridge(bridge) = &self.memory {
+            Some(&bridge.space)
+        } else {
+            None
+        }
+    }
+
     fn translate(&self, va: &u64, size: usize) -> Option<u64> {
         assert!(
             *va >= self.info.base
@@ -429,6 +804,7 @@ impl Region {
         );
         match &self.memory {
             Memory::Remap(remap) => Some(va - self.info.base + remap.info.base),
+            Memory::Bridge(bridge) => Some(va - self.info.base + bridge.info.base),
             _ => None,
         }
     }
@@ -526,18 +902,21 @@ impl U64Access for Region {
 
 impl SizedAccess for Region {}
 
+impl LayoutAccess for Region {}
+
 impl Drop for Region {
     fn drop(&mut self) {
-        if let Memory::Block(heap, _) = &self.memory {
-            heap.free(self.info.base)
-        } else if let Memory::RootBlock(_) = &self.memory {
-            GHEAP.free(self.info.base)
+        if let Memory::Block(heap, _, generation) = &self.memory {
+            heap.free(self.info.base, *generation)
+        } else if let Memory::RootBlock(_, generation) = &self.memory {
+            GHEAP.free(self.info.base, *generation)
         }
     }
 }
 
+//blocks of an older generation were dropped by teardown, they are not freed again
 trait Free {
-    fn free(&self, addr: u64);
+    fn free(&self, addr: u64, generation: u64);
 }
 
 #[cfg(test)]
@@ -545,6 +924,7 @@ trait Free {
 pub struct Heap {
     memory: Rc<Region>,
     pub allocator: RefCell<Allocator>,
+    generation: Cell<u64>,
 }
 
 #[cfg(not(test))]
@@ -552,82 +932,356 @@ pub struct Heap {
 pub struct Heap {
     memory: Rc<Region>,
     allocator: RefCell<Allocator>,
+    generation: Cell<u64>,
 }
 
 impl Heap {
     pub fn new(memory: &Rc<Region>) -> Rc<Heap> {
+        Self::with_policy(memory, Policy::default())
+    }
+
+    pub fn with_policy(memory: &Rc<Region>, policy: Policy) -> Rc<Heap> {
         Rc::new(Heap {
             memory: Rc::clone(memory),
-            allocator: RefCell::new(Allocator::new(memory.info.base, memory.info.size)),
+            allocator: RefCell::new(Allocator::with_policy(
+                memory.info.base,
+                memory.info.size,
+                policy,
+            )),
+            generation: Cell::new(0),
         })
     }
 
+    //bounded heap on its own lazily backed memory, reset it to wipe everything
+    pub fn scratch(size: u64) -> std::result::Result<Rc<Heap>, String> {
+        Ok(Heap::new(&GHEAP.lazy_alloc(size, 0x1000)?))
+    }
+
+    //all outstanding blocks become stale, accessing them panics and dropping them is a no-op
+    pub fn teardown(&self) {
+        self.allocator.borrow_mut().reset();
+        self.generation.set(self.generation.get() + 1);
+    }
+
+    //teardown and zero the memory, nothing is torn down if the memory can not be wiped
+    pub fn reset(&self) -> std::result::Result<(), String> {
+        self.check_reset()?;
+        self.teardown();
+        self.memory.wipe()
+    }
+
+    pub fn check_reset(&self) -> std::result::Result<(), String> {
+        self.memory.check_wipe()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    fn check(&self, generation: u64) {
+        if generation != self.generation.get() {
+            panic!(
+                "stale block of heap @{:#x}: allocated in generation {}, heap is in generation {}!",
+                self.memory.info.base,
+                generation,
+                self.generation.get()
+            )
+        }
+    }
+
     pub fn alloc(
         self: &Rc<Self>,
         size: u64,
         align: u64,
     ) -> std::result::Result<Rc<Region>, String> {
-        if let Some(info) = self.allocator.borrow_mut().alloc(size, align) {
+        self.alloc_with_storage(size, align, Storage::Heap)
+    }
+
+    //blocks not using Storage::Heap get their own memory, the heap only provides the address
+    pub fn alloc_with_storage(
+        self: &Rc<Self>,
+        size: u64,
+        align: u64,
+        storage: Storage,
+    ) -> std::result::Result<Rc<Region>, String> {
+        let info = self
+            .allocator
+            .borrow_mut()
+            .alloc(size, align)
+            .ok_or_else(|| "oom!".to_string())?;
+        if storage == Storage::Heap {
+            return Ok(Region::block(info.base, info.size, self, &self.memory));
+        }
+        match Region::with_storage(info.base, info.size, storage) {
+            Ok(memory) => Ok(Region::block(info.base, info.size, self, &Rc::new(memory))),
+            Err(e) => {
+                self.allocator.borrow_mut().free(info.base).unwrap();
+                Err(e)
+            }
+        }
+    }
+
+    #[track_caller]
+    pub fn alloc_tagged(
+        self: &Rc<Self>,
+        size: u64,
+        align: u64,
+        tag: &str,
+    ) -> std::result::Result<Rc<Region>, String> {
+        let tag = Tag::with_location(tag, &Location::caller().to_string());
+        if let Some(info) = self.allocator.borrow_mut().alloc_tagged(size, align, tag) {
             Ok(Region::block(info.base, info.size, self, &self.memory))
         } else {
             Err("oom!".to_string())
         }
     }
+
+    pub fn alloc_at(
+        self: &Rc<Self>,
+        base: u64,
+        size: u64,
+    ) -> std::result::Result<Rc<Region>, String> {
+        let info = self
+            .allocator
+            .borrow_mut()
+            .alloc_at(base, size)
+            .map_err(|e| e.to_string())?;
+        Ok(Region::block(info.base, info.size, self, &self.memory))
+    }
+
+    pub fn reserve(&self, base: u64, size: u64) -> std::result::Result<(), String> {
+        self.allocator
+            .borrow_mut()
+            .reserve(base, size)
+            .map_err(|e| e.to_string())
+    }
+
+    //region must be a block of this heap and the only reference to it, contents are kept when moved
+    pub fn realloc(
+        self: &Rc<Self>,
+        region: &mut Rc<Region>,
+        size: u64,
+        align: u64,
+    ) -> std::result::Result<(), Error> {
+        let old = region.info;
+        //stale blocks were dropped by teardown, they are not allocated any more
+        let storage = match &region.memory {
+            Memory::Block(heap, memory, generation)
+                if Rc::ptr_eq(heap, self) && *generation == self.generation.get() =>
+            {
+                if Rc::ptr_eq(memory, &self.memory) {
+                    Storage::Heap
+                } else {
+                    memory.memory.storage().unwrap()
+                }
+            }
+            _ => return Err(Error::NotAllocated(old.base)),
+        };
+        if Rc::get_mut(region).is_none() {
+            return Err(Error::Shared(old.base));
+        }
+        //own memory can not be resized, move to a new block and let the old one drop
+        if storage != Storage::Heap {
+            let block = self
+                .alloc_with_storage(size, align, storage)
+                .map_err(|_| Error::OutOfMemory(size))?;
+            let mut data = vec![0; old.size.min(size) as usize];
+            BytesAccess::read(&**region, &old.base, &mut data).unwrap();
+            BytesAccess::write(block.deref(), &block.info.base, &data).unwrap();
+            let tag = self.allocator.borrow().get_tag(old.base).cloned();
+            if let Some(tag) = tag {
+                self.set_tag(block.info.base, tag);
+            }
+            *region = block;
+            return Ok(());
+        }
+        let block = Rc::get_mut(region).unwrap();
+        let info = self.allocator.borrow_mut().realloc(old.base, size, align)?;
+        //old block has been resized or freed by allocator
+        block.memory = Memory::Detached;
+        if info.base != old.base {
+            let mut data = vec![0; old.size.min(size) as usize];
+            BytesAccess::read(self.memory.deref(), &old.base, &mut data).unwrap();
+            BytesAccess::write(self.memory.deref(), &info.base, &data).unwrap();
+        }
+        *region = Region::block(info.base, info.size, self, &self.memory);
+        Ok(())
+    }
+
+    pub fn set_tag(&self, addr: u64, tag: Tag) -> bool {
+        self.allocator.borrow_mut().set_tag(addr, tag)
+    }
+
+    pub fn leaks(&self) -> Vec<(MemInfo, Option<Tag>)> {
+        self.allocator.borrow().leaks()
+    }
+
+    pub fn leak_report(&self) -> String {
+        self.allocator.borrow().leak_report()
+    }
+
     pub fn get_region(&self) -> &Rc<Region> {
         &self.memory
     }
+
+    #[cfg(feature = "memprof")]
+    pub fn stats(&self) -> Stats {
+        self.allocator.borrow().stats()
+    }
+
+    #[cfg(feature = "memprof")]
+    pub fn report(&self) -> String {
+        self.allocator.borrow().report()
+    }
 }
 
 impl Free for Heap {
-    fn free(&self, addr: u64) {
-        self.allocator.borrow_mut().free(addr)
+    fn free(&self, addr: u64, generation: u64) {
+        if generation == self.generation.get() {
+            self.allocator.borrow_mut().free(addr).unwrap()
+        }
     }
 }
 
 lazy_static! {
     pub static ref GHEAP: GlobalHeap = GlobalHeap {
-        allocator: LockedAllocator::new(0, 0x8000_0000_0000_0000)
+        allocator: LockedAllocator::new(0, 0x8000_0000_0000_0000),
+        generation: AtomicU64::new(0),
     };
 }
 
 #[cfg(test)]
 pub struct GlobalHeap {
     pub allocator: LockedAllocator,
+    generation: AtomicU64,
 }
 
 #[cfg(not(test))]
 pub struct GlobalHeap {
     allocator: LockedAllocator,
+    generation: AtomicU64,
 }
 
 impl GlobalHeap {
+    //generation is read under the allocator lock so a concurrent reset is never missed
+    fn alloc_root<F: FnOnce(&mut Allocator) -> std::result::Result<MemInfo, String>>(
+        &self,
+        storage: Storage,
+        f: F,
+    ) -> std::result::Result<Rc<Region>, String> {
+        let mut allocator = self.allocator.lock().unwrap();
+        let info = f(&mut allocator)?;
+        let memory = match Region::with_storage(info.base, info.size, storage) {
+            Ok(memory) => memory,
+            Err(e) => {
+                allocator.free(info.base).unwrap();
+                return Err(e);
+            }
+        };
+        Ok(Region::root_block(
+            info.base,
+            info.size,
+            memory,
+            self.generation.load(Ordering::SeqCst),
+        ))
+    }
+
     pub fn lazy_alloc(&self, size: u64, align: u64) -> std::result::Result<Rc<Region>, String> {
-        if let Some(info) = self.allocator.alloc(size, align) {
-            Ok(Region::root_block(
-                info.base,
-                info.size,
-                Region::lazy_model(info.base, info.size),
-            ))
-        } else {
-            Err("oom!".to_string())
-        }
+        self.alloc_with_storage(size, align, Storage::LazyModel)
     }
 
     pub fn alloc(&self, size: u64, align: u64) -> std::result::Result<Rc<Region>, String> {
-        if let Some(info) = self.allocator.alloc(size, align) {
-            Ok(Region::root_block(
-                info.base,
-                info.size,
-                Region::model(info.base, info.size),
-            ))
-        } else {
-            Err("oom!".to_string())
+        self.alloc_with_storage(size, align, Storage::Model)
+    }
+
+    pub fn alloc_with_storage(
+        &self,
+        size: u64,
+        align: u64,
+        storage: Storage,
+    ) -> std::result::Result<Rc<Region>, String> {
+        self.alloc_root(storage, |allocator| {
+            allocator
+                .alloc(size, align)
+                .ok_or_else(|| "oom!".to_string())
+        })
+    }
+
+    pub fn alloc_at(&self, base: u64, size: u64) -> std::result::Result<Rc<Region>, String> {
+        self.alloc_root(Storage::Model, |allocator| {
+            allocator.alloc_at(base, size).map_err(|e| e.to_string())
+        })
+    }
+
+    pub fn reserve(&self, base: u64, size: u64) -> std::result::Result<(), String> {
+        self.allocator
+            .reserve(base, size)
+            .map_err(|e| e.to_string())
+    }
+
+    #[track_caller]
+    pub fn alloc_tagged(
+        &self,
+        size: u64,
+        align: u64,
+        tag: &str,
+    ) -> std::result::Result<Rc<Region>, String> {
+        let tag = Tag::with_location(tag, &Location::caller().to_string());
+        self.alloc_root(Storage::Model, |allocator| {
+            allocator
+                .alloc_tagged(size, align, tag)
+                .ok_or_else(|| "oom!".to_string())
+        })
+    }
+
+    //for long regressions, all root blocks allocated so far become stale, heaps on them included
+    pub fn reset(&self) {
+        self.allocator.reset();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    fn check(&self, generation: u64) {
+        let current = self.generation.load(Ordering::SeqCst);
+        if generation != current {
+            panic!(
+                "stale root block: allocated in generation {}, GHEAP is in generation {}!",
+                generation, current
+            )
         }
     }
+
+    pub fn set_tag(&self, addr: u64, tag: Tag) -> bool {
+        self.allocator.set_tag(addr, tag)
+    }
+
+    pub fn leaks(&self) -> Vec<(MemInfo, Option<Tag>)> {
+        self.allocator.leaks()
+    }
+
+    pub fn leak_report(&self) -> String {
+        self.allocator.leak_report()
+    }
+
+    #[cfg(feature = "memprof")]
+    pub fn stats(&self) -> Stats {
+        self.allocator.stats()
+    }
+
+    #[cfg(feature = "memprof")]
+    pub fn report(&self) -> String {
+        self.allocator.report()
+    }
 }
 
 impl Free for GlobalHeap {
-    fn free(&self, addr: u64) {
-        self.allocator.free(addr)
+    fn free(&self, addr: u64, generation: u64) {
+        let mut allocator = self.allocator.lock().unwrap();
+        if generation == self.generation.load(Ordering::SeqCst) {
+            allocator.free(addr).unwrap()
+        }
     }
 }
@@ -10,7 +10,7 @@ fn region_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .collect::<Vec<MemInfo>>()
     );
     let region = heap.alloc(9, 1).unwrap();
@@ -25,7 +25,7 @@ fn region_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .collect::<Vec<MemInfo>>()
     );
     assert_ne!(
@@ -34,7 +34,7 @@ fn region_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -45,7 +45,7 @@ fn region_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -56,7 +56,7 @@ fn region_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -68,7 +68,7 @@ fn region_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -80,7 +80,7 @@ fn region_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -91,7 +91,7 @@ fn region_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -102,7 +102,7 @@ fn region_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -137,3 +137,186 @@ fn region_access() {
         );
     }
 }
+
+#[test]
+fn heap_leak() {
+    let memory = GHEAP.alloc(0x100, 1).unwrap();
+    let heap = Heap::new(&memory);
+    let line = line!() + 1;
+    let leaked = heap.alloc_tagged(0x10, 1, "leaked").unwrap();
+    {
+        let _freed = heap.alloc_tagged(0x10, 1, "freed").unwrap();
+    }
+    let leaks = heap.leaks();
+    assert_eq!(leaks.len(), 1);
+    assert_eq!(leaks[0].0, leaked.info);
+    let tag = leaks[0].1.as_ref().unwrap();
+    assert_eq!(tag.name, "leaked");
+    assert!(tag
+        .location
+        .as_ref()
+        .unwrap()
+        .starts_with(&format!("{}:{}:", file!(), line)));
+    std::mem::drop(leaked);
+    assert!(heap.leaks().is_empty());
+}
+
+#[test]
+fn heap_realloc() {
+    let memory = GHEAP.alloc(0x100, 1).unwrap();
+    let heap = Heap::new(&memory);
+    let base = memory.info.base;
+    let mut ring = heap.alloc(0x8, 8).unwrap();
+    U64Access::write(ring.deref(), &ring.info.base, 0xdead_beef_a5a5_5a5a);
+    heap.realloc(&mut ring, 0x10, 8).unwrap();
+    assert_eq!(ring.info, MemInfo { base, size: 0x10 });
+    let _blocker = heap.alloc(0x8, 8).unwrap();
+    heap.realloc(&mut ring, 0x20, 8).unwrap();
+    assert_eq!(
+        ring.info,
+        MemInfo {
+            base: base + 0x18,
+            size: 0x20
+        }
+    );
+    assert_eq!(
+        U64Access::read(ring.deref(), &ring.info.base),
+        0xdead_beef_a5a5_5a5a
+    );
+    let shared = Rc::clone(&ring);
+    assert_eq!(
+        heap.realloc(&mut ring, 0x8, 8),
+        Err(Error::Shared(ring.info.base))
+    );
+    std::mem::drop(shared);
+    let other = Heap::new(&GHEAP.alloc(0x100, 1).unwrap());
+    assert_eq!(
+        other.realloc(&mut ring, 0x8, 8),
+        Err(Error::NotAllocated(ring.info.base))
+    );
+    std::mem::drop(ring);
+    assert_eq!(heap.leaks().len(), 1);
+}
+
+#[test]
+fn heap_fixed() {
+    let heap = Heap::new(&GHEAP.alloc(0x100, 1).unwrap());
+    let base = heap.get_region().info.base;
+    heap.reserve(base + 0x80, 0x80).unwrap();
+    let fw = heap.alloc_at(base + 0x10, 0x10).unwrap();
+    assert_eq!(
+        fw.info,
+        MemInfo {
+            base: base + 0x10,
+            size: 0x10
+        }
+    );
+    assert!(heap.alloc_at(base + 0x78, 0x10).is_err());
+    assert!(heap.alloc(0x80, 1).is_err());
+    std::mem::drop(fw);
+    assert!(heap.alloc(0x80, 1).is_ok());
+}
+
+#[test]
+fn heap_teardown() {
+    let heap = Heap::scratch(0x100).unwrap();
+    let base = heap.get_region().info.base;
+    let stale = heap.alloc(0x10, 1).unwrap();
+    U64Access::write(stale.deref(), &base, 0x5a5a);
+    heap.teardown();
+    assert_eq!(heap.generation(), 1);
+    assert!(heap.leaks().is_empty());
+    let fresh = heap.alloc(0x10, 1).unwrap();
+    assert_eq!(fresh.info, stale.info);
+    //dropping the stale block must not free the fresh one
+    std::mem::drop(stale);
+    assert_eq!(heap.leaks().len(), 1);
+    assert_eq!(U64Access::read(fresh.deref(), &base), 0x5a5a);
+    let mut moved = heap.alloc(0x10, 1).unwrap();
+    heap.reset().unwrap();
+    assert_eq!(
+        heap.realloc(&mut moved, 0x20, 1),
+        Err(Error::NotAllocated(moved.info.base))
+    );
+    let fresh = heap.alloc(0x10, 1).unwrap();
+    assert_eq!(U64Access::read(fresh.deref(), &base), 0);
+    //remapped memory can not be wiped, the heap must stay untouched
+    let remap = Heap::new(&Region::remap(0x8000_0000, heap.get_region()));
+    let live = remap.alloc(0x10, 1).unwrap();
+    assert!(remap.reset().is_err());
+    assert_eq!(remap.generation(), 0);
+    U64Access::write(live.deref(), &live.info.base, 0x5a5a);
+}
+
+#[test]
+fn heap_reset_keeps_reserved() {
+    let heap = Heap::scratch(0x100).unwrap();
+    let base = heap.get_region().info.base;
+    heap.reserve(base + 0x40, 0x80).unwrap();
+    heap.reset().unwrap();
+    assert!(heap.alloc_at(base + 0x40, 0x10).is_err());
+    assert!(heap.alloc(0x80, 1).is_err());
+    let low = heap.alloc(0x40, 1).unwrap();
+    let high = heap.alloc(0x40, 1).unwrap();
+    assert_eq!(low.info.base, base);
+    assert_eq!(high.info.base, base + 0xc0);
+}
+
+#[test]
+#[should_panic(expected = "allocated in generation 0, heap is in generation 1!")]
+fn heap_stale_access() {
+    let heap = Heap::scratch(0x100).unwrap();
+    let stale = heap.alloc(0x10, 1).unwrap();
+    heap.teardown();
+    U8Access::read(stale.deref(), &stale.info.base);
+}
+
+#[test]
+fn heap_storage() {
+    let heap = Heap::scratch(0x40000).unwrap();
+    let storages = [
+        Storage::Heap,
+        Storage::Model,
+        Storage::LazyModel,
+        Storage::Sparse,
+        Storage::File,
+    ];
+    let mut blocks = storages
+        .iter()
+        .map(|&s| heap.alloc_with_storage(0x2000, 0x1000, s).unwrap())
+        .collect::<Vec<_>>();
+    for (i, block) in blocks.iter().enumerate() {
+        let addr = block.info.base + 0x1ff8;
+        assert_eq!(U64Access::read(block.deref(), &addr), 0);
+        U64Access::write(block.deref(), &addr, i as u64 + 1);
+    }
+    //only Storage::Heap blocks are backed by the heap memory
+    let last = blocks[0].info.base + 0x1ff8;
+    assert_eq!(U64Access::read(heap.get_region().deref(), &last), 1);
+    let last = blocks[4].info.base + 0x1ff8;
+    assert_eq!(U64Access::read(heap.get_region().deref(), &last), 0);
+    for (i, block) in blocks.iter_mut().enumerate() {
+        let old = block.info.base;
+        heap.realloc(block, 0x4000, 0x1000).unwrap();
+        assert_eq!(
+            U64Access::read(&**block, &(block.info.base + 0x1ff8)),
+            i as u64 + 1
+        );
+        if i > 0 {
+            assert_ne!(block.info.base, old);
+            if let Memory::Block(_, memory, _) = &block.memory {
+                assert_eq!(memory.memory.storage(), Some(storages[i]));
+            }
+        }
+    }
+    assert_eq!(heap.leaks().len(), storages.len());
+    std::mem::drop(blocks);
+    assert!(heap.leaks().is_empty());
+    let file = GHEAP
+        .alloc_with_storage(0x1000, 0x1000, Storage::File)
+        .unwrap();
+    U32Access::write(file.deref(), &file.info.base, 0xdead_beef);
+    assert_eq!(U32Access::read(file.deref(), &file.info.base), 0xdead_beef);
+    assert_eq!(Storage::try_from(4), Ok(Storage::File));
+    assert!(Storage::try_from(5).is_err());
+}
@@ -0,0 +1,143 @@
+use crate::deferred::Latency;
+use crate::space::AccessKind;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+
+//an access costs latency + size / bandwidth cycles
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Timing {
+    pub latency: u64,
+    //bytes per cycle
+    pub bandwidth: u64,
+}
+
+impl Timing {
+    pub const DRAM: Timing = Timing {
+        latency: 100,
+        bandwidth: 8,
+    };
+    pub const SRAM: Timing = Timing {
+        latency: 1,
+        bandwidth: 16,
+    };
+    pub const MMIO: Timing = Timing {
+        latency: 20,
+        bandwidth: 4,
+    };
+
+    pub fn new(latency: u64, bandwidth: u64) -> Timing {
+        assert!(bandwidth > 0, "bandwidth must be > 0!");
+        Timing { latency, bandwidth }
+    }
+
+    pub fn cost(&self, size: usize) -> u64 {
+        self.latency + (size as u64).div_ceil(self.bandwidth)
+    }
+}
+
+impl Latency for Timing {
+    fn latency(&self, _: AccessKind, _: &u64, size: usize) -> Option<u64> {
+        Some(self.cost(size))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub reads: u64,
+    pub writes: u64,
+    pub bytes: u64,
+    pub cycles: u64,
+}
+
+impl Stats {
+    pub fn accesses(&self) -> u64 {
+        self.reads + self.writes
+    }
+}
+
+//regions are modeled by name, regions without timing use the default one
+pub struct PerfModel {
+    default: Timing,
+    timings: RefCell<HashMap<String, Timing>>,
+    stats: RefCell<BTreeMap<String, Stats>>,
+    enable: Cell<bool>,
+}
+
+impl PerfModel {
+    pub fn new(default: Timing) -> PerfModel {
+        PerfModel {
+            default,
+            timings: RefCell::new(HashMap::new()),
+            stats: RefCell::new(BTreeMap::new()),
+            enable: Cell::new(true),
+        }
+    }
+
+    pub fn set_timing(&self, name: &str, timing: Timing) {
+        self.timings.borrow_mut().insert(name.to_string(), timing);
+    }
+
+    pub fn timing(&self, name: &str) -> Timing {
+        self.timings
+            .borrow()
+            .get(name)
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    pub fn enable(&self, enable: bool) {
+        self.enable.set(enable)
+    }
+
+    pub(crate) fn account(&self, name: &str, kind: AccessKind, size: usize) {
+        if !self.enable.get() {
+            return;
+        }
+        let cost = self.timing(name).cost(size);
+        let mut stats = self.stats.borrow_mut();
+        let s = stats.entry(name.to_string()).or_default();
+        match kind {
+            AccessKind::Read => s.reads += 1,
+            AccessKind::Write => s.writes += 1,
+        }
+        s.bytes += size as u64;
+        s.cycles += cost;
+    }
+
+    pub fn stats(&self, name: &str) -> Stats {
+        self.stats.borrow().get(name).copied().unwrap_or_default()
+    }
+
+    pub fn cycles(&self) -> u64 {
+        self.stats.borrow().values().map(|s| s.cycles).sum()
+    }
+
+    pub fn reset(&self) {
+        self.stats.borrow_mut().clear()
+    }
+
+    //one line per accessed region in name order, then the total
+    pub fn report(&self) -> String {
+        let stats = self.stats.borrow();
+        let mut total = Stats::default();
+        let mut s = format!(
+            "{:<16}{:>12}{:>12}{:>16}{:>16}\n",
+            "region", "reads", "writes", "bytes", "cycles"
+        );
+        for (name, r) in stats.iter() {
+            s.push_str(&format!(
+                "{:<16}{:>12}{:>12}{:>16}{:>16}\n",
+                name, r.reads, r.writes, r.bytes, r.cycles
+            ));
+            total.reads += r.reads;
+            total.writes += r.writes;
+            total.bytes += r.bytes;
+            total.cycles += r.cycles;
+        }
+        s.push_str(&format!(
+            "{:<16}{:>12}{:>12}{:>16}{:>16}\n",
+            "total", total.reads, total.writes, total.bytes, total.cycles
+        ));
+        s
+    }
+}
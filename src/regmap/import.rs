@@ -0,0 +1,324 @@
+use super::*;
+use crate::space::Space;
+use roxmltree::{Document, Node};
+use tinyjson::JsonValue;
+
+//verilog style literals are accepted: 0x10, 'h10, 32'h10, 'd16, 'b10000
+fn parse_num(s: &str) -> Result<u64, String> {
+    let s = s.trim().replace('_', "");
+    let v = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16)
+    } else if let Some(pos) = s.find('\'') {
+        let (radix, digits) = s[pos + 1..].split_at(1);
+        let radix = match radix {
+            "h" | "H" => 16,
+            "d" | "D" => 10,
+            "o" | "O" => 8,
+            "b" | "B" => 2,
+            _ => return Err(format!("invalid number {}!", s)),
+        };
+        u64::from_str_radix(digits, radix)
+    } else {
+        s.parse::<u64>()
+    };
+    v.map_err(|e| format!("invalid number {}:{}!", s, e))
+}
+
+fn mask_of(lsb: u32, width: u32) -> u64 {
+    mask(width) << lsb
+}
+
+fn xml_child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.tag_name().name() == name)
+}
+
+fn xml_children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |n| n.tag_name().name() == name)
+}
+
+fn xml_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    xml_child(node, name)
+        .and_then(|n| n.text())
+        .map(|s| s.trim())
+}
+
+fn xml_num(node: Node, name: &str) -> Result<u64, String> {
+    xml_text(node, name)
+        .ok_or_else(|| {
+            format!(
+                "{} {} has no {}!",
+                node.tag_name().name(),
+                xml_text(node, "name").unwrap_or("?"),
+                name
+            )
+        })
+        .and_then(parse_num)
+}
+
+//<reset><value> in 1685-2009, <resets><reset><value> for 1685-2014 fields
+fn xml_reset(node: Node) -> Result<Option<u64>, String> {
+    let reset = xml_child(node, "reset")
+        .or_else(|| xml_child(node, "resets").and_then(|n| xml_child(n, "reset")));
+    match reset.and_then(|n| xml_text(n, "value")) {
+        Some(v) => parse_num(v).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn xml_access(node: Node, default: Access) -> Result<Access, String> {
+    let modified = xml_text(node, "modifiedWriteValue");
+    let read_action = xml_text(node, "readAction");
+    Ok(match (modified, read_action) {
+        (Some("oneToClear"), _) => Access::W1C,
+        (Some("oneToSet"), _) => Access::W1S,
+        (Some(m), _) => return Err(format!("unsupported modifiedWriteValue {}!", m)),
+        (None, Some("clear")) => Access::RC,
+        (None, Some(a)) => return Err(format!("unsupported readAction {}!", a)),
+        (None, None) => match xml_text(node, "access") {
+            Some("read-write") | Some("read-writeOnce") => Access::RW,
+            Some("read-only") => Access::RO,
+            Some("write-only") | Some("writeOnce") => Access::WO,
+            Some(a) => return Err(format!("unsupported access {}!", a)),
+            None => default,
+        },
+    })
+}
+
+fn xml_register(node: Node, prefix: &str, base: u64, access: Access) -> Result<Register, String> {
+    let name = format!("{}{}", prefix, xml_text(node, "name").unwrap_or(""));
+    let access = xml_access(node, access)?;
+    let mut reset = xml_reset(node)?.unwrap_or(0);
+    let mut reg = Register::new(
+        &name,
+        base + xml_num(node, "addressOffset")?,
+        xml_num(node, "size")? as u32,
+    )
+    .access(access)
+    .desc(xml_text(node, "description").unwrap_or(""));
+    for f in xml_children(node, "field") {
+        let lsb = xml_num(f, "bitOffset")? as u32;
+        let width = xml_num(f, "bitWidth")? as u32;
+        if let Some(v) = xml_reset(f)? {
+            reset = (reset & !mask_of(lsb, width)) | ((v << lsb) & mask_of(lsb, width));
+        }
+        reg = reg.field(
+            Field::new(
+                xml_text(f, "name").unwrap_or(""),
+                lsb,
+                width,
+                xml_access(f, access)?,
+            )
+            .desc(xml_text(f, "description").unwrap_or("")),
+        );
+    }
+    Ok(reg.reset(reset))
+}
+
+//register files are flattened, register names are prefixed with "<file>."
+fn xml_registers(
+    node: Node,
+    prefix: &str,
+    base: u64,
+    access: Access,
+    map: &mut RegMap,
+) -> Result<(), String> {
+    for n in node.children().filter(|n| n.is_element()) {
+        match n.tag_name().name() {
+            "register" => map.add(xml_register(n, prefix, base, access)?)?,
+            "registerFile" => {
+                let prefix = format!("{}{}.", prefix, xml_text(n, "name").unwrap_or(""));
+                let base = base + xml_num(n, "addressOffset")?;
+                xml_registers(n, &prefix, base, xml_access(n, access)?, map)?
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+//every addressBlock of every memoryMap becomes a register map at (baseAddress, range)
+pub fn ipxact(xml: &str) -> Result<Vec<(u64, RegMap)>, String> {
+    let doc = Document::parse(xml).map_err(|e| e.to_string())?;
+    let mut blocks = vec![];
+    for block in doc
+        .descendants()
+        .filter(|n| n.tag_name().name() == "addressBlock")
+    {
+        let mut map = RegMap::new(
+            xml_text(block, "name").unwrap_or(""),
+            xml_num(block, "range")?,
+        );
+        let access = xml_access(block, Access::RW)?;
+        xml_registers(block, "", 0, access, &mut map)?;
+        blocks.push((xml_num(block, "baseAddress")?, map));
+    }
+    Ok(blocks)
+}
+
+//json tree dumped from the SystemRDL compiler, one object per component:
+//  {"type": "addrmap"|"regfile"|"reg"|"field", "inst_name", "addr_offset", "children"}
+//  addrmap: "size" optional, the end of the last register is used if missing
+//  reg: "regwidth" default 32, "desc"
+//  field: "lsb", "msb", "reset", "sw" (rw|r|w), "onread" (rclr), "onwrite" (woclr|woset), "desc"
+//numbers are json numbers or strings in parse_num format
+fn json_get<'a>(v: &'a JsonValue, key: &str) -> Option<&'a JsonValue> {
+    match v {
+        JsonValue::Object(o) => o.get(key),
+        _ => None,
+    }
+}
+
+fn json_num(v: &JsonValue, key: &str) -> Result<Option<u64>, String> {
+    match json_get(v, key) {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(JsonValue::Number(n)) if *n >= 0.0 && n.fract() == 0.0 => Ok(Some(*n as u64)),
+        Some(JsonValue::String(s)) => parse_num(s).map(Some),
+        _ => Err(format!(
+            "{} of {} is not a number!",
+            key,
+            json_str(v, "inst_name")
+        )),
+    }
+}
+
+fn json_expect(v: &JsonValue, key: &str) -> Result<u64, String> {
+    json_num(v, key)?.ok_or_else(|| format!("{} has no {}!", json_str(v, "inst_name"), key))
+}
+
+fn json_str<'a>(v: &'a JsonValue, key: &str) -> &'a str {
+    match json_get(v, key) {
+        Some(JsonValue::String(s)) => s,
+        _ => "",
+    }
+}
+
+fn json_children(v: &JsonValue) -> &[JsonValue] {
+    match json_get(v, "children") {
+        Some(JsonValue::Array(a)) => a,
+        _ => &[],
+    }
+}
+
+fn json_access(v: &JsonValue) -> Result<Access, String> {
+    Ok(match (json_str(v, "onwrite"), json_str(v, "onread")) {
+        ("woclr", _) => Access::W1C,
+        ("woset", _) => Access::W1S,
+        ("", "rclr") => Access::RC,
+        ("", "") => match json_str(v, "sw") {
+            "rw" | "" => Access::RW,
+            "r" => Access::RO,
+            "w" => Access::WO,
+            a => return Err(format!("unsupported sw {}!", a)),
+        },
+        (w, r) => return Err(format!("unsupported onwrite {} onread {}!", w, r)),
+    })
+}
+
+fn json_register(v: &JsonValue, prefix: &str, base: u64) -> Result<Register, String> {
+    let width = json_num(v, "regwidth")?.unwrap_or(32) as u32;
+    let mut reg = Register::new(
+        &format!("{}{}", prefix, json_str(v, "inst_name")),
+        base + json_expect(v, "addr_offset")?,
+        width,
+    )
+    .desc(json_str(v, "desc"));
+    let mut reset = 0;
+    for f in json_children(v).iter() {
+        let lsb = json_expect(f, "lsb")? as u32;
+        let msb = json_expect(f, "msb")? as u32;
+        if msb < lsb {
+            return Err(format!("field {} msb < lsb!", json_str(f, "inst_name")));
+        }
+        let width = msb - lsb + 1;
+        if let Some(v) = json_num(f, "reset")? {
+            reset |= (v << lsb) & mask_of(lsb, width);
+        }
+        reg = reg.field(
+            Field::new(json_str(f, "inst_name"), lsb, width, json_access(f)?)
+                .desc(json_str(f, "desc")),
+        );
+    }
+    Ok(reg.reset(reset))
+}
+
+fn json_registers(
+    v: &JsonValue,
+    prefix: &str,
+    base: u64,
+    regs: &mut Vec<Register>,
+) -> Result<(), String> {
+    for c in json_children(v).iter() {
+        match json_str(c, "type") {
+            "reg" => regs.push(json_register(c, prefix, base)?),
+            "regfile" => {
+                let prefix = format!("{}{}.", prefix, json_str(c, "inst_name"));
+                json_registers(c, &prefix, base + json_expect(c, "addr_offset")?, regs)?
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+//an addrmap holding registers becomes a register map, nested addrmaps are named "<parent>.<child>"
+fn json_addrmap(
+    v: &JsonValue,
+    prefix: &str,
+    base: u64,
+    blocks: &mut Vec<(u64, RegMap)>,
+) -> Result<(), String> {
+    let name = format!("{}{}", prefix, json_str(v, "inst_name"));
+    let base = base + json_num(v, "addr_offset")?.unwrap_or(0);
+    let mut regs = vec![];
+    json_registers(v, "", 0, &mut regs)?;
+    if !regs.is_empty() {
+        let end = regs.iter().map(|r| r.offset + r.size()).max().unwrap();
+        let mut map = RegMap::new(&name, json_num(v, "size")?.unwrap_or(end));
+        for r in regs {
+            map.add(r)?
+        }
+        blocks.push((base, map));
+    }
+    for c in json_children(v)
+        .iter()
+        .filter(|c| json_str(c, "type") == "addrmap")
+    {
+        json_addrmap(c, &format!("{}.", name), base, blocks)?
+    }
+    Ok(())
+}
+
+pub fn systemrdl(s: &str) -> Result<Vec<(u64, RegMap)>, String> {
+    let root = s.parse::<JsonValue>().map_err(|e| e.to_string())?;
+    if json_str(&root, "type") != "addrmap" {
+        return Err("top component must be an addrmap!".to_string());
+    }
+    let mut blocks = vec![];
+    json_addrmap(&root, "", 0, &mut blocks)?;
+    Ok(blocks)
+}
+
+//regions are named after the register maps
+//all or nothing, blocks added before a failing one are deleted again
+pub fn add_to_space(
+    space: &mut Space,
+    blocks: Vec<(u64, RegMap)>,
+) -> Result<Vec<Rc<Region>>, String> {
+    let mut added: Vec<(String, Rc<Region>)> = vec![];
+    for (base, map) in blocks {
+        let name = map.name.clone();
+        match space.add_region(&name, &map.into_region(base)) {
+            Ok(region) => added.push((name, region)),
+            Err(e) => {
+                for (name, _) in added.iter() {
+                    space.delete_region(name)
+                }
+                return Err(e.to_string());
+            }
+        }
+    }
+    Ok(added.into_iter().map(|(_, region)| region).collect())
+}
@@ -0,0 +1,485 @@
+#[cfg(test)]
+mod test;
+
+pub mod import;
+
+use crate::memory::prelude::*;
+use crate::memory::region::Region;
+use std::cell::Cell;
+use std::fmt::Write;
+use std::mem::size_of;
+use std::rc::Rc;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Access {
+    RW,
+    RO,
+    WO,
+    //write 1 to clear
+    W1C,
+    //write 1 to set
+    W1S,
+    //clear on read
+    RC,
+}
+
+impl Access {
+    fn read(&self, value: u64) -> u64 {
+        match self {
+            Access::WO => 0,
+            _ => value,
+        }
+    }
+
+    fn after_read(&self, value: u64, mask: u64) -> u64 {
+        match self {
+            Access::RC => value & !mask,
+            _ => value,
+        }
+    }
+
+    fn write(&self, old: u64, data: u64, mask: u64) -> u64 {
+        let new = match self {
+            Access::RW | Access::WO => data,
+            Access::RO | Access::RC => old,
+            Access::W1C => old & !data,
+            Access::W1S => old | data,
+        };
+        (old & !mask) | (new & mask)
+    }
+}
+
+fn mask(width: u32) -> u64 {
+    if width == 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+pub struct Field {
+    pub name: String,
+    pub lsb: u32,
+    pub width: u32,
+    pub access: Access,
+    pub desc: String,
+}
+
+impl Field {
+    pub fn new(name: &str, lsb: u32, width: u32, access: Access) -> Field {
+        Field {
+            name: name.to_string(),
+            lsb,
+            width,
+            access,
+            desc: String::new(),
+        }
+    }
+
+    pub fn desc(mut self, desc: &str) -> Field {
+        self.desc = desc.to_string();
+        self
+    }
+
+    pub fn mask(&self) -> u64 {
+        mask(self.width) << self.lsb
+    }
+
+    fn bits(&self) -> String {
+        if self.width == 1 {
+            format!("{}", self.lsb)
+        } else {
+            format!("{}:{}", self.lsb + self.width - 1, self.lsb)
+        }
+    }
+}
+
+//read callback returns the current value, it replaces the stored one before access policy applies
+type ReadFn = Box<dyn Fn(&RegMap, u64) -> u64>;
+//write callback sees the stored value after access policy applied
+type WriteFn = Box<dyn Fn(&RegMap, u64)>;
+
+pub struct Register {
+    pub name: String,
+    pub offset: u64,
+    pub width: u32,
+    pub reset: u64,
+    pub access: Access,
+    pub desc: String,
+    fields: Vec<Field>,
+    value: Cell<u64>,
+    on_read: Option<ReadFn>,
+    on_write: Option<WriteFn>,
+}
+
+impl Register {
+    //width in bits, RW and reset to 0 by default
+    pub fn new(name: &str, offset: u64, width: u32) -> Register {
+        Register {
+            name: name.to_string(),
+            offset,
+            width,
+            reset: 0,
+            access: Access::RW,
+            desc: String::new(),
+            fields: vec![],
+            value: Cell::new(0),
+            on_read: None,
+            on_write: None,
+        }
+    }
+
+    pub fn reset(mut self, reset: u64) -> Register {
+        self.reset = reset;
+        self
+    }
+
+    //only used when there is no field
+    pub fn access(mut self, access: Access) -> Register {
+        self.access = access;
+        self
+    }
+
+    pub fn desc(mut self, desc: &str) -> Register {
+        self.desc = desc.to_string();
+        self
+    }
+
+    //bits not in any field are reserved, read as 0 and ignore writes
+    pub fn field(mut self, field: Field) -> Register {
+        self.fields.push(field);
+        self
+    }
+
+    pub fn on_read<F: Fn(&RegMap, u64) -> u64 + 'static>(mut self, f: F) -> Register {
+        self.on_read = Some(Box::new(f));
+        self
+    }
+
+    pub fn on_write<F: Fn(&RegMap, u64) + 'static>(mut self, f: F) -> Register {
+        self.on_write = Some(Box::new(f));
+        self
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn field_of(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value.get()
+    }
+
+    pub fn size(&self) -> u64 {
+        (self.width / 8) as u64
+    }
+
+    fn parts(&self) -> Vec<(u64, Access)> {
+        if self.fields.is_empty() {
+            vec![(mask(self.width), self.access)]
+        } else {
+            self.fields.iter().map(|f| (f.mask(), f.access)).collect()
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if ![8, 16, 32, 64].contains(&self.width) {
+            return Err(format!(
+                "register {}: width {} is not one of 8, 16, 32, 64!",
+                self.name, self.width
+            ));
+        }
+        if !self.offset.is_multiple_of(self.size()) {
+            return Err(format!(
+                "register {}: offset {:#x} is not aligned to width!",
+                self.name, self.offset
+            ));
+        }
+        if self.reset & !mask(self.width) != 0 {
+            return Err(format!(
+                "register {}: reset {:#x} out of width!",
+                self.name, self.reset
+            ));
+        }
+        let mut used = 0;
+        for f in self.fields.iter() {
+            if f.width == 0 || f.lsb + f.width > self.width {
+                return Err(format!(
+                    "register {}: field {} [{}] out of width!",
+                    self.name,
+                    f.name,
+                    f.bits()
+                ));
+            }
+            if used & f.mask() != 0 {
+                return Err(format!(
+                    "register {}: field {} [{}] overlaps!",
+                    self.name,
+                    f.name,
+                    f.bits()
+                ));
+            }
+            used |= f.mask();
+        }
+        Ok(())
+    }
+
+    fn sw_read(&self, map: &RegMap, mask: u64) -> u64 {
+        let value = if let Some(ref f) = self.on_read {
+            f(map, self.value.get())
+        } else {
+            self.value.get()
+        };
+        let parts = self.parts();
+        let data = parts
+            .iter()
+            .fold(0, |acc, (m, a)| acc | (a.read(value) & m));
+        self.value.set(
+            parts
+                .iter()
+                .fold(value, |v, (m, a)| a.after_read(v, m & mask)),
+        );
+        data
+    }
+
+    fn sw_write(&self, map: &RegMap, data: u64, mask: u64) {
+        let value = self
+            .parts()
+            .iter()
+            .fold(self.value.get(), |v, (m, a)| a.write(v, data, m & mask));
+        self.value.set(value);
+        if let Some(ref f) = self.on_write {
+            f(map, value)
+        }
+    }
+}
+
+//offsets are relative to the block, see into_region
+#[derive_io(Bytes, U8, U16, U32, U64)]
+pub struct RegMap {
+    pub name: String,
+    pub size: u64,
+    regs: Vec<Register>,
+}
+
+impl RegMap {
+    pub fn new(name: &str, size: u64) -> RegMap {
+        RegMap {
+            name: name.to_string(),
+            size,
+            regs: vec![],
+        }
+    }
+
+    pub fn add(&mut self, reg: Register) -> Result<(), String> {
+        reg.check()?;
+        if reg.offset + reg.size() > self.size {
+            return Err(format!(
+                "register {} @{:#x} out of {}!",
+                reg.name, reg.offset, self.name
+            ));
+        }
+        if let Some(r) = self.regs.iter().find(|r| {
+            r.name == reg.name
+                || (r.offset < reg.offset + reg.size() && reg.offset < r.offset + r.size())
+        }) {
+            return Err(format!(
+                "register {} @{:#x} conflicts with {} @{:#x}!",
+                reg.name, reg.offset, r.name, r.offset
+            ));
+        }
+        reg.value.set(reg.reset);
+        let idx = self
+            .regs
+            .iter()
+            .position(|r| r.offset > reg.offset)
+            .unwrap_or(self.regs.len());
+        self.regs.insert(idx, reg);
+        Ok(())
+    }
+
+    pub fn registers(&self) -> impl Iterator<Item = &Register> {
+        self.regs.iter()
+    }
+
+    pub fn register(&self, name: &str) -> Option<&Register> {
+        self.regs.iter().find(|r| r.name == name)
+    }
+
+    fn expect(&self, name: &str) -> &Register {
+        self.register(name)
+            .unwrap_or_else(|| panic!("{} has no register {}!", self.name, name))
+    }
+
+    //device side accesses bypass access policy and callbacks
+    pub fn get(&self, name: &str) -> u64 {
+        self.expect(name).value.get()
+    }
+
+    pub fn set(&self, name: &str, value: u64) {
+        let reg = self.expect(name);
+        reg.value.set(value & mask(reg.width))
+    }
+
+    pub fn get_field(&self, name: &str, field: &str) -> u64 {
+        let reg = self.expect(name);
+        let f = reg
+            .field_of(field)
+            .unwrap_or_else(|| panic!("register {} has no field {}!", name, field));
+        (reg.value.get() & f.mask()) >> f.lsb
+    }
+
+    pub fn set_field(&self, name: &str, field: &str, value: u64) {
+        let reg = self.expect(name);
+        let f = reg
+            .field_of(field)
+            .unwrap_or_else(|| panic!("register {} has no field {}!", name, field));
+        reg.value
+            .set((reg.value.get() & !f.mask()) | ((value << f.lsb) & f.mask()))
+    }
+
+    pub fn reset(&self) {
+        for r in self.regs.iter() {
+            r.value.set(r.reset)
+        }
+    }
+
+    //markdown tables of registers and fields
+    pub fn doc(&self) -> String {
+        let mut s = String::new();
+        writeln!(s, "# {}", self.name).unwrap();
+        writeln!(s).unwrap();
+        writeln!(
+            s,
+            "| offset | name | width | access | reset | description |"
+        )
+        .unwrap();
+        writeln!(s, "|---|---|---|---|---|---|").unwrap();
+        for r in self.regs.iter() {
+            let access = if r.fields.is_empty() {
+                format!("{:?}", r.access)
+            } else {
+                "-".to_string()
+            };
+            writeln!(
+                s,
+                "| {:#x} | {} | {} | {} | {:#x} | {} |",
+                r.offset, r.name, r.width, access, r.reset, r.desc
+            )
+            .unwrap();
+        }
+        for r in self.regs.iter().filter(|r| !r.fields.is_empty()) {
+            writeln!(s).unwrap();
+            writeln!(s, "## {}", r.name).unwrap();
+            writeln!(s).unwrap();
+            writeln!(s, "| bits | name | access | reset | description |").unwrap();
+            writeln!(s, "|---|---|---|---|---|").unwrap();
+            for f in r.fields.iter() {
+                writeln!(
+                    s,
+                    "| {} | {} | {:?} | {:#x} | {} |",
+                    f.bits(),
+                    f.name,
+                    f.access,
+                    (r.reset & f.mask()) >> f.lsb,
+                    f.desc
+                )
+                .unwrap();
+            }
+        }
+        s
+    }
+
+    //(register, mask of the accessed bytes, shift of data in the register), holes are skipped
+    fn hits(&self, addr: u64, len: usize) -> Vec<(&Register, u64, i64)> {
+        let end = addr + len as u64;
+        self.regs
+            .iter()
+            .filter(|r| r.offset < end && addr < r.offset + r.size())
+            .map(|r| {
+                let lo = addr.max(r.offset) - r.offset;
+                let hi = end.min(r.offset + r.size()) - r.offset;
+                let mask = mask(((hi - lo) * 8) as u32) << (lo * 8);
+                (r, mask, r.offset as i64 - addr as i64)
+            })
+            .collect()
+    }
+
+    fn decode(&self, addr: u64, len: usize) -> Result<Vec<(&Register, u64, i64)>, String> {
+        let hits = self.hits(addr, len);
+        let covered: u64 = hits.iter().map(|(_, m, _)| m.count_ones() as u64 / 8).sum();
+        if covered != len as u64 {
+            return Err(format!(
+                "{}: access {:#x}-{:#x} hits undefined register!",
+                self.name,
+                addr,
+                addr + len as u64 - 1
+            ));
+        }
+        Ok(hits)
+    }
+
+    fn write_hits(&self, hits: Vec<(&Register, u64, i64)>, data: &[u8]) {
+        for (reg, mask, shift) in hits {
+            let mut bytes = [0; 8];
+            let lo = shift.max(0) as usize;
+            let skip = (-shift).max(0) as usize;
+            let n = (reg.size() as usize - skip).min(data.len() - lo);
+            bytes[skip..skip + n].copy_from_slice(&data[lo..lo + n]);
+            reg.sw_write(self, u64::from_le_bytes(bytes), mask);
+        }
+    }
+
+    fn read_hits(&self, hits: Vec<(&Register, u64, i64)>, data: &mut [u8]) {
+        for (reg, mask, shift) in hits {
+            let bytes = (reg.sw_read(self, mask) & mask).to_le_bytes();
+            let lo = shift.max(0) as usize;
+            let skip = (-shift).max(0) as usize;
+            let n = (reg.size() as usize - skip).min(data.len() - lo);
+            data[lo..lo + n].copy_from_slice(&bytes[skip..skip + n]);
+        }
+    }
+
+    pub fn into_region(self, base: u64) -> Rc<Region> {
+        let size = self.size;
+        Region::remap(base, &Region::io(0, size, Box::new(self)))
+    }
+}
+
+//bytes access fails on undefined registers
+impl BytesAccess for RegMap {
+    fn write(&self, addr: &u64, data: &[u8]) -> Result<usize, String> {
+        self.write_hits(self.decode(*addr, data.len())?, data);
+        Ok(data.len())
+    }
+
+    fn read(&self, addr: &u64, data: &mut [u8]) -> Result<usize, String> {
+        self.read_hits(self.decode(*addr, data.len())?, data);
+        Ok(data.len())
+    }
+}
+
+//like bus accesses, reserved bytes read as 0 and writes to them are ignored
+macro_rules! regmap_access {
+    ($x:ident, $t:ty) => {
+        impl $x for RegMap {
+            fn write(&self, addr: &u64, data: $t) {
+                self.write_hits(self.hits(*addr, size_of::<$t>()), &data.to_le_bytes())
+            }
+
+            fn read(&self, addr: &u64) -> $t {
+                let mut bytes = [0; size_of::<$t>()];
+                self.read_hits(self.hits(*addr, size_of::<$t>()), &mut bytes);
+                <$t>::from_le_bytes(bytes)
+            }
+        }
+    };
+}
+
+regmap_access!(U8Access, u8);
+regmap_access!(U16Access, u16);
+regmap_access!(U32Access, u32);
+regmap_access!(U64Access, u64);
@@ -0,0 +1,331 @@
+use super::*;
+use crate::space::Space;
+use std::cell::RefCell;
+use std::ops::Deref;
+
+fn uart() -> RegMap {
+    let mut map = RegMap::new("uart", 0x20);
+    map.add(
+        Register::new("ctrl", 0x0, 32)
+            .reset(0x1)
+            .desc("control")
+            .field(Field::new("en", 0, 1, Access::RW).desc("enable"))
+            .field(Field::new("mode", 4, 4, Access::RW))
+            .field(Field::new("start", 8, 1, Access::W1S))
+            .on_write(|map, value| {
+                if value & 0x100 != 0 {
+                    map.set_field("status", "done", 1);
+                    map.set_field("ctrl", "start", 0);
+                }
+            }),
+    )
+    .unwrap();
+    map.add(
+        Register::new("status", 0x4, 32)
+            .field(Field::new("done", 0, 1, Access::W1C))
+            .field(Field::new("err", 1, 1, Access::RC))
+            .field(Field::new("busy", 2, 1, Access::RO)),
+    )
+    .unwrap();
+    map.add(Register::new("data", 0x8, 8).access(Access::WO))
+        .unwrap();
+    map.add(
+        Register::new("id", 0xc, 16)
+            .reset(0xbeef)
+            .access(Access::RO),
+    )
+    .unwrap();
+    map.add(Register::new("counter", 0x10, 64)).unwrap();
+    map
+}
+
+#[test]
+fn regmap_access() {
+    let map = uart();
+    assert_eq!(U32Access::read(&map, &0), 0x1);
+    U32Access::write(&map, &0, 0xffff_ff30);
+    assert_eq!(U32Access::read(&map, &0), 0x30);
+    assert_eq!(map.get_field("ctrl", "mode"), 3);
+    //reserved bits are dropped, byte write keeps the other bytes
+    U8Access::write(&map, &0, 0x51);
+    assert_eq!(map.get("ctrl"), 0x51);
+    U8Access::write(&map, &1, 0x1);
+    assert_eq!(map.get_field("status", "done"), 1);
+    assert_eq!(map.get_field("ctrl", "start"), 0);
+
+    map.set("status", 0x7);
+    U32Access::write(&map, &4, 0x6);
+    assert_eq!(U32Access::read(&map, &4), 0x7);
+    assert_eq!(U32Access::read(&map, &4), 0x5);
+    U32Access::write(&map, &4, 0x1);
+    assert_eq!(map.get("status"), 0x4);
+
+    U8Access::write(&map, &8, 0xa5);
+    assert_eq!(map.get("data"), 0xa5);
+    assert_eq!(U8Access::read(&map, &8), 0);
+    U16Access::write(&map, &0xc, 0);
+    assert_eq!(U16Access::read(&map, &0xc), 0xbeef);
+
+    //one access across registers
+    let mut data = [0; 8];
+    assert!(BytesAccess::read(&map, &0x8, &mut data).is_err());
+    assert!(BytesAccess::read(&map, &0x0, &mut data).is_ok());
+    assert_eq!(data, [0x51, 0, 0, 0, 0x4, 0, 0, 0]);
+    U64Access::write(&map, &0x10, 0x0123_4567_89ab_cdef);
+    assert_eq!(U32Access::read(&map, &0x14), 0x0123_4567);
+
+    //reserved bytes read as 0 and ignore writes
+    assert_eq!(U8Access::read(&map, &0x9), 0);
+    U8Access::write(&map, &0x1a, 0xff);
+    assert_eq!(U64Access::read(&map, &0x18), 0);
+    map.set("data", 0x5a);
+    U32Access::write(&map, &0x8, 0xffff_ff3c);
+    assert_eq!(map.get("data"), 0x3c);
+    assert_eq!(U32Access::read(&map, &0xc), 0xbeef);
+
+    map.reset();
+    assert_eq!(map.get("ctrl"), 0x1);
+    assert_eq!(map.get("counter"), 0);
+}
+
+#[test]
+fn regmap_region() {
+    let reads = Rc::new(RefCell::new(0));
+    let counter = reads.clone();
+    let mut map = uart();
+    map.add(Register::new("live", 0x18, 32).on_read(move |_, _| {
+        *counter.borrow_mut() += 1;
+        *counter.borrow()
+    }))
+    .unwrap();
+    let region = map.into_region(0x1000_0000);
+    assert_eq!(U32Access::read(region.deref(), &0x1000_0018), 1);
+    assert_eq!(U32Access::read(region.deref(), &0x1000_0018), 2);
+    assert_eq!(*reads.borrow(), 2);
+    assert_eq!(U16Access::read(region.deref(), &0x1000_000c), 0xbeef);
+}
+
+#[test]
+fn regmap_check() {
+    let mut map = uart();
+    assert!(map.add(Register::new("dup", 0x2, 16)).is_err());
+    assert!(map.add(Register::new("ctrl", 0x18, 32)).is_err());
+    assert!(map.add(Register::new("odd", 0x19, 16)).is_err());
+    assert!(map.add(Register::new("wide", 0x18, 24)).is_err());
+    assert!(map.add(Register::new("out", 0x20, 32)).is_err());
+    assert!(map
+        .add(
+            Register::new("fields", 0x18, 8)
+                .field(Field::new("a", 0, 4, Access::RW))
+                .field(Field::new("b", 3, 2, Access::RW))
+        )
+        .is_err());
+    assert!(map
+        .add(Register::new("fields", 0x18, 8).field(Field::new("a", 4, 5, Access::RW)))
+        .is_err());
+    assert!(map
+        .add(Register::new("reset", 0x18, 8).reset(0x100))
+        .is_err());
+}
+
+#[test]
+fn regmap_doc() {
+    let doc = uart().doc();
+    assert!(doc.starts_with("# uart\n"));
+    assert!(doc.contains("| 0x0 | ctrl | 32 | - | 0x1 | control |"));
+    assert!(doc.contains("| 0xc | id | 16 | RO | 0xbeef |  |"));
+    assert!(doc.contains("## status"));
+    assert!(doc.contains("| 7:4 | mode | RW | 0x0 |  |"));
+    assert!(doc.contains("| 0 | en | RW | 0x1 | enable |"));
+}
+
+const IPXACT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2014">
+  <ipxact:name>timer</ipxact:name>
+  <ipxact:memoryMaps>
+    <ipxact:memoryMap>
+      <ipxact:name>regs</ipxact:name>
+      <ipxact:addressBlock>
+        <ipxact:name>timer0</ipxact:name>
+        <ipxact:baseAddress>'h4000_0000</ipxact:baseAddress>
+        <ipxact:range>0x100</ipxact:range>
+        <ipxact:width>32</ipxact:width>
+        <ipxact:register>
+          <ipxact:name>ctrl</ipxact:name>
+          <ipxact:description>control</ipxact:description>
+          <ipxact:addressOffset>0x0</ipxact:addressOffset>
+          <ipxact:size>32</ipxact:size>
+          <ipxact:field>
+            <ipxact:name>en</ipxact:name>
+            <ipxact:bitOffset>0</ipxact:bitOffset>
+            <ipxact:resets><ipxact:reset><ipxact:value>1</ipxact:value></ipxact:reset></ipxact:resets>
+            <ipxact:bitWidth>1</ipxact:bitWidth>
+            <ipxact:access>read-write</ipxact:access>
+          </ipxact:field>
+          <ipxact:field>
+            <ipxact:name>irq</ipxact:name>
+            <ipxact:bitOffset>8</ipxact:bitOffset>
+            <ipxact:bitWidth>1</ipxact:bitWidth>
+            <ipxact:access>read-write</ipxact:access>
+            <ipxact:modifiedWriteValue>oneToClear</ipxact:modifiedWriteValue>
+          </ipxact:field>
+        </ipxact:register>
+        <ipxact:registerFile>
+          <ipxact:name>cnt</ipxact:name>
+          <ipxact:addressOffset>0x10</ipxact:addressOffset>
+          <ipxact:range>0x10</ipxact:range>
+          <ipxact:register>
+            <ipxact:name>value</ipxact:name>
+            <ipxact:addressOffset>0x8</ipxact:addressOffset>
+            <ipxact:size>64</ipxact:size>
+            <ipxact:access>read-only</ipxact:access>
+            <ipxact:reset><ipxact:value>0x1234</ipxact:value></ipxact:reset>
+          </ipxact:register>
+        </ipxact:registerFile>
+      </ipxact:addressBlock>
+    </ipxact:memoryMap>
+  </ipxact:memoryMaps>
+</ipxact:component>
+"#;
+
+const SYSTEMRDL: &str = r#"{
+  "type": "addrmap", "inst_name": "soc", "addr_offset": 0,
+  "children": [
+    {"type": "addrmap", "inst_name": "uart", "addr_offset": "0x1000", "size": "0x20",
+     "children": [
+       {"type": "reg", "inst_name": "status", "addr_offset": 4, "regwidth": 16, "desc": "status",
+        "children": [
+          {"type": "field", "inst_name": "rx", "lsb": 0, "msb": 0, "reset": 1, "sw": "r", "onread": "rclr"},
+          {"type": "field", "inst_name": "err", "lsb": 4, "msb": 7, "reset": "'h3", "onwrite": "woclr"}
+        ]},
+       {"type": "regfile", "inst_name": "fifo", "addr_offset": 8,
+        "children": [
+          {"type": "reg", "inst_name": "data", "addr_offset": 0,
+           "children": [{"type": "field", "inst_name": "d", "lsb": 0, "msb": 7, "sw": "w"}]}
+        ]}
+     ]}
+  ]
+}"#;
+
+#[test]
+fn regmap_import() {
+    let blocks = import::ipxact(IPXACT).unwrap();
+    assert_eq!(blocks.len(), 1);
+    let (base, map) = &blocks[0];
+    assert_eq!(*base, 0x4000_0000);
+    assert_eq!(map.name, "timer0");
+    assert_eq!(map.size, 0x100);
+    assert_eq!(map.get("ctrl"), 1);
+    assert_eq!(map.register("ctrl").unwrap().desc, "control");
+    assert_eq!(
+        map.register("ctrl").unwrap().fields()[1].access,
+        Access::W1C
+    );
+    assert_eq!(map.register("cnt.value").unwrap().offset, 0x18);
+    assert_eq!(map.get("cnt.value"), 0x1234);
+
+    let blocks = import::systemrdl(SYSTEMRDL).unwrap();
+    assert_eq!(blocks.len(), 1);
+    let (base, map) = &blocks[0];
+    assert_eq!(*base, 0x1000);
+    assert_eq!(map.name, "soc.uart");
+    assert_eq!(map.get("status"), 0x31);
+    assert_eq!(U16Access::read(map, &4), 0x31);
+    assert_eq!(map.get("status"), 0x30);
+    U16Access::write(map, &4, 0x10);
+    assert_eq!(map.get("status"), 0x20);
+    assert_eq!(map.register("fifo.data").unwrap().offset, 8);
+
+    let mut space = Space::new();
+    let mut blocks = import::ipxact(IPXACT).unwrap();
+    blocks.append(&mut import::systemrdl(SYSTEMRDL).unwrap());
+    import::add_to_space(&mut space, blocks).unwrap();
+    assert_eq!(space.read_u32(&0x4000_0000), Ok(1));
+    space.write_u32(&0x4000_0000, 0x100).unwrap();
+    assert_eq!(space.read_u32(&0x4000_0000), Ok(0));
+    assert_eq!(space.read_u16(&0x1004), Ok(0x31));
+    assert!(space.get_region("soc.uart").is_some());
+
+    //the uart block overlaps, the blocks added before it are rolled back
+    let mut space = Space::new();
+    space
+        .add_region("ram", &RegMap::new("ram", 0x10).into_region(0x1000))
+        .unwrap();
+    let mut blocks = import::ipxact(IPXACT).unwrap();
+    blocks.append(&mut import::systemrdl(SYSTEMRDL).unwrap());
+    assert!(import::add_to_space(&mut space, blocks).is_err());
+    assert!(space.read_u32(&0x4000_0000).is_err());
+    assert!(space.get_region("ram").is_some());
+
+    assert!(import::systemrdl("{\"type\": \"reg\"}").is_err());
+    assert!(import::ipxact(
+        "<addressBlock><name>a</name><baseAddress>0</baseAddress></addressBlock>"
+    )
+    .is_err());
+    assert!(import::ipxact("<component></block>").is_err());
+}
+
+struct Timer {
+    ctrl: Cell<u32>,
+    count: Cell<u64>,
+}
+
+#[derive_io]
+impl Timer {
+    #[reg(offset = 0x0, width = 32)]
+    fn ctrl_read(&self) -> u32 {
+        self.ctrl.get()
+    }
+
+    #[reg(offset = 0x0, width = 32)]
+    fn ctrl_write(&self, v: u32) {
+        self.ctrl.set(v & 0x3)
+    }
+
+    #[reg(offset = 0x4, width = 8)]
+    fn status(&self) -> u8 {
+        (self.count.get() != 0) as u8
+    }
+
+    #[reg(offset = 0x8, width = 64)]
+    #[reg(offset = 0x10, width = 64)]
+    fn count(&self) -> u64 {
+        self.count.get()
+    }
+
+    #[reg(offset = 0x8, width = 64)]
+    fn set_count(&self, v: u64) {
+        self.count.set(v)
+    }
+}
+
+#[test]
+fn regmap_derive_io() {
+    let timer = Timer {
+        ctrl: Cell::new(0),
+        count: Cell::new(0),
+    };
+    U32Access::write(&timer, &0, 0xff);
+    assert_eq!(U32Access::read(&timer, &0), 0x3);
+    assert_eq!(U8Access::read(&timer, &4), 0);
+    U64Access::write(&timer, &8, 0x1_0000_0000);
+    assert_eq!(U64Access::read(&timer, &0x10), 0x1_0000_0000);
+    assert_eq!(U8Access::read(&timer, &4), 1);
+    let mut data = [0; 4];
+    assert_eq!(BytesAccess::read(&timer, &0, &mut data), Ok(4));
+    assert_eq!(data, [3, 0, 0, 0]);
+    assert!(BytesAccess::write(&timer, &0, &[1, 0]).is_err());
+    assert!(BytesAccess::write(&timer, &0x10, &[0; 8]).is_err());
+    let region = Region::io(0, 0x18, Box::new(timer));
+    assert_eq!(U64Access::read(region.deref(), &8), 0x1_0000_0000);
+}
+
+#[test]
+#[should_panic(expected = "U16Access::write for Timer @0x2 not mapped!")]
+fn regmap_derive_io_unmapped() {
+    let timer = Timer {
+        ctrl: Cell::new(0),
+        count: Cell::new(0),
+    };
+    U16Access::write(&timer, &2, 0);
+}
@@ -0,0 +1,150 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+pub type EventId = u64;
+
+type Clock = Rc<dyn Fn(u64)>;
+
+enum Task {
+    Once(Box<dyn FnOnce(&Scheduler)>),
+    //rescheduled every period while it returns true
+    Every(u64, Box<dyn FnMut(&Scheduler) -> bool>),
+}
+
+pub struct Scheduler {
+    now: Cell<u64>,
+    next: Cell<EventId>,
+    //same due events fire in schedule order
+    events: RefCell<BTreeMap<(u64, EventId), Task>>,
+    //models with their own time, advanced by the elapsed cycles before each event fires
+    clocks: RefCell<BTreeMap<EventId, Clock>>,
+    running: Cell<Option<EventId>>,
+    cancelled: Cell<bool>,
+}
+
+impl Scheduler {
+    pub fn new() -> Scheduler {
+        Scheduler {
+            now: Cell::new(0),
+            next: Cell::new(1),
+            events: RefCell::new(BTreeMap::new()),
+            clocks: RefCell::new(BTreeMap::new()),
+            running: Cell::new(None),
+            cancelled: Cell::new(false),
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.now.get()
+    }
+
+    fn id(&self) -> EventId {
+        let id = self.next.get();
+        self.next.set(id + 1);
+        id
+    }
+
+    fn push(&self, due: u64, task: Task) -> EventId {
+        let id = self.id();
+        self.events.borrow_mut().insert((due, id), task);
+        id
+    }
+
+    pub fn schedule<F: FnOnce(&Scheduler) + 'static>(&self, delay: u64, f: F) -> EventId {
+        self.push(self.now() + delay, Task::Once(Box::new(f)))
+    }
+
+    //first fires one period from now
+    pub fn every<F: FnMut(&Scheduler) -> bool + 'static>(&self, period: u64, f: F) -> EventId {
+        assert!(period > 0, "period must be > 0!");
+        self.push(self.now() + period, Task::Every(period, Box::new(f)))
+    }
+
+    //f is called with the cycles elapsed whenever time moves, cancel detaches it
+    pub fn clock<F: Fn(u64) + 'static>(&self, f: F) -> EventId {
+        let id = self.id();
+        self.clocks.borrow_mut().insert(id, Rc::new(f));
+        id
+    }
+
+    pub fn cancel(&self, id: EventId) -> bool {
+        if self.clocks.borrow_mut().remove(&id).is_some() {
+            return true;
+        }
+        if self.running.get() == Some(id) {
+            self.cancelled.set(true);
+            return true;
+        }
+        let mut events = self.events.borrow_mut();
+        if let Some(key) = events.keys().find(|(_, i)| *i == id).cloned() {
+            events.remove(&key);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn pending(&self) -> usize {
+        self.events.borrow().len()
+    }
+
+    pub fn next_due(&self) -> Option<u64> {
+        self.events.borrow().keys().next().map(|(due, _)| *due)
+    }
+
+    fn pop(&self, until: u64) -> Option<((u64, EventId), Task)> {
+        let mut events = self.events.borrow_mut();
+        let key = *events.keys().next().filter(|(due, _)| *due <= until)?;
+        events.remove(&key).map(|t| (key, t))
+    }
+
+    fn advance(&self, to: u64) {
+        let cycles = to - self.now();
+        self.now.set(to);
+        if cycles == 0 {
+            return;
+        }
+        let clocks = self.clocks.borrow().values().cloned().collect::<Vec<_>>();
+        for clock in clocks {
+            clock(cycles)
+        }
+    }
+
+    //fire the events due in the next cycles, events may schedule or cancel others
+    pub fn tick(&self, cycles: u64) {
+        let until = self.now() + cycles;
+        while let Some(((due, id), task)) = self.pop(until) {
+            self.advance(due);
+            self.running.set(Some(id));
+            self.cancelled.set(false);
+            match task {
+                Task::Once(f) => f(self),
+                Task::Every(period, mut f) => {
+                    if f(self) && !self.cancelled.get() {
+                        self.events
+                            .borrow_mut()
+                            .insert((due + period, id), Task::Every(period, f));
+                    }
+                }
+            }
+            self.running.set(None);
+        }
+        self.advance(until)
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Scheduler {
+        Scheduler::new()
+    }
+}
+
+thread_local! {
+    static SCHED: Rc<Scheduler> = Rc::new(Scheduler::new());
+}
+
+//the scheduler driven by ts_tick
+pub fn global() -> Rc<Scheduler> {
+    SCHED.with(Rc::clone)
+}
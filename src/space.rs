@@ -1,18 +1,27 @@
 extern crate intrusive_collections;
 
+use crate::cache::Cache;
+use crate::deferred::{Deferred, Latency, Op, Response, Token};
 use crate::memory::region::{BytesAccess, Region, U16Access, U32Access, U64Access, U8Access};
+use crate::perf::PerfModel;
+use crate::sched::{EventId, Scheduler};
 use intrusive_collections::rbtree::RBTree;
 use intrusive_collections::{intrusive_adapter, Bound, KeyAdapter, RBTreeLink};
-use std::collections::HashMap;
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{CStr, CString};
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 struct SpaceElem {
     link: RBTreeLink,
     key: u64,
     value: (String, Rc<Region>),
+    attr: Attr,
+    //for ffi query
+    cname: CString,
 }
 
 intrusive_adapter!(Adapter = Box<SpaceElem>:SpaceElem {link:RBTreeLink});
@@ -24,10 +33,139 @@ impl<'a> KeyAdapter<'a> for Adapter {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AccessKind {
+    Read,
+    Write,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Attr {
+    pub readable: bool,
+    pub writable: bool,
+}
+
+impl Attr {
+    pub const RW: Attr = Attr {
+        readable: true,
+        writable: true,
+    };
+    pub const RO: Attr = Attr {
+        readable: true,
+        writable: false,
+    };
+    pub const WO: Attr = Attr {
+        readable: false,
+        writable: true,
+    };
+}
+
+impl Default for Attr {
+    fn default() -> Attr {
+        Attr::RW
+    }
+}
+
+//view is the index of the view name in the Tracer
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trace {
+    pub view: usize,
+    pub kind: AccessKind,
+    pub addr: u64,
+    pub size: usize,
+}
+
+//disabled by default, only the latest limit records are kept
+pub struct Tracer {
+    enable: Cell<bool>,
+    limit: Cell<usize>,
+    views: RefCell<Vec<String>>,
+    records: RefCell<VecDeque<Trace>>,
+}
+
+impl Tracer {
+    pub const DEFAULT_LIMIT: usize = 4096;
+
+    pub fn new() -> Tracer {
+        Tracer {
+            enable: Cell::new(false),
+            limit: Cell::new(Tracer::DEFAULT_LIMIT),
+            views: RefCell::new(vec![]),
+            records: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    pub fn enable(&self, enable: bool) {
+        self.enable.set(enable)
+    }
+
+    pub fn set_limit(&self, limit: usize) {
+        self.limit.set(limit);
+        let mut records = self.records.borrow_mut();
+        while records.len() > limit {
+            records.pop_front();
+        }
+    }
+
+    pub fn records(&self) -> Ref<'_, VecDeque<Trace>> {
+        self.records.borrow()
+    }
+
+    pub fn view_name(&self, view: usize) -> Option<String> {
+        self.views.borrow().get(view).cloned()
+    }
+
+    fn intern(&self, name: &str) -> usize {
+        let mut views = self.views.borrow_mut();
+        if let Some(i) = views.iter().position(|v| v == name) {
+            i
+        } else {
+            views.push(name.to_string());
+            views.len() - 1
+        }
+    }
+
+    pub fn clear(&self) {
+        self.records.borrow_mut().clear()
+    }
+
+    //the view which issued the latest traced access covering addr
+    pub fn issuer(&self, addr: &u64) -> Option<String> {
+        self.records
+            .borrow()
+            .iter()
+            .rev()
+            .find(|t| *addr >= t.addr && *addr < t.addr + t.size as u64)
+            .and_then(|t| self.view_name(t.view))
+    }
+
+    fn trace(&self, view: usize, kind: AccessKind, addr: &u64, size: usize) {
+        if self.enable.get() && self.limit.get() > 0 {
+            let mut records = self.records.borrow_mut();
+            if records.len() >= self.limit.get() {
+                records.pop_front();
+            }
+            records.push_back(Trace {
+                view,
+                kind,
+                addr: *addr,
+                size,
+            })
+        }
+    }
+}
+
+impl Default for Tracer {
+    fn default() -> Tracer {
+        Tracer::new()
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     Overlap(String, String),
     Renamed(String, String),
+    NotFound(String, String),
 }
 
 impl Display for Error {
@@ -35,6 +173,7 @@ impl Display for Error {
         match self {
             Error::Overlap(s1, s2) => write!(f, "Overlap!{}:{}", s1, s2),
             Error::Renamed(s1, s2) => write!(f, "Renamed!{}:{}", s1, s2),
+            Error::NotFound(s1, s2) => write!(f, "NotFound!{}:{}", s1, s2),
         }
     }
 }
@@ -44,11 +183,23 @@ unsafe impl Send for RegionCPtr {}
 
 unsafe impl Sync for RegionCPtr {}
 
+//handle returned by the space, redirected when the region is replaced
+#[derive(Debug)]
+struct RegionHandle(*mut Box<Rc<Region>>);
+
 //Space should be an owner of Regions
 pub struct Space {
     regions: RBTree<Adapter>,
     //for ffi free
     ptrs: HashMap<String, Vec<RegionCPtr>>,
+    handles: HashMap<String, Vec<RegionHandle>>,
+    tracer: Option<(usize, Rc<Tracer>)>,
+    perf: Option<Rc<PerfModel>>,
+    cache: Option<Rc<Cache>>,
+    latencies: HashMap<String, Rc<dyn Latency>>,
+    deferred: Deferred,
+    //the clock driving tick, cancelled when the space is dropped
+    clock: Cell<Option<(EventId, Weak<Scheduler>)>>,
 }
 
 impl Space {
@@ -56,10 +207,52 @@ impl Space {
         Space {
             regions: RBTree::new(Adapter::default()),
             ptrs: HashMap::new(),
+            handles: HashMap::new(),
+            tracer: None,
+            perf: None,
+            cache: None,
+            latencies: HashMap::new(),
+            deferred: Deferred::new(),
+            clock: Cell::new(None),
+        }
+    }
+
+    pub fn set_tracer(&mut self, view: &str, tracer: &Rc<Tracer>) {
+        self.tracer = Some((tracer.intern(view), Rc::clone(tracer)))
+    }
+
+    pub fn set_perf(&mut self, perf: &Rc<PerfModel>) {
+        self.perf = Some(Rc::clone(perf))
+    }
+
+    //accesses to memory regions go through the cache, other spaces still see memory only
+    pub fn set_cache(&mut self, cache: &Rc<Cache>) {
+        self.cache = Some(Rc::clone(cache))
+    }
+
+    fn cache_for(&self, region: &Region) -> Option<&Rc<Cache>> {
+        self.cache.as_ref().filter(|_| !region.is_io())
+    }
+
+    //dirty lines go back to the region while it is still the mapped one
+    fn uncache(&self, region: &Rc<Region>) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache
+                .flush_region(region)
+                .unwrap_or_else(|addr| panic!("cache write back @{:#x} fail!", addr))
         }
     }
 
     pub fn add_region(&mut self, name: &str, region: &Rc<Region>) -> Result<Rc<Region>, Error> {
+        self.add_region_with_attr(name, region, Attr::default())
+    }
+
+    pub fn add_region_with_attr(
+        &mut self,
+        name: &str,
+        region: &Rc<Region>,
+        attr: Attr,
+    ) -> Result<Rc<Region>, Error> {
         let check = || {
             if let Some(_) = self.regions.iter().find(|a| a.value.0 == name) {
                 return Err(Error::Renamed(
@@ -67,30 +260,7 @@ impl Space {
                     format!("region name {} has existed!", name),
                 ));
             }
-            if let Some(v) = self.regions.iter().find(|a| {
-                region.info.base >= a.value.1.info.base
-                    && region.info.base < a.value.1.info.base + a.value.1.info.size
-                    || region.info.base + region.info.size - 1 >= a.value.1.info.base
-                        && region.info.base + region.info.size - 1
-                            < a.value.1.info.base + a.value.1.info.size
-                    || a.value.1.info.base >= region.info.base
-                        && a.value.1.info.base < region.info.base + region.info.size
-                    || a.value.1.info.base + a.value.1.info.size - 1 >= region.info.base
-                        && a.value.1.info.base + a.value.1.info.size - 1
-                            < region.info.base + region.info.size
-            }) {
-                return Err(Error::Overlap(
-                    v.value.0.to_string(),
-                    format!(
-                        "region [{} : {:?}] is overlapped with [{} : {:?}]!",
-                        name,
-                        region.deref().info,
-                        v.value.0,
-                        v.value.1.deref().info
-                    ),
-                ));
-            }
-            Ok(())
+            self.check_overlap(name, region)
         };
         check()?;
         // self.regions.insert(region.info.base, (name.to_string(), Rc::clone(region)));
@@ -98,21 +268,93 @@ impl Space {
             link: RBTreeLink::new(),
             key: region.info.base,
             value: (name.to_string(), Rc::clone(region)),
+            attr,
+            cname: CString::new(name).unwrap(),
         }));
         Ok(Rc::clone(region))
     }
 
+    //regions named name are skipped, they are going to be replaced
+    fn check_overlap(&self, name: &str, region: &Rc<Region>) -> Result<(), Error> {
+        if let Some(v) = self.regions.iter().filter(|a| a.value.0 != name).find(|a| {
+            region.info.base >= a.value.1.info.base
+                && region.info.base < a.value.1.info.base + a.value.1.info.size
+                || region.info.base + region.info.size - 1 >= a.value.1.info.base
+                    && region.info.base + region.info.size - 1
+                        < a.value.1.info.base + a.value.1.info.size
+                || a.value.1.info.base >= region.info.base
+                    && a.value.1.info.base < region.info.base + region.info.size
+                || a.value.1.info.base + a.value.1.info.size - 1 >= region.info.base
+                    && a.value.1.info.base + a.value.1.info.size - 1
+                        < region.info.base + region.info.size
+        }) {
+            return Err(Error::Overlap(
+                v.value.0.to_string(),
+                format!(
+                    "region [{} : {:?}] is overlapped with [{} : {:?}]!",
+                    name,
+                    region.deref().info,
+                    v.value.0,
+                    v.value.1.deref().info
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    //swap the region mapped as name in one step, ffi handles returned for name are
+    //redirected to the new region and the old region is returned
+    pub fn replace_region(&mut self, name: &str, region: &Rc<Region>) -> Result<Rc<Region>, Error> {
+        let attr = self.get_attr(name).ok_or_else(|| {
+            Error::NotFound(name.to_string(), format!("region {} does not exist!", name))
+        })?;
+        self.check_overlap(name, region)?;
+        let mut cursor = self.regions.front_mut();
+        while !cursor.is_null() {
+            if let Some(e) = cursor.get() {
+                if e.value.0 == name {
+                    break;
+                }
+            }
+            cursor.move_next();
+        }
+        let old = cursor.remove().unwrap();
+        self.uncache(&old.value.1);
+        self.regions.insert(Box::new(SpaceElem {
+            link: RBTreeLink::new(),
+            key: region.info.base,
+            value: (name.to_string(), Rc::clone(region)),
+            attr,
+            cname: CString::new(name).unwrap(),
+        }));
+        if let Some(hs) = self.handles.get(name) {
+            hs.iter()
+                .for_each(|RegionHandle(ptr)| unsafe { ***ptr = Rc::clone(region) })
+        }
+        Ok(Rc::clone(&old.value.1))
+    }
+
+    pub fn move_region(&mut self, name: &str, base: u64) -> Result<Rc<Region>, Error> {
+        let region = self.get_region(name).ok_or_else(|| {
+            Error::NotFound(name.to_string(), format!("region {} does not exist!", name))
+        })?;
+        self.replace_region(name, &Region::rebase(base, &region))
+    }
+
     pub fn delete_region(&mut self, name: &str) {
         let mut cursor = self.regions.front_mut();
         while !cursor.is_null() {
             if let Some(e) = cursor.get() {
                 if e.value.0 == name {
-                    cursor.remove();
+                    let old = cursor.remove().unwrap();
+                    self.uncache(&old.value.1);
                     break;
                 }
             }
             cursor.move_next();
         }
+        self.latencies.remove(name);
+        self.handles.remove(name);
         if let Some(ps) = self.ptrs.remove(name) {
             ps.iter()
                 .for_each(|RegionCPtr(ptr)| std::mem::drop(unsafe { (*ptr).read() }))
@@ -133,6 +375,13 @@ impl Space {
         }
     }
 
+    pub fn get_attr(&self, name: &str) -> Option<Attr> {
+        self.regions
+            .iter()
+            .find(|a| a.value.0 == name)
+            .map(|a| a.attr)
+    }
+
     pub fn get_region_by_addr(&self, addr: &u64) -> Result<Rc<Region>, u64> {
         if let Some(e) = self.regions.upper_bound(Bound::Included(addr)).get() {
             if *addr < e.value.1.info.base + e.value.1.info.size {
@@ -145,48 +394,200 @@ impl Space {
         }
     }
 
+    //(name, base, size, type) in address order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64, u64, String)> {
+        self.regions.iter().map(|e| {
+            (
+                e.value.0.as_str(),
+                e.value.1.info.base,
+                e.value.1.info.size,
+                e.value.1.get_type(),
+            )
+        })
+    }
+
+    //regions intersecting [start, end)
+    pub fn intersects(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> impl Iterator<Item = (&str, u64, u64, String)> {
+        self.iter()
+            .filter(move |(_, base, size, _)| *base < end && start < *base + *size)
+    }
+
+    pub fn free_gap(&self, size: u64, align: u64) -> Option<u64> {
+        let align = align.max(1);
+        let align_up = |addr: u64| match addr % align {
+            0 => Some(addr),
+            r => addr.checked_add(align - r),
+        };
+        let mut cursor = Some(0);
+        for e in self.regions.iter() {
+            let info = &e.value.1.info;
+            if let Some(base) = cursor.and_then(align_up) {
+                if matches!(base.checked_add(size), Some(end) if end <= info.base) {
+                    return Some(base);
+                }
+            }
+            cursor = info.base.checked_add(info.size);
+        }
+        cursor
+            .and_then(align_up)
+            .filter(|base| size == 0 || base.checked_add(size - 1).is_some())
+    }
+
+    pub(crate) fn nth_region(&self, n: usize) -> Option<(&CStr, Rc<Region>)> {
+        self.regions
+            .iter()
+            .nth(n)
+            .map(|e| (e.cname.as_c_str(), Rc::clone(&e.value.1)))
+    }
+
+    pub fn to_json(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let entries = self
+            .iter()
+            .map(|(name, base, size, ty)| {
+                format!(
+                    "  {{\"name\": \"{}\", \"base\": \"{:#x}\", \"size\": \"{:#x}\", \"type\": \"{}\"}}",
+                    escape(name),
+                    base,
+                    size,
+                    escape(&ty)
+                )
+            })
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            "[]\n".to_string()
+        } else {
+            format!("[\n{}\n]\n", entries.join(",\n"))
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let quote = |s: &str| {
+            if s.contains(&[',', '"', '\n'][..]) {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s.to_string()
+            }
+        };
+        self.iter().fold(
+            "name,base,size,type\n".to_string(),
+            |acc, (name, base, size, ty)| {
+                format!(
+                    "{}{},{:#x},{:#x},{}\n",
+                    acc,
+                    quote(name),
+                    base,
+                    size,
+                    quote(&ty)
+                )
+            },
+        )
+    }
+
+    fn access(&self, kind: AccessKind, addr: &u64, size: usize) -> Result<Rc<Region>, u64> {
+        let e = self
+            .regions
+            .upper_bound(Bound::Included(addr))
+            .get()
+            .filter(|e| *addr < e.value.1.info.base + e.value.1.info.size)
+            .ok_or(*addr)?;
+        let permitted = match kind {
+            AccessKind::Read => e.attr.readable,
+            AccessKind::Write => e.attr.writable,
+        };
+        if !permitted {
+            return Err(*addr);
+        }
+        if let Some((view, ref tracer)) = self.tracer {
+            tracer.trace(view, kind, addr, size)
+        }
+        if let Some(ref perf) = self.perf {
+            perf.account(&e.value.0, kind, size)
+        }
+        Ok(Rc::clone(&e.value.1))
+    }
+
     pub fn write_u8(&self, addr: &u64, data: u8) -> Result<(), u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Write, addr, 1)?;
+        if let Some(cache) = self.cache_for(&region) {
+            return cache.write(&region, addr, &data.to_le_bytes());
+        }
         Ok(U8Access::write(region.deref(), addr, data))
     }
 
     pub fn read_u8(&self, addr: &u64) -> Result<u8, u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Read, addr, 1)?;
+        if let Some(cache) = self.cache_for(&region) {
+            let mut data = [0; 1];
+            cache.read(&region, addr, &mut data)?;
+            return Ok(u8::from_le_bytes(data));
+        }
         Ok(U8Access::read(region.deref(), addr))
     }
 
     pub fn write_u16(&self, addr: &u64, data: u16) -> Result<(), u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Write, addr, 2)?;
+        if let Some(cache) = self.cache_for(&region) {
+            return cache.write(&region, addr, &data.to_le_bytes());
+        }
         Ok(U16Access::write(region.deref(), addr, data))
     }
 
     pub fn read_u16(&self, addr: &u64) -> Result<u16, u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Read, addr, 2)?;
+        if let Some(cache) = self.cache_for(&region) {
+            let mut data = [0; 2];
+            cache.read(&region, addr, &mut data)?;
+            return Ok(u16::from_le_bytes(data));
+        }
         Ok(U16Access::read(region.deref(), addr))
     }
 
     pub fn write_u32(&self, addr: &u64, data: u32) -> Result<(), u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Write, addr, 4)?;
+        if let Some(cache) = self.cache_for(&region) {
+            return cache.write(&region, addr, &data.to_le_bytes());
+        }
         Ok(U32Access::write(region.deref(), addr, data))
     }
 
     pub fn read_u32(&self, addr: &u64) -> Result<u32, u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Read, addr, 4)?;
+        if let Some(cache) = self.cache_for(&region) {
+            let mut data = [0; 4];
+            cache.read(&region, addr, &mut data)?;
+            return Ok(u32::from_le_bytes(data));
+        }
         Ok(U32Access::read(region.deref(), addr))
     }
 
     pub fn write_u64(&self, addr: &u64, data: u64) -> Result<(), u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Write, addr, 8)?;
+        if let Some(cache) = self.cache_for(&region) {
+            return cache.write(&region, addr, &data.to_le_bytes());
+        }
         Ok(U64Access::write(region.deref(), addr, data))
     }
 
     pub fn read_u64(&self, addr: &u64) -> Result<u64, u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Read, addr, 8)?;
+        if let Some(cache) = self.cache_for(&region) {
+            let mut data = [0; 8];
+            cache.read(&region, addr, &mut data)?;
+            return Ok(u64::from_le_bytes(data));
+        }
         Ok(U64Access::read(region.deref(), addr))
     }
 
     pub fn write_bytes(&self, addr: &u64, data: &[u8]) -> Result<usize, u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Write, addr, data.len())?;
+        if let Some(cache) = self.cache_for(&region) {
+            return cache.write(&region, addr, data).map(|_| data.len());
+        }
         if let Ok(size) = BytesAccess::write(region.deref(), addr, data) {
             Ok(size)
         } else {
@@ -195,7 +596,10 @@ impl Space {
     }
 
     pub fn read_bytes(&self, addr: &u64, data: &mut [u8]) -> Result<usize, u64> {
-        let region = self.get_region_by_addr(addr)?;
+        let region = self.access(AccessKind::Read, addr, data.len())?;
+        if let Some(cache) = self.cache_for(&region) {
+            return cache.read(&region, addr, data).map(|_| data.len());
+        }
         if let Ok(size) = BytesAccess::read(region.deref(), addr, data) {
             Ok(size)
         } else {
@@ -203,12 +607,141 @@ impl Space {
         }
     }
 
+    //requests to the region are answered after the modeled latency instead of in place
+    pub fn set_latency(&mut self, name: &str, latency: &Rc<dyn Latency>) -> Result<(), Error> {
+        if self.get_region(name).is_none() {
+            return Err(Error::NotFound(
+                name.to_string(),
+                format!("region {} does not exist!", name),
+            ));
+        }
+        self.latencies.insert(name.to_string(), Rc::clone(latency));
+        Ok(())
+    }
+
+    fn latency(&self, kind: AccessKind, addr: &u64, size: usize) -> Option<u64> {
+        self.regions
+            .upper_bound(Bound::Included(addr))
+            .get()
+            .filter(|e| *addr < e.value.1.info.base + e.value.1.info.size)
+            .and_then(|e| self.latencies.get(&e.value.0))
+            .map_or(Some(0), |l| l.latency(kind, addr, size))
+    }
+
+    fn respond(&self, addr: &u64, op: &Op) -> Response {
+        match op {
+            Op::Read(size) => {
+                let mut data = vec![0; *size];
+                match self.read_bytes(addr, &mut data) {
+                    Ok(_) => Response::Read(data),
+                    Err(e) => Response::Err(e),
+                }
+            }
+            Op::Write(data) => match self.write_bytes(addr, data) {
+                Ok(size) => Response::Write(size),
+                Err(e) => Response::Err(e),
+            },
+        }
+    }
+
+    fn request(&self, kind: AccessKind, addr: &u64, op: Op, size: usize) -> Token {
+        let latency = self.latency(kind, addr, size);
+        let token = self.deferred.push(addr, op, latency);
+        if latency == Some(0) {
+            self.complete(token);
+        }
+        token
+    }
+
+    //the access takes effect when the response completes
+    pub fn request_read(&self, addr: &u64, size: usize) -> Token {
+        self.request(AccessKind::Read, addr, Op::Read(size), size)
+    }
+
+    pub fn request_write(&self, addr: &u64, data: &[u8]) -> Token {
+        self.request(
+            AccessKind::Write,
+            addr,
+            Op::Write(data.to_vec()),
+            data.len(),
+        )
+    }
+
+    pub fn tick(&self, ticks: u64) {
+        for (token, r) in self.deferred.advance(ticks) {
+            self.deferred.finish(token, self.respond(&r.addr, &r.op))
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.deferred.now()
+    }
+
+    //let sched drive tick, the space catches up with it first, cancel the returned id to detach
+    pub fn attach(self: &Rc<Self>, sched: &Rc<Scheduler>) -> EventId {
+        let space = Rc::downgrade(self);
+        self.attach_with(sched, move |cycles| {
+            if let Some(space) = space.upgrade() {
+                space.tick(cycles)
+            }
+        })
+    }
+
+    //a space is driven by one clock at a time, the previous one is cancelled
+    pub fn attach_with<F: Fn(u64) + 'static>(&self, sched: &Rc<Scheduler>, f: F) -> EventId {
+        self.detach();
+        if sched.now() > self.now() {
+            self.tick(sched.now() - self.now())
+        }
+        let id = sched.clock(f);
+        self.clock.set(Some((id, Rc::downgrade(sched))));
+        id
+    }
+
+    pub fn detach(&self) -> bool {
+        match self.clock.take() {
+            Some((id, sched)) => sched.upgrade().is_some_and(|sched| sched.cancel(id)),
+            None => false,
+        }
+    }
+
+    //complete a pending request now, false if it is not pending
+    pub fn complete(&self, token: Token) -> bool {
+        if let Some(r) = self.deferred.take(token) {
+            self.deferred.finish(token, self.respond(&r.addr, &r.op));
+            true
+        } else {
+            false
+        }
+    }
+
+    //a response is returned only once
+    pub fn poll(&self, token: Token) -> Option<Response> {
+        self.deferred.poll(token)
+    }
+
+    //ffi handle freed when name is deleted
     pub fn clean(&mut self, name: &str, ptr: *const Box<Rc<Region>>) {
         self.ptrs
             .entry(String::from(name))
             .or_insert(vec![])
             .push(RegionCPtr(ptr))
     }
+
+    //ffi handle returned by the space for name, also redirected when name is replaced
+    pub fn track(&mut self, name: &str, ptr: *mut Box<Rc<Region>>) {
+        self.clean(name, ptr);
+        self.handles
+            .entry(String::from(name))
+            .or_insert(vec![])
+            .push(RegionHandle(ptr))
+    }
+}
+
+impl Drop for Space {
+    fn drop(&mut self) {
+        self.detach();
+    }
 }
 
 impl Display for Space {
@@ -1,8 +1,13 @@
 use crate::memory::region::Heap;
 use crate::memory::region::Region;
 use crate::memory::region::GHEAP;
+use crate::memory::region::{U16Access, U32Access, U8Access};
 use crate::memory::MemInfo;
 use crate::space::*;
+use crate::views::Views;
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::rc::Rc;
 
 #[test]
 fn space_drop() {
@@ -25,7 +30,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .collect::<Vec<MemInfo>>()
     );
     assert_ne!(
@@ -34,7 +39,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -46,7 +51,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .collect::<Vec<MemInfo>>()
     );
     assert_ne!(
@@ -55,7 +60,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -67,7 +72,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .collect::<Vec<MemInfo>>()
     );
     assert_ne!(
@@ -76,7 +81,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -89,7 +94,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .collect::<Vec<MemInfo>>()
     );
     assert_ne!(
@@ -98,7 +103,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -111,7 +116,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .collect::<Vec<MemInfo>>()
     );
     assert_ne!(
@@ -120,7 +125,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -132,7 +137,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .collect::<Vec<MemInfo>>()
     );
     assert_ne!(
@@ -141,7 +146,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -152,7 +157,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -164,7 +169,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .collect::<Vec<MemInfo>>()
     );
     assert_eq!(
@@ -173,7 +178,7 @@ fn space_drop() {
             .unwrap()
             .alloced_blocks
             .iter()
-            .map(|l| { l.car().unwrap() })
+            .map(|(&base, &size)| MemInfo { base, size })
             .find(|i| { i == &info }),
         None
     );
@@ -207,3 +212,570 @@ fn space_query() {
         region3.info
     );
 }
+
+#[test]
+fn space_bridge() {
+    let mut periph = Space::new();
+    let uart = periph
+        .add_region(
+            "uart",
+            &Region::remap(0x1000, &GHEAP.alloc(0x100, 8).unwrap()),
+        )
+        .unwrap();
+    let periph = Rc::new(periph);
+
+    let mut interconnect = Space::new();
+    interconnect
+        .add_region("periph", &Region::bridge(0x10_0000, &periph, 0, 0x1_0000))
+        .unwrap();
+    let interconnect = Rc::new(interconnect);
+
+    let accesses = Rc::new(RefCell::new(vec![]));
+    let mut cpu = Space::new();
+    let log = Rc::clone(&accesses);
+    cpu.add_region(
+        "interconnect",
+        &Region::bridge_with_hook(
+            0x8000_0000,
+            &interconnect,
+            0x10_0000,
+            0x1_0000,
+            move |kind, addr, size| log.borrow_mut().push((kind, *addr, size)),
+        ),
+    )
+    .unwrap();
+
+    cpu.write_u32(&0x8000_1004, 0xdeadbeef).unwrap();
+    assert_eq!(U32Access::read(uart.deref(), &0x1004), 0xdeadbeef);
+    U16Access::write(uart.deref(), &0x1008, 0x5aa5);
+    assert_eq!(cpu.read_u16(&0x8000_1008).unwrap(), 0x5aa5);
+    let mut data = [0u8; 4];
+    cpu.read_bytes(&0x8000_1004, &mut data).unwrap();
+    assert_eq!(u32::from_le_bytes(data), 0xdeadbeef);
+    assert_eq!(
+        *accesses.borrow(),
+        vec![
+            (AccessKind::Write, 0x10_1004, 4),
+            (AccessKind::Read, 0x10_1008, 2),
+            (AccessKind::Read, 0x10_1004, 4)
+        ]
+    );
+    assert!(cpu
+        .get_region("interconnect")
+        .unwrap()
+        .get_space()
+        .unwrap()
+        .get_region("periph")
+        .is_some());
+}
+
+#[test]
+fn space_views() {
+    let mut views = Views::new();
+    let memory = GHEAP.alloc(0x100, 8).unwrap();
+    let rom = GHEAP.alloc(0x100, 8).unwrap();
+    {
+        let cpu = views.add_view("cpu").unwrap();
+        cpu.add_region("memory", &Region::remap(0x8000_0000, &memory))
+            .unwrap();
+        cpu.add_region_with_attr("rom", &Region::remap(0x1000, &rom), Attr::RO)
+            .unwrap();
+    }
+    {
+        let dma = views.add_view("dma").unwrap();
+        dma.add_region("memory", &Region::remap(0x0, &memory))
+            .unwrap();
+    }
+    assert!(views.add_view("cpu").is_err());
+    assert_eq!(views.names(), vec!["cpu", "dma"]);
+
+    let cpu = views.get_view("cpu").unwrap();
+    let dma = views.get_view("dma").unwrap();
+    dma.write_u8(&0x10, 0).unwrap();
+    assert!(views.tracer().records().is_empty());
+    views.tracer().enable(true);
+    dma.write_u32(&0x10, 0x5a5a_a5a5).unwrap();
+    assert_eq!(cpu.read_u32(&0x8000_0010).unwrap(), 0x5a5a_a5a5);
+    assert_eq!(cpu.write_u8(&0x1000, 0), Err(0x1000));
+    assert_eq!(cpu.read_u8(&0x1000), Ok(0));
+    assert_eq!(views.issuer(&0x12), Some("dma"));
+    assert_eq!(views.issuer(&0x8000_0013), Some("cpu"));
+    assert_eq!(views.issuer(&0x14), None);
+    assert_eq!(
+        views.tracer().records()[0],
+        Trace {
+            view: 1,
+            kind: AccessKind::Write,
+            addr: 0x10,
+            size: 4
+        }
+    );
+    assert_eq!(views.tracer().view_name(1), Some("dma".to_string()));
+
+    views.tracer().set_limit(2);
+    assert_eq!(views.tracer().records().len(), 2);
+    dma.read_u8(&0x20).unwrap();
+    assert_eq!(views.tracer().records().len(), 2);
+    assert_eq!(views.tracer().records()[1].addr, 0x20);
+
+    views.tracer().clear();
+    views.tracer().enable(false);
+    dma.read_u8(&0x10).unwrap();
+    assert_eq!(views.issuer(&0x10), None);
+}
+
+#[test]
+fn space_enumerate() {
+    let mut space = Space::new();
+    let memory = GHEAP.alloc(0x1000, 8).unwrap();
+    space
+        .add_region("rom", &Region::remap(0x0, &memory))
+        .unwrap();
+    space
+        .add_region(
+            "dram",
+            &Region::remap_partial(0x8000_0000, &memory, 0, 0x100),
+        )
+        .unwrap();
+    space
+        .add_region("sram", &Region::remap_partial(0x2800, &memory, 0, 0x800))
+        .unwrap();
+    assert_eq!(
+        space
+            .iter()
+            .map(|(name, base, size, _)| (name, base, size))
+            .collect::<Vec<_>>(),
+        vec![
+            ("rom", 0x0, 0x1000),
+            ("sram", 0x2800, 0x800),
+            ("dram", 0x8000_0000, 0x100)
+        ]
+    );
+    assert_eq!(
+        space
+            .intersects(0xfff, 0x2801)
+            .map(|(name, _, _, _)| name)
+            .collect::<Vec<_>>(),
+        vec!["rom", "sram"]
+    );
+    assert_eq!(space.intersects(0x1000, 0x2800).count(), 0);
+    assert_eq!(space.free_gap(0x1000, 1), Some(0x1000));
+    assert_eq!(space.free_gap(0x800, 0x800), Some(0x1000));
+    assert_eq!(space.free_gap(0x1801, 1), Some(0x3000));
+    assert_eq!(space.free_gap(0x1000, 0x2000), Some(0x4000));
+    assert_eq!(
+        space.free_gap(0x8000_0000, 0x8000_0000),
+        Some(0x1_0000_0000)
+    );
+    assert_eq!(space.free_gap(u64::MAX, 1), None);
+
+    let csv = space.to_csv();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("name,base,size,type"));
+    assert!(lines.next().unwrap().starts_with("rom,0x0,0x1000,Remap("));
+    assert_eq!(csv.lines().count(), 4);
+    let json = space.to_json();
+    assert!(json.starts_with("[\n  {\"name\": \"rom\", \"base\": \"0x0\", \"size\": \"0x1000\""));
+    assert_eq!(json.matches("\"name\"").count(), 3);
+    assert_eq!(Space::new().to_json(), "[]\n");
+}
+
+#[test]
+fn space_replace() {
+    let mut space = Space::new();
+    let rom = GHEAP.alloc(0x100, 8).unwrap();
+    let dram = GHEAP.alloc(0x1000, 8).unwrap();
+    U8Access::write(rom.deref(), &rom.info.base, 0x5a);
+    U8Access::write(dram.deref(), &dram.info.base, 0xa5);
+    space
+        .add_region_with_attr("boot", &Region::remap(0x0, &rom), Attr::RO)
+        .unwrap();
+    space
+        .add_region(
+            "mmio",
+            &Region::remap(0x800, &GHEAP.alloc(0x100, 8).unwrap()),
+        )
+        .unwrap();
+    //handles held by c side, returned by the space or passed in by the caller
+    let handle = Box::into_raw(Box::new(Box::new(space.get_region("boot").unwrap())));
+    space.track("boot", handle);
+    let input = Box::into_raw(Box::new(Box::new(Rc::clone(&rom))));
+    space.clean("boot", input);
+
+    assert!(space
+        .replace_region("boot", &Region::remap(0x0, &dram))
+        .is_err());
+    assert!(space
+        .replace_region("none", &Region::remap(0x0, &dram))
+        .is_err());
+    assert_eq!(space.read_u8(&0x0), Ok(0x5a));
+
+    let old = space
+        .replace_region("boot", &Region::remap(0x0, &GHEAP.alloc(0x100, 8).unwrap()))
+        .unwrap();
+    assert_eq!(U8Access::read(old.deref(), &0x0), 0x5a);
+    assert_eq!(space.read_u8(&0x0), Ok(0x0));
+    assert_eq!(space.get_attr("boot"), Some(Attr::RO));
+    assert!(Rc::ptr_eq(
+        unsafe { (*handle).deref() },
+        &space.get_region("boot").unwrap()
+    ));
+    assert!(Rc::ptr_eq(unsafe { (*input).deref() }, &rom));
+
+    space.move_region("boot", 0x8000_0000).unwrap();
+    assert_eq!(space.get_region_by_addr(&0x0).map(|_| ()), Err(0x0));
+    assert_eq!(
+        space.get_region("boot").unwrap().info,
+        MemInfo {
+            base: 0x8000_0000,
+            size: 0x100
+        }
+    );
+    assert_eq!(
+        space
+            .get_region("boot")
+            .unwrap()
+            .get_type()
+            .matches("Remap")
+            .count(),
+        1
+    );
+    assert_eq!(unsafe { (&*handle).info.base }, 0x8000_0000);
+    assert!(space.move_region("boot", 0x780).is_err());
+    space.delete_region("boot");
+}
+
+#[test]
+fn space_deferred() {
+    use crate::deferred::{FixedLatency, Latency, Response};
+    let mut space = Space::new();
+    space
+        .add_region("dram", &Region::remap(0x0, &GHEAP.alloc(0x100, 8).unwrap()))
+        .unwrap();
+    space
+        .add_region(
+            "mmio",
+            &Region::remap(0x1000, &GHEAP.alloc(0x100, 8).unwrap()),
+        )
+        .unwrap();
+    //regions without latency answer at once
+    let t = space.request_write(&0x0, &[1, 2]);
+    assert_eq!(space.poll(t), Some(Response::Write(2)));
+    assert_eq!(space.poll(t), None);
+
+    let dram: Rc<dyn Latency> = Rc::new(FixedLatency::new(Some(2), Some(3)));
+    let mmio: Rc<dyn Latency> = Rc::new(FixedLatency::new(None, Some(1)));
+    space.set_latency("dram", &dram).unwrap();
+    space.set_latency("mmio", &mmio).unwrap();
+    assert!(space.set_latency("none", &dram).is_err());
+
+    let w = space.request_write(&0x10, &[0xaa]);
+    let r = space.request_read(&0x10, 1);
+    space.tick(1);
+    assert_eq!(space.poll(w), None);
+    assert_eq!(space.read_u8(&0x10), Ok(0));
+    space.tick(1);
+    //reads complete before the earlier write, as modeled
+    assert_eq!(space.poll(r), Some(Response::Read(vec![0])));
+    space.tick(1);
+    assert_eq!(space.now(), 3);
+    assert_eq!(space.poll(w), Some(Response::Write(1)));
+    assert_eq!(space.read_u8(&0x10), Ok(0xaa));
+
+    //mmio reads wait for an event
+    space.write_u8(&0x1004, 0x5a).unwrap();
+    let r = space.request_read(&0x1004, 1);
+    space.tick(100);
+    assert_eq!(space.poll(r), None);
+    assert!(space.complete(r));
+    assert!(!space.complete(r));
+    assert_eq!(space.poll(r), Some(Response::Read(vec![0x5a])));
+
+    let e = space.request_read(&0x2000, 4);
+    assert_eq!(space.poll(e), Some(Response::Err(0x2000)));
+    space.delete_region("dram");
+    let e = space.request_read(&0x10, 1);
+    assert_eq!(space.poll(e), Some(Response::Err(0x10)));
+}
+
+#[test]
+fn sched_events() {
+    use crate::sched::Scheduler;
+    let sched = Scheduler::new();
+    let log = Rc::new(RefCell::new(vec![]));
+    let l = log.clone();
+    sched.schedule(5, move |s| l.borrow_mut().push(("once", s.now())));
+    let l = log.clone();
+    let every = sched.every(2, move |s| {
+        l.borrow_mut().push(("every", s.now()));
+        true
+    });
+    let l = log.clone();
+    //chained events and zero delay fire within the same tick
+    sched.schedule(3, move |s| {
+        let l = l.clone();
+        s.schedule(0, move |s| l.borrow_mut().push(("chained", s.now())));
+    });
+    sched.tick(5);
+    assert_eq!(sched.now(), 5);
+    assert_eq!(
+        *log.borrow(),
+        vec![("every", 2), ("chained", 3), ("every", 4), ("once", 5)]
+    );
+    assert_eq!(sched.next_due(), Some(6));
+    assert!(sched.cancel(every));
+    assert!(!sched.cancel(every));
+    assert_eq!(sched.pending(), 0);
+
+    //periodic tasks stop by returning false or cancelling themselves
+    let count = Rc::new(RefCell::new(0));
+    let c = count.clone();
+    sched.every(1, move |_| {
+        *c.borrow_mut() += 1;
+        *c.borrow() < 3
+    });
+    let c = count.clone();
+    let id = Rc::new(RefCell::new(0));
+    let me = id.clone();
+    *id.borrow_mut() = sched.every(10, move |s| {
+        *c.borrow_mut() += 100;
+        s.cancel(*me.borrow());
+        true
+    });
+    sched.tick(100);
+    assert_eq!(*count.borrow(), 103);
+    assert_eq!(sched.pending(), 0);
+    assert_eq!(sched.now(), 105);
+
+    //an attached space runs on the scheduler time, responses are ready when their events fire
+    use crate::deferred::{FixedLatency, Latency, Response};
+    let mut space = Space::new();
+    space
+        .add_region("dram", &Region::remap(0x0, &GHEAP.alloc(0x100, 8).unwrap()))
+        .unwrap();
+    let dram: Rc<dyn Latency> = Rc::new(FixedLatency::new(Some(3), None));
+    space.set_latency("dram", &dram).unwrap();
+    let space = Rc::new(space);
+    let sched = Rc::new(sched);
+    let clock = space.attach(&sched);
+    assert_eq!(space.now(), 105);
+    let r = space.request_read(&0x0, 1);
+    let s = space.clone();
+    let polled = Rc::new(RefCell::new(None));
+    let p = polled.clone();
+    sched.schedule(3, move |_| *p.borrow_mut() = s.poll(r));
+    sched.tick(5);
+    assert_eq!(*polled.borrow(), Some(Response::Read(vec![0])));
+    assert_eq!(space.now(), 110);
+    assert!(sched.cancel(clock));
+    sched.tick(1);
+    assert_eq!(space.now(), 110);
+    //dropping an attached space cancels its clock
+    let clock = space.attach(&sched);
+    std::mem::drop(space);
+    assert!(!sched.cancel(clock));
+}
+
+#[test]
+fn space_perf() {
+    use crate::deferred::{Latency, Response};
+    use crate::perf::{PerfModel, Stats, Timing};
+    let mut space = Space::new();
+    space
+        .add_region("dram", &Region::remap(0x0, &GHEAP.alloc(0x100, 8).unwrap()))
+        .unwrap();
+    space
+        .add_region(
+            "mmio",
+            &Region::remap(0x1000, &GHEAP.alloc(0x100, 8).unwrap()),
+        )
+        .unwrap();
+    let perf = Rc::new(PerfModel::new(Timing::SRAM));
+    perf.set_timing("dram", Timing::DRAM);
+    space.set_perf(&perf);
+
+    space.write_u64(&0x0, 1).unwrap();
+    space.read_bytes(&0x0, &mut [0; 20]).unwrap();
+    space.read_u32(&0x1000).unwrap();
+    assert!(space.read_u32(&0x2000).is_err());
+    assert_eq!(
+        perf.stats("dram"),
+        Stats {
+            reads: 1,
+            writes: 1,
+            bytes: 28,
+            cycles: 101 + 103,
+        }
+    );
+    assert_eq!(perf.stats("mmio").accesses(), 1);
+    assert_eq!(perf.cycles(), 204 + 2);
+    assert!(perf.report().contains("total"));
+
+    perf.enable(false);
+    space.read_u32(&0x1000).unwrap();
+    assert_eq!(perf.cycles(), 206);
+    perf.enable(true);
+    perf.reset();
+    assert_eq!(perf.cycles(), 0);
+
+    //timings model deferred responses too
+    let dram: Rc<dyn Latency> = Rc::new(Timing::DRAM);
+    space.set_latency("dram", &dram).unwrap();
+    let r = space.request_read(&0x0, 8);
+    space.tick(100);
+    assert_eq!(space.poll(r), None);
+    space.tick(1);
+    assert_eq!(
+        space.poll(r),
+        Some(Response::Read(vec![1, 0, 0, 0, 0, 0, 0, 0]))
+    );
+    assert_eq!(perf.stats("dram").cycles, 101);
+}
+
+#[test]
+fn space_cache() {
+    use crate::cache::{Cache, CacheConfig, CacheStats, Replace, WritePolicy};
+    let config = CacheConfig {
+        size: 256,
+        ways: 2,
+        line: 32,
+        replace: Replace::Lru,
+        write: WritePolicy::WriteBack,
+        buffered: true,
+    };
+    assert!(Cache::new(CacheConfig { line: 24, ..config }).is_err());
+    assert!(Cache::new(CacheConfig {
+        size: 200,
+        ..config
+    })
+    .is_err());
+
+    let mut views = Views::new();
+    let memory = GHEAP.alloc(0x1000, 0x1000).unwrap();
+    let cache = Rc::new(Cache::new(config).unwrap());
+    let cpu = views.add_view("cpu").unwrap();
+    cpu.add_region("memory", &Region::remap(0x0, &memory))
+        .unwrap();
+    cpu.set_cache(&cache);
+    views
+        .add_view("dma")
+        .unwrap()
+        .add_region("memory", &Region::remap(0x0, &memory))
+        .unwrap();
+    let cpu = views.get_view("cpu").unwrap();
+    let dma = views.get_view("dma").unwrap();
+
+    //dirty lines are not visible to dma until cleaned
+    cpu.write_u32(&0x10, 0xdeadbeef).unwrap();
+    assert!(cache.is_dirty(0x10));
+    assert_eq!(dma.read_u32(&0x10), Ok(0));
+    cache.clean(0x10, 4).unwrap();
+    assert!(cache.is_cached(0x10) && !cache.is_dirty(0x10));
+    assert_eq!(dma.read_u32(&0x10), Ok(0xdeadbeef));
+    //stale line until invalidated
+    dma.write_u32(&0x14, 0x5a5a).unwrap();
+    assert_eq!(cpu.read_u32(&0x14), Ok(0));
+    cache.invalidate(0x0, 0x20).unwrap();
+    assert_eq!(cpu.read_u32(&0x14), Ok(0x5a5a));
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 1,
+            misses: 2,
+            evictions: 0,
+            writebacks: 1,
+        }
+    );
+
+    //0x0, 0x80, 0x100 share one set, lru evicts 0x80 and writes it back
+    cache.reset_stats();
+    cpu.write_u8(&0x80, 0x11).unwrap();
+    cpu.read_u8(&0x0).unwrap();
+    cpu.read_u8(&0x100).unwrap();
+    assert!(!cache.is_cached(0x80));
+    assert_eq!(dma.read_u8(&0x80), Ok(0x11));
+    assert_eq!(cache.stats().evictions, 1);
+    assert_eq!(cache.stats().writebacks, 1);
+    //accesses crossing lines
+    cpu.write_bytes(&0x3c, &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+    let mut data = [0; 8];
+    cpu.read_bytes(&0x3c, &mut data).unwrap();
+    assert_eq!(data, [1, 2, 3, 4, 5, 6, 7, 8]);
+    cache.flush_all().unwrap();
+    assert!(!cache.is_cached(0x3c));
+    dma.read_bytes(&0x3c, &mut data).unwrap();
+    assert_eq!(data, [1, 2, 3, 4, 5, 6, 7, 8]);
+
+    //replaced, moved and deleted regions are flushed, lines of the old region never hit
+    cpu.write_u32(&0x40, 0x1234).unwrap();
+    let fresh = GHEAP.alloc(0x1000, 0x1000).unwrap();
+    let cpu = views.get_view_mut("cpu").unwrap();
+    cpu.replace_region("memory", &Region::remap(0x0, &fresh))
+        .unwrap();
+    assert!(!cache.is_cached(0x40));
+    assert_eq!(
+        U32Access::read(memory.deref(), &(memory.info.base + 0x40)),
+        0x1234
+    );
+    assert_eq!(cpu.read_u32(&0x40), Ok(0));
+    cpu.write_u32(&0x40, 0x5678).unwrap();
+    cpu.move_region("memory", 0x1000).unwrap();
+    assert!(!cache.is_cached(0x40));
+    assert_eq!(cpu.read_u32(&0x1040), Ok(0x5678));
+    cpu.write_u32(&0x1040, 0x9abc).unwrap();
+    cpu.delete_region("memory");
+    assert!(!cache.is_cached(0x1040));
+    assert_eq!(
+        U32Access::read(fresh.deref(), &(fresh.info.base + 0x40)),
+        0x9abc
+    );
+    cpu.add_region("memory", &Region::remap(0x0, &memory))
+        .unwrap();
+    let mut other = Space::new();
+    other
+        .add_region("memory", &Region::remap(0x0, &fresh))
+        .unwrap();
+    other.set_cache(&cache);
+    cpu.write_u32(&0x60, 0x42).unwrap();
+    assert_eq!(other.read_u32(&0x60), Ok(0));
+    cache.flush_all().unwrap();
+
+    //io regions are never cached
+    let mut regs = crate::regmap::RegMap::new("regs", 0x20);
+    regs.add(crate::regmap::Register::new("data", 0x0, 32))
+        .unwrap();
+    views
+        .get_view_mut("cpu")
+        .unwrap()
+        .add_region("regs", &regs.into_region(0x2000))
+        .unwrap();
+    let cpu = views.get_view("cpu").unwrap();
+    let dma = views.get_view("dma").unwrap();
+    cache.reset_stats();
+    cpu.write_u32(&0x2000, 0x77).unwrap();
+    assert_eq!(cpu.read_u32(&0x2000), Ok(0x77));
+    assert!(!cache.is_cached(0x2000));
+    assert_eq!(cache.stats(), CacheStats::default());
+
+    //write through keeps memory up to date and does not allocate
+    let wt = Rc::new(
+        Cache::new(CacheConfig {
+            write: WritePolicy::WriteThrough,
+            ..config
+        })
+        .unwrap(),
+    );
+    let mut space = Space::new();
+    space
+        .add_region("memory", &Region::remap(0x0, &memory))
+        .unwrap();
+    space.set_cache(&wt);
+    space.write_u16(&0x200, 0x1234).unwrap();
+    assert!(!wt.is_cached(0x200));
+    assert_eq!(dma.read_u16(&0x200), Ok(0x1234));
+    assert_eq!(space.read_u16(&0x200), Ok(0x1234));
+    space.write_u16(&0x202, 0x5678).unwrap();
+    assert!(!wt.is_dirty(0x200));
+    assert_eq!(dma.read_u32(&0x200), Ok(0x5678_1234));
+    assert_eq!(wt.stats().hits, 1);
+}
@@ -0,0 +1,77 @@
+use crate::space::{Error, Space, Tracer};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::rc::Rc;
+
+//Views hold one Space per bus master, all views share one Tracer
+pub struct Views {
+    views: HashMap<CString, Box<Space>>,
+    tracer: Rc<Tracer>,
+}
+
+impl Views {
+    pub fn new() -> Views {
+        Views {
+            views: HashMap::new(),
+            tracer: Rc::new(Tracer::new()),
+        }
+    }
+
+    pub fn add_view(&mut self, name: &str) -> Result<&mut Space, Error> {
+        let key = CString::new(name).unwrap();
+        if self.views.contains_key(&key) {
+            return Err(Error::Renamed(
+                name.to_string(),
+                format!("view name {} has existed!", name),
+            ));
+        }
+        let mut space = Space::new();
+        space.set_tracer(name, &self.tracer);
+        Ok(self.views.entry(key).or_insert_with(|| Box::new(space)))
+    }
+
+    pub fn get_view(&self, name: &str) -> Option<&Space> {
+        self.views
+            .get(&CString::new(name).unwrap())
+            .map(|s| s.as_ref())
+    }
+
+    pub fn get_view_mut(&mut self, name: &str) -> Option<&mut Space> {
+        self.views
+            .get_mut(&CString::new(name).unwrap())
+            .map(|s| s.as_mut())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self
+            .views
+            .keys()
+            .map(|k| k.to_str().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    pub fn tracer(&self) -> &Rc<Tracer> {
+        &self.tracer
+    }
+
+    pub fn issuer(&self, addr: &u64) -> Option<&str> {
+        self.issuer_cstr(addr).map(|k| k.to_str().unwrap())
+    }
+
+    //for ffi, the returned str lives as long as the view
+    pub(crate) fn issuer_cstr(&self, addr: &u64) -> Option<&CStr> {
+        let view = self.tracer.issuer(addr)?;
+        self.views
+            .keys()
+            .find(|k| k.to_str().unwrap() == view)
+            .map(|k| k.as_c_str())
+    }
+}
+
+impl Default for Views {
+    fn default() -> Views {
+        Views::new()
+    }
+}
@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use crate::memory::prelude::{Endian, Layout, LayoutAccess};
 use crate::memory::region::{BytesAccess, Heap, Region, SizedAccess, U16Access};
 use crate::virtio::{DESC_F_NEXT, DESC_F_WRITE};
 use std::cell::RefCell;
@@ -35,7 +36,7 @@ pub struct QueueSetting {
     pub max_queue_size: u16,
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default, Layout)]
 #[repr(C)]
 pub struct DescMeta {
     pub addr: u64,
@@ -44,7 +45,7 @@ pub struct DescMeta {
     pub next: u16,
 }
 
-#[derive(Default)]
+#[derive(Default, Layout)]
 #[repr(C)]
 pub struct RingMetaHeader {
     flags: u16,
@@ -53,7 +54,7 @@ pub struct RingMetaHeader {
 
 pub type RingAvailMetaElem = u16;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default, Layout)]
 #[repr(C)]
 pub struct RingUsedMetaElem {
     pub id: u32,
@@ -112,11 +113,11 @@ impl Queue {
 
     fn desc_addr(&self, idx: u16) -> Result<u64> {
         self.check_idx(idx)?;
-        Ok(self.get_desc_addr() + (idx as usize * mem::size_of::<DescMeta>()) as u64)
+        Ok(self.get_desc_addr() + (idx as usize * DescMeta::SIZE) as u64)
     }
 
     pub fn desc_table_size(&self) -> usize {
-        mem::size_of::<DescMeta>() * self.get_queue_size()
+        DescMeta::SIZE * self.get_queue_size()
     }
 
     pub fn avail_ring_size(&self) -> usize {
@@ -124,7 +125,7 @@ impl Queue {
     }
 
     pub fn used_ring_size(&self) -> usize {
-        mem::size_of::<RingUsedMetaElem>() * self.get_queue_size()
+        RingUsedMetaElem::SIZE * self.get_queue_size()
     }
 
     fn check_range(&self, base: u64, size: u64) -> bool {
@@ -136,7 +137,7 @@ impl Queue {
 
     fn avail_elem_addr(&self, idx: u16) -> u64 {
         self.get_avail_addr()
-            + mem::size_of::<RingMetaHeader>() as u64
+            + RingMetaHeader::SIZE as u64
             + (idx as usize % self.get_queue_size()) as u64
                 * mem::size_of::<RingAvailMetaElem>() as u64
     }
@@ -215,14 +216,15 @@ impl Queue {
     }
 
     pub fn set_desc(&self, idx: u16, desc: &DescMeta) -> Result<()> {
-        SizedAccess::write(self.memory.deref(), &self.desc_addr(idx)?, desc);
+        LayoutAccess::write(self.memory.deref(), &self.desc_addr(idx)?, desc)?;
         Ok(())
     }
 
     pub fn get_desc(&self, idx: u16) -> Result<DescMeta> {
-        let mut desc = DescMeta::default();
-        SizedAccess::read(self.memory.deref(), &self.desc_addr(idx)?, &mut desc);
-        Ok(desc)
+        Ok(LayoutAccess::read(
+            self.memory.deref(),
+            &self.desc_addr(idx)?,
+        )?)
     }
 
     pub fn get_avail_idx(&self) -> Result<Wrapping<u16>> {
@@ -261,24 +263,20 @@ impl Queue {
 
     fn used_elem_addr(&self, idx: u16) -> u64 {
         self.get_used_addr()
-            + mem::size_of::<RingMetaHeader>() as u64
-            + (idx as usize % self.get_queue_size()) as u64
-                * mem::size_of::<RingUsedMetaElem>() as u64
+            + RingMetaHeader::SIZE as u64
+            + (idx as usize % self.get_queue_size()) as u64 * RingUsedMetaElem::SIZE as u64
     }
 
     pub fn get_used_elem(&self, used_idx: u16) -> Result<RingUsedMetaElem> {
-        let mut elem = RingUsedMetaElem::default();
-        SizedAccess::read(
+        Ok(LayoutAccess::read(
             self.memory.deref(),
             &self.used_elem_addr(used_idx),
-            &mut elem,
-        );
-        Ok(elem)
+        )?)
     }
 
     fn set_used_elem(&self, used_idx: u16, elem: &RingUsedMetaElem) -> Result<()> {
         self.check_idx(elem.id as u16)?;
-        SizedAccess::write(self.memory.deref(), &self.used_elem_addr(used_idx), elem);
+        LayoutAccess::write(self.memory.deref(), &self.used_elem_addr(used_idx), elem)?;
         Ok(())
     }
 
@@ -309,7 +307,7 @@ impl Queue {
         }
         if !self.check_range(
             self.get_avail_addr(),
-            (self.avail_ring_size() + mem::size_of::<RingMetaHeader>()) as u64,
+            (self.avail_ring_size() + RingMetaHeader::SIZE) as u64,
         ) {
             return Err(Error::InvalidInit(format!(
                 "invalid avail addr {:#016x}",
@@ -318,7 +316,7 @@ impl Queue {
         }
         if !self.check_range(
             self.get_used_addr(),
-            (self.used_ring_size() + mem::size_of::<RingMetaHeader>()) as u64,
+            (self.used_ring_size() + RingMetaHeader::SIZE) as u64,
         ) {
             return Err(Error::InvalidInit(format!(
                 "invalid used addr {:#016x}",
@@ -341,8 +339,8 @@ impl Queue {
     }
 
     pub fn avail_iter(&self) -> Result<AvailIter> {
-        let mut header = RingMetaHeader { flags: 0, idx: 0 };
-        SizedAccess::read(self.memory.deref(), &self.get_avail_addr(), &mut header);
+        let header: RingMetaHeader =
+            LayoutAccess::read(self.memory.deref(), &self.get_avail_addr())?;
         Ok(AvailIter::new(
             self,
             Wrapping(header.idx),
@@ -590,8 +588,12 @@ impl DefaultQueueServer {
 impl QueueServer for DefaultQueueServer {
     fn init_queue(&mut self, queue: &Queue) -> Result<()> {
         let desc_region = self.heap.alloc(queue.desc_table_size() as u64, 8)?;
-        let avail_region = self.heap.alloc(queue.avail_ring_size() as u64, 2)?;
-        let used_region = self.heap.alloc(queue.used_ring_size() as u64, 4)?;
+        let avail_region = self
+            .heap
+            .alloc((queue.avail_ring_size() + RingMetaHeader::SIZE) as u64, 2)?;
+        let used_region = self
+            .heap
+            .alloc((queue.used_ring_size() + RingMetaHeader::SIZE) as u64, 4)?;
         queue.set_desc_addr(desc_region.info.base);
         queue.set_avail_addr(avail_region.info.base);
         queue.set_used_addr(used_region.info.base);
@@ -9,29 +9,133 @@ import "DPI-C" function chandle tsv_new_locked_allocator
     input longint unsigned base,
     input longint unsigned size
 );
+import "DPI-C" function chandle tsv_new_policy_allocator
+(
+    input longint unsigned base,
+    input longint unsigned size,
+    input int unsigned policy
+);
+import "DPI-C" function chandle tsv_new_policy_locked_allocator
+(
+    input longint unsigned base,
+    input longint unsigned size,
+    input int unsigned policy
+);
+import "DPI-C" function chandle tsv_new_pool_allocator();
+import "DPI-C" function int tsv_add_pool
+(
+    input chandle allocator,
+    input string name,
+    input longint unsigned base,
+    input longint unsigned size,
+    input bit dma,
+    input bit cacheable,
+    input int unsigned policy
+);
+import "DPI-C" function longint unsigned tsv_alloc_pool_addr
+(
+    input chandle allocator,
+    input longint unsigned size,
+    input longint unsigned align,
+    input longint unsigned limit,
+    input int dma,
+    input int cacheable,
+    input string pool
+);
 import "DPI-C" function longint unsigned tsv_alloc_addr
 (
     input chandle  allocator,
     input longint unsigned size,
     input longint unsigned align
 );
-import "DPI-C" function void tsv_free_addr
+import "DPI-C" function int tsv_free_addr
 (
     input chandle allocator,
     input longint unsigned addr
 );
+import "DPI-C" function void tsv_allocator_set_strict
+(
+    input chandle allocator,
+    input bit strict
+);
+import "DPI-C" function void tsv_allocator_set_seed
+(
+    input chandle allocator,
+    input longint unsigned seed
+);
+import "DPI-C" function void tsv_allocator_save(input chandle allocator, input string path);
+import "DPI-C" function chandle tsv_allocator_restore(input string path, input bit locked);
+import "DPI-C" function void tsv_allocator_start_log(input chandle allocator, input string path);
+import "DPI-C" function void tsv_allocator_stop_log(input chandle allocator);
+import "DPI-C" function int tsv_realloc_addr
+(
+    input chandle allocator,
+    input longint unsigned addr,
+    input longint unsigned size,
+    input longint unsigned align,
+    output longint unsigned new_addr
+);
+import "DPI-C" function int tsv_alloc_addr_at
+(
+    input chandle allocator,
+    input longint unsigned base,
+    input longint unsigned size
+);
+import "DPI-C" function int tsv_reserve_addr
+(
+    input chandle allocator,
+    input longint unsigned base,
+    input longint unsigned size
+);
+import "DPI-C" function longint unsigned tsv_allocator_total_free(input chandle allocator);
+import "DPI-C" function longint unsigned tsv_allocator_largest_free(input chandle allocator);
+import "DPI-C" function longint unsigned tsv_allocator_fragments(input chandle allocator);
+import "DPI-C" function longint unsigned tsv_allocator_high_water(input chandle allocator);
+import "DPI-C" function longint unsigned tsv_allocator_live(input chandle allocator);
+import "DPI-C" function void tsv_allocator_report(input chandle allocator);
 
 
 import "DPI-C" function chandle tsv_space();
+import "DPI-C" function void tsv_free_space(input chandle space);
 import "DPI-C" function chandle tsv_get_region(input chandle space , input string name);
+import "DPI-C" function chandle tsv_replace_region(input chandle space, input string name, input chandle region);
+import "DPI-C" function chandle tsv_move_region(input chandle space, input string name, input longint unsigned base);
+import "DPI-C" function longint unsigned tsv_space_region_count(input chandle space);
+import "DPI-C" function string tsv_space_region_name(input chandle space, input longint unsigned idx);
+import "DPI-C" function longint unsigned tsv_space_region_base(input chandle space, input longint unsigned idx);
+import "DPI-C" function longint unsigned tsv_space_region_size(input chandle space, input longint unsigned idx);
+import "DPI-C" function longint unsigned tsv_space_free_gap(input chandle space, input longint unsigned size, input longint unsigned align);
+import "DPI-C" function void tsv_space_export_json(input chandle space, input string path);
+import "DPI-C" function longint tsv_space_import_regmap(input chandle space, input string path, input int unsigned format);
+import "DPI-C" function void tsv_space_export_csv(input chandle space, input string path);
+import "DPI-C" function chandle tsv_add_region_with_attr(input chandle space, input string name, input chandle region, input bit readable, input bit writable);
+import "DPI-C" function chandle tsv_views();
+import "DPI-C" function chandle tsv_add_view(input chandle views, input string name);
+import "DPI-C" function chandle tsv_get_view(input chandle views, input string name);
+import "DPI-C" function void tsv_views_trace(input chandle views, input bit enable);
+import "DPI-C" function string tsv_views_issuer(input chandle views, input longint unsigned addr);
 import "DPI-C" function chandle tsv_alloc_region(input chandle heap, input longint unsigned size, input longint unsigned align);
+import "DPI-C" function chandle tsv_alloc_storage_region(input chandle heap, input longint unsigned size, input longint unsigned align, input int unsigned storage);
 import "DPI-C" function chandle tsv_root_region(input longint unsigned size, input longint unsigned align);
 import "DPI-C" function chandle tsv_lazy_root_region(input longint unsigned size, input longint unsigned align);
+import "DPI-C" function chandle tsv_root_storage_region(input longint unsigned size, input longint unsigned align, input int unsigned storage);
+import "DPI-C" function chandle tsv_alloc_region_tagged(input chandle heap, input longint unsigned size, input longint unsigned align, input string tag, input string file, input int unsigned line);
+import "DPI-C" function chandle tsv_root_region_tagged(input longint unsigned size, input longint unsigned align, input string tag, input string file, input int unsigned line);
+import "DPI-C" function longint unsigned tsv_heap_leak_report(input chandle heap);
+import "DPI-C" function int tsv_realloc_region(input chandle heap, input chandle region, input longint unsigned size, input longint unsigned align);
+import "DPI-C" function chandle tsv_alloc_region_at(input chandle heap, input longint unsigned base, input longint unsigned size);
+import "DPI-C" function chandle tsv_root_region_at(input longint unsigned base, input longint unsigned size);
+import "DPI-C" function void tsv_heap_reserve(input chandle heap, input longint unsigned base, input longint unsigned size);
 import "DPI-C" function chandle tsv_map_region(input chandle region, input longint unsigned base);
 import "DPI-C" function chandle tsv_map_region_partial(input chandle region, input longint unsigned base, input longint unsigned offset, input longint unsigned size);
 import "DPI-C" function chandle tsv_heap(input chandle region);
 import "DPI-C" function void tsv_free_region(input chandle region);
 import "DPI-C" function void tsv_free_heap(input chandle heap);
+import "DPI-C" function chandle tsv_scratch_heap(input longint unsigned size);
+import "DPI-C" function void tsv_heap_teardown(input chandle heap);
+import "DPI-C" function void tsv_heap_reset(input chandle heap);
+import "DPI-C" function int tsv_heaps_reset();
+import "DPI-C" function void tsv_heap_report(input chandle heap);
 import "DPI-C" function longint unsigned tsv_region_base(input chandle region);
 import "DPI-C" function longint unsigned tsv_region_size(input chandle region);
 
@@ -52,4 +156,30 @@ import "DPI-C" function void tsv_space_read_u8(input chandle  space, input longi
 import "DPI-C" function void tsv_space_read_u16(input chandle  space, input longint unsigned addr, output shortint unsigned data);
 import "DPI-C" function void tsv_space_read_u32(input chandle  space, input longint unsigned addr, output int unsigned data);
 import "DPI-C" function void tsv_space_read_u64(input chandle  space, input longint unsigned addr, output longint unsigned data);
+import "DPI-C" function void tsv_space_set_latency(input chandle space, input string name, input longint unsigned read, input longint unsigned write);
+import "DPI-C" function longint unsigned tsv_space_request_read(input chandle space, input longint unsigned addr, input int unsigned size);
+import "DPI-C" function longint unsigned tsv_space_request_write(input chandle space, input longint unsigned addr, input longint unsigned data, input int unsigned size);
+import "DPI-C" function void tsv_space_tick(input chandle space, input longint unsigned ticks);
+import "DPI-C" function longint unsigned tsv_space_attach(input chandle space);
+import "DPI-C" function bit tsv_space_complete(input chandle space, input longint unsigned token);
+import "DPI-C" function bit tsv_space_poll(input chandle space, input longint unsigned token, output longint unsigned data);
+import "DPI-C" function chandle tsv_perf_model(input longint unsigned latency, input longint unsigned bandwidth);
+import "DPI-C" function void tsv_perf_set_timing(input chandle perf, input string name, input longint unsigned latency, input longint unsigned bandwidth);
+import "DPI-C" function void tsv_space_set_perf(input chandle space, input chandle perf);
+import "DPI-C" function longint unsigned tsv_perf_cycles(input chandle perf);
+import "DPI-C" function longint unsigned tsv_perf_accesses(input chandle perf, input string name);
+import "DPI-C" function void tsv_perf_reset(input chandle perf);
+import "DPI-C" function void tsv_perf_report(input chandle perf);
+import "DPI-C" function chandle tsv_cache(input longint unsigned size, input longint unsigned ways, input longint unsigned line, input int unsigned replace, input int unsigned write, input bit buffered);
+import "DPI-C" function void tsv_space_set_cache(input chandle space, input chandle cache);
+import "DPI-C" function longint unsigned tsv_cache_hits(input chandle cache);
+import "DPI-C" function longint unsigned tsv_cache_misses(input chandle cache);
+import "DPI-C" function longint unsigned tsv_cache_evictions(input chandle cache);
+import "DPI-C" function longint unsigned tsv_cache_writebacks(input chandle cache);
+import "DPI-C" function void tsv_cache_clean(input chandle cache, input longint unsigned addr, input longint unsigned size);
+import "DPI-C" function void tsv_cache_invalidate(input chandle cache, input longint unsigned addr, input longint unsigned size);
+import "DPI-C" function void tsv_cache_flush(input chandle cache, input longint unsigned addr, input longint unsigned size);
+import "DPI-C" function void tsv_tick(input longint unsigned cycles);
+import "DPI-C" function longint unsigned tsv_now();
+import "DPI-C" function bit tsv_cancel(input longint unsigned id);
 `endif
\ No newline at end of file